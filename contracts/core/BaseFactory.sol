@@ -58,6 +58,12 @@ abstract contract BaseFactory is CloneFactory, ReentrancyGuard {
         _;
     }
 
+    /// @notice Блокирует вызов, пока модуль приостановлен в ядре
+    modifier whenModuleActive() {
+        if (core.isModulePaused(MODULE_ID)) revert ModulePaused();
+        _;
+    }
+
     /// @dev Копирует сервис из основного модуля в экземпляр, если сервис существует
     /// @param instanceId Идентификатор экземпляра
     /// @param serviceName Имя сервиса
@@ -25,6 +25,7 @@ library CoreDefs {
     bytes32 internal constant AUTOMATION_ROLE = keccak256('AUTOMATION_ROLE');
     bytes32 internal constant GOVERNOR_ROLE = keccak256('GOVERNOR_ROLE');
     bytes32 internal constant AUTHOR_ROLE = keccak256('AUTHOR_ROLE');
+    bytes32 internal constant ARBITER_ROLE = keccak256('ARBITER_ROLE');
 
     // ------------------------------------------------------------------
     // Common time constants
@@ -32,12 +32,16 @@ contract CoreSystem {
     mapping(bytes32 => address) private coreServices;
     mapping(bytes32 => mapping(bytes32 => address)) private moduleServices;
 
+    // Аварийная остановка модулей
+    mapping(bytes32 => bool) private pausedModules;
+
     // События
     event RoleGranted(bytes32 indexed role, address indexed account, address indexed sender);
     event RoleRevoked(bytes32 indexed role, address indexed account, address indexed sender);
     event ServiceRegistered(bytes32 indexed serviceId, address serviceAddress, bytes32 moduleId);
     event ModuleRegistered(bytes32 indexed moduleId, string serviceAlias, address serviceAddress);
     event FeatureRegistered(bytes32 indexed featureId, address implementation, uint8 context);
+    event ModulePauseUpdated(bytes32 indexed moduleId, bool paused, address indexed sender);
     event FeatureUpgraded(
         bytes32 indexed featureId,
         address oldImplementation,
@@ -146,4 +150,15 @@ contract CoreSystem {
     function getService(bytes32 moduleId, string calldata serviceAlias) external view returns (address) {
         return moduleServices[moduleId][keccak256(bytes(serviceAlias))];
     }
+
+    // === Аварийная остановка ===
+
+    function setModulePaused(bytes32 moduleId, bool paused) external onlyOperator {
+        pausedModules[moduleId] = paused;
+        emit ModulePauseUpdated(moduleId, paused, msg.sender);
+    }
+
+    function isModulePaused(bytes32 moduleId) external view returns (bool) {
+        return pausedModules[moduleId];
+    }
 }
@@ -19,6 +19,7 @@ error NotOperator();
 error NotRelayer();
 error NotFactoryAdmin();
 error NotGovernor();
+error NotArbiter();
 error NotSeller();
 error Unauthorized();
 error NotOwner();
@@ -32,10 +33,11 @@ error ServiceAlreadyRegistered();
 error ServiceNotFound();
 error InvalidModule();
 error ModuleNotRegistered();
+error ModulePaused();
 
 // Ошибки токенов и платежей
 error NothingToWithdraw();
-error InsufficientBalance();
+error InsufficientBalance(uint256 required, uint256 available);
 error RefundDisabled();
 error TransferFailed();
 error NotAllowedToken();
@@ -67,6 +69,8 @@ error InvalidEventKind();
 
 // Ошибки подписи и транзакций
 error Expired();
+error DeadlineExpired(uint256 deadline, uint256 timestamp);
+error NonceAlreadyUsed();
 error InvalidChain();
 error PermitFailed();
 error LimitExceeded();
@@ -78,7 +82,7 @@ error GasZero();
 
 // Ошибки рынка
 error AlreadyPurchased();
-error PriceExceedsMaximum();
+error PriceExceedsMaximum(uint256 maximum, uint256 actual);
 error NotListed();
 error NotCreator();
 error NotTemplateAdmin();
@@ -0,0 +1,39 @@
+// SPDX-License-Identifier: MIT
+pragma solidity ^0.8.20;
+
+import '../external/AggregatorV3Interface.sol';
+
+/// @title Settable Chainlink-style price feed for tests
+contract MockPriceFeed is AggregatorV3Interface {
+    uint8 public immutable override decimals;
+    int256 private answer;
+    uint256 private updatedAt;
+    uint80 private roundId;
+
+    constructor(uint8 decimals_, int256 answer_) {
+        decimals = decimals_;
+        setAnswer(answer_, block.timestamp);
+    }
+
+    function setAnswer(int256 answer_, uint256 updatedAt_) public {
+        answer = answer_;
+        updatedAt = updatedAt_;
+        roundId += 1;
+    }
+
+    function description() external pure override returns (string memory) {
+        return 'MockPriceFeed';
+    }
+
+    function version() external pure override returns (uint256) {
+        return 1;
+    }
+
+    function getRoundData(uint80) external view override returns (uint80, int256, uint256, uint256, uint80) {
+        return (roundId, answer, updatedAt, updatedAt, roundId);
+    }
+
+    function latestRoundData() external view override returns (uint80, int256, uint256, uint256, uint80) {
+        return (roundId, answer, updatedAt, updatedAt, roundId);
+    }
+}
@@ -0,0 +1,29 @@
+// SPDX-License-Identifier: MIT
+pragma solidity ^0.8.28;
+
+import {ISettlementHook} from '../modules/marketplace/Marketplace.sol';
+
+/// @notice Settlement hook used in tests; counts the settlements it is notified about and can be made to revert
+contract SettlementHookMock is ISettlementHook {
+    uint256 public calls;
+    bool public shouldRevert;
+
+    event Settled(bytes32 indexed listingHash, address indexed buyer, address indexed seller, uint256 paymentAmount);
+
+    function setShouldRevert(bool value) external {
+        shouldRevert = value;
+    }
+
+    function onSettlement(
+        bytes32 listingHash,
+        address buyer,
+        address seller,
+        bytes32,
+        address,
+        uint256 paymentAmount
+    ) external override {
+        require(!shouldRevert, 'SettlementHookMock: rejected');
+        calls += 1;
+        emit Settled(listingHash, buyer, seller, paymentAmount);
+    }
+}
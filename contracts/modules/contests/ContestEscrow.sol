@@ -27,14 +27,87 @@ contract ContestEscrow is ReentrancyGuard {
     uint256 public constant GRACE_PERIOD = 30 days;
 
     uint8 public constant maxWinnersPerTx = 20;
+    uint8 public constant MAX_SPONSORSHIPS = 50;
+    uint16 public constant MIN_SPONSORSHIP_BPS = 100; // sponsorship floor as a share of the slot's current amount
+    uint16 public constant MAX_PLATFORM_FEE_BPS = 2_000;
+    uint8 public constant MAX_JUDGES = 15;
+    uint32 public constant MAX_JUDGED_ENTRIES = 500; // keeps the on-chain tally within the block gas limit
     bytes32 public constant MODULE_ID = CoreDefs.CONTEST_MODULE_ID;
 
+    struct Sponsorship {
+        address sponsor;
+        uint256 prizeIndex;
+        uint256 amount;
+        bytes32 attributionHash;
+    }
+
+    Sponsorship[] public sponsorships;
+    mapping(uint256 => uint256) public sponsoredAmount; // prizeIndex => total sponsored
+    mapping(uint256 => uint256) public minSponsorship; // prizeIndex => creator-set minimum sponsorship
+    mapping(uint256 => bool) public sponsorshipRefunded; // sponsorship index => claimed after cancellation
+
+    uint256 public entryFee; // paid in the token of prize slot 0 and added to that prize
+    uint16 public platformFeeBps; // share of collected entry fees routed to the Treasury service
+    uint16 public withdrawalRefundBps; // share of the entry fee returned when a contestant withdraws
+    uint256 public entryCount;
+    uint32 public maxEntries; // 0 = unlimited
+    uint32 public maxEntriesPerContestant; // 0 = unlimited
+    uint256 public entryFeesCollected;
+    bool public cancelled;
+    mapping(address => uint256) public entriesOf;
+    mapping(address => uint256) public entryFeesPaid;
+    uint256 public entryStake; // refundable deposit per entry in the token of prize slot 0, slashed for spam
+    uint256 public stakesHeld;
+    mapping(address => uint256) public stakeOf;
+    mapping(address => bool) public flagged;
+
+    // Optional linear vesting of monetary prizes, starting when winners are set
+    uint40 public vestingCliff;
+    uint40 public vestingDuration; // 0 = prizes are paid out immediately
+    uint40 public vestingStart;
+    mapping(uint256 => uint256) public vestedPrizeAmount; // prize slot => amount under vesting
+    mapping(uint256 => uint256) public vestedPrizeClaimed; // prize slot => amount already claimed
+    address[] public contestants;
+    mapping(address => uint256) private contestantIndex; // index + 1
+
+    address[] public judges;
+    uint8 public judgeQuorum;
+    uint40 public votingEndsAt;
+    uint256 public judgesVoted;
+    mapping(address => bool) public isJudge;
+    mapping(address => bool) public hasJudgeVoted;
+    mapping(address => mapping(address => uint32)) public judgeScores; // judge => contestant => score
+    mapping(address => uint256) public totalScores; // contestant => sum of judge scores
+
     event MonetaryPrizePaid(address indexed to, uint256 amount);
     event PromoPrizeIssued(uint8 indexed slot, address indexed to, string uri);
     event EmergencyWithdraw(address indexed creator, uint256 timestamp);
     event ContestCancelled(address indexed creator, uint256 timestamp);
     event ContestFinalized(address[] winners);
     event GasRefunded(address indexed to, uint256 amount);
+    event ContestSponsored(
+        address indexed sponsor,
+        uint256 indexed prizeIndex,
+        address token,
+        uint256 amount,
+        bytes32 attributionHash
+    );
+    event SponsorRefunded(address indexed sponsor, uint256 indexed prizeIndex, uint256 amount);
+    event MinSponsorshipUpdated(uint256 indexed prizeIndex, uint256 amount);
+    event EntryFeeUpdated(uint256 fee, uint16 platformFeeBps);
+    event EntryLimitsUpdated(uint32 maxEntries, uint32 maxEntriesPerContestant);
+    event WithdrawalRefundUpdated(uint16 refundBps);
+    event EntryWithdrawn(address indexed contestant, uint256 entries, uint256 refund, uint256 forfeited);
+    event ContestEntered(address indexed contestant, uint256 indexed entryId, uint256 fee);
+    event PlatformFeeCollected(address indexed treasury, uint256 amount);
+    event EntryFeeRefunded(address indexed contestant, uint256 amount);
+    event EntryStakeUpdated(uint256 stake);
+    event EntryFlagged(address indexed contestant, uint256 slashed, address indexed recipient);
+    event StakeReturned(address indexed contestant, uint256 amount);
+    event PrizeVestingUpdated(uint40 cliff, uint40 duration);
+    event VestedPrizeClaimed(uint256 indexed prizeIndex, address indexed winner, uint256 amount);
+    event JudgesConfigured(address[] judges, uint8 quorum, uint40 votingEndsAt);
+    event EntryScored(address indexed judge, address indexed contestant, uint32 score);
 
     modifier onlyCreator() {
         if (msg.sender != creator) revert NotCreator();
@@ -68,12 +141,28 @@ contract ContestEscrow is ReentrancyGuard {
     /// @param _winners List of winner addresses
     /// @param priorityCap Priority fee cap for gas refund calculation
     function finalize(address[] calldata _winners, uint256 priorityCap) external nonReentrant onlyCreator {
+        // judged contests pick their winners through tally(); the creator may only continue batching
+        if (judges.length > 0 && winners.length == 0) revert Forbidden();
+        _finalize(_winners, priorityCap);
+    }
+
+    /// @notice Pay the highest-scored contestants once quorum is reached or voting has closed
+    function tally() external nonReentrant {
+        if (judges.length == 0 || winners.length != 0) revert InvalidState();
+        if (judgesVoted < judgeQuorum && block.timestamp <= votingEndsAt) revert DeadlineNotReached();
+
+        _finalize(_rankContestants(), 0);
+    }
+
+    function _finalize(address[] memory _winners, uint256 priorityCap) internal {
         if (finalized) revert ContestAlreadyFinalized();
         if (_winners.length != prizes.length) revert WrongWinnersCount();
 
         if (winners.length == 0) {
             // Store winners on first call
             winners = _winners;
+            _collectPlatformFee();
+            if (vestingDuration > 0) vestingStart = uint40(block.timestamp);
         } else {
             // Ensure winners array is not changed on subsequent calls
             for (uint256 i = 0; i < winners.length && i < _winners.length; i++) {
@@ -96,17 +185,21 @@ contract ContestEscrow is ReentrancyGuard {
             if (p.prizeType == PrizeType.MONETARY) {
                 uint256 amount = p.distribution == 0 ? p.amount : _computeDescending(p.amount, uint8(i));
 
-                if (p.token == address(0)) {
+                if (vestingDuration > 0) {
+                    // the winner claims the prize over time through claimVestedPrize
+                    vestedPrizeAmount[i] = amount;
+                } else if (p.token == address(0)) {
                     // Handle native ETH
-                    if (address(this).balance < amount) revert InsufficientBalance();
+                    if (address(this).balance < amount) revert InsufficientBalance(amount, address(this).balance);
                     (bool success, ) = payable(winners[i]).call{value: amount}('');
                     if (!success) revert TransferFailed();
                 } else {
                     // Handle ERC20 tokens
-                    if (IERC20(p.token).balanceOf(address(this)) < amount) revert InsufficientBalance();
+                    uint256 tokenBalance = IERC20(p.token).balanceOf(address(this));
+                    if (tokenBalance < amount) revert InsufficientBalance(amount, tokenBalance);
                     IERC20(p.token).safeTransfer(winners[i], amount);
                 }
-                emit MonetaryPrizePaid(winners[i], amount);
+                if (vestingDuration == 0) emit MonetaryPrizePaid(winners[i], amount);
             } else {
                 emit PromoPrizeIssued(uint8(i), winners[i], p.uri);
             }
@@ -144,23 +237,31 @@ contract ContestEscrow is ReentrancyGuard {
     }
 
     /// @notice Cancel the contest and return all funds to the creator
-    function cancel() external onlyCreator {
-        if (finalized) revert ContestAlreadyFinalized();
+    /// @dev Operators may cancel on the creator's behalf once the deadline passed without entries
+    function cancel() external {
+        if (msg.sender != creator) {
+            if (!core.hasRole(CoreDefs.OPERATOR_ROLE, msg.sender)) revert NotCreator();
+            if (block.timestamp <= deadline || entryCount > 0) revert Forbidden();
+        }
+        if (finalized || processedWinners > 0 || winners.length != 0) revert ContestAlreadyFinalized();
 
         // Set finalized before external calls (CEI pattern)
         finalized = true;
+        cancelled = true;
 
-        // Return all monetary prizes to the creator
+        // Return all monetary prizes to the creator, sponsored shares and entry fees
+        // stay in escrow until sponsors and contestants claim them
         for (uint256 i = 0; i < prizes.length; i++) {
             PrizeInfo memory p = prizes[i];
-            if (p.prizeType == PrizeType.MONETARY && p.amount > 0) {
+            uint256 creatorAmount = p.amount - sponsoredAmount[i] - (i == 0 ? entryFeesCollected : 0);
+            if (p.prizeType == PrizeType.MONETARY && creatorAmount > 0) {
                 if (p.token == address(0)) {
                     // Handle native ETH
-                    (bool success, ) = payable(creator).call{value: p.amount}('');
+                    (bool success, ) = payable(creator).call{value: creatorAmount}('');
                     if (!success) revert TransferFailed();
                 } else {
                     // Handle ERC20 tokens
-                    IERC20(p.token).safeTransfer(creator, p.amount);
+                    IERC20(p.token).safeTransfer(creator, creatorAmount);
                 }
             }
         }
@@ -175,12 +276,335 @@ contract ContestEscrow is ReentrancyGuard {
         emit ContestCancelled(creator, block.timestamp);
     }
 
+    /// @notice Add funds to a monetary prize slot on behalf of a sponsor
+    /// @param prizeIndex Prize slot to co-fund
+    /// @param amount Amount of the prize token to add
+    /// @param attributionHash Hash of the sponsor's brand/logo attribution data
+    function sponsor(uint256 prizeIndex, uint256 amount, bytes32 attributionHash) external payable nonReentrant {
+        if (finalized || processedWinners > 0) revert ContestAlreadyFinalized();
+        if (prizeIndex >= prizes.length) revert InvalidParameters();
+        if (amount == 0) revert AmountZero();
+        if (sponsorships.length >= MAX_SPONSORSHIPS) revert LimitExceeded();
+
+        PrizeInfo storage p = prizes[prizeIndex];
+        if (p.prizeType != PrizeType.MONETARY) revert InvalidPrizeData();
+        if (amount < minSponsorshipFor(prizeIndex)) revert InvalidAmount();
+
+        uint256 received = amount;
+        if (p.token == address(0)) {
+            if (msg.value != amount) revert InvalidAmount();
+        } else {
+            if (msg.value != 0) revert InvalidAmount();
+            uint256 beforeBal = IERC20(p.token).balanceOf(address(this));
+            IERC20(p.token).safeTransferFrom(msg.sender, address(this), amount);
+            received = IERC20(p.token).balanceOf(address(this)) - beforeBal;
+            if (received == 0) revert ContestFundingMissing();
+        }
+
+        p.amount += received;
+        sponsoredAmount[prizeIndex] += received;
+        sponsorships.push(
+            Sponsorship({
+                sponsor: msg.sender,
+                prizeIndex: prizeIndex,
+                amount: received,
+                attributionHash: attributionHash
+            })
+        );
+
+        emit ContestSponsored(msg.sender, prizeIndex, p.token, received, attributionHash);
+    }
+
+    /// @notice Raise the minimum sponsorship accepted for a prize slot above the default floor
+    /// @param prizeIndex Prize slot
+    /// @param amount Minimum amount of the prize token per sponsorship
+    function setMinSponsorship(uint256 prizeIndex, uint256 amount) external onlyCreator {
+        if (prizeIndex >= prizes.length) revert InvalidParameters();
+        minSponsorship[prizeIndex] = amount;
+        emit MinSponsorshipUpdated(prizeIndex, amount);
+    }
+
+    /// @notice Smallest sponsorship a prize slot accepts, so that dust cannot exhaust MAX_SPONSORSHIPS
+    /// @param prizeIndex Prize slot
+    /// @return Larger of the creator-set minimum and MIN_SPONSORSHIP_BPS of the slot's current amount
+    function minSponsorshipFor(uint256 prizeIndex) public view returns (uint256) {
+        uint256 floor = (prizes[prizeIndex].amount * MIN_SPONSORSHIP_BPS) / 10_000;
+        uint256 configured = minSponsorship[prizeIndex];
+        return configured > floor ? configured : floor;
+    }
+
+    /// @notice Configure the fee contestants pay to enter
+    /// @param fee Entry fee in the token of prize slot 0 (0 = free entry)
+    /// @param platformBps Share of collected fees sent to the platform treasury at finalization
+    function setEntryFee(uint256 fee, uint16 platformBps) external onlyCreator {
+        if (finalized || entryCount > 0) revert Forbidden();
+        if (platformBps > MAX_PLATFORM_FEE_BPS) revert InvalidParameters();
+        if (fee > 0 && prizes[0].prizeType != PrizeType.MONETARY) revert InvalidPrizeData();
+
+        entryFee = fee;
+        platformFeeBps = platformBps;
+        emit EntryFeeUpdated(fee, platformBps);
+    }
+
+    /// @notice Require a refundable stake per entry, returned after the contest unless the entry is flagged
+    /// @param stake Stake in the token of prize slot 0 (0 = no stake)
+    function setEntryStake(uint256 stake) external onlyCreator {
+        if (finalized || entryCount > 0) revert Forbidden();
+        if (stake > 0 && prizes[0].prizeType != PrizeType.MONETARY) revert InvalidPrizeData();
+
+        entryStake = stake;
+        emit EntryStakeUpdated(stake);
+    }
+
+    /// @notice Flag a contestant's entries as spam, removing them and slashing their stake
+    /// @dev The stake goes to the Treasury service, or stays in prize slot 0 if none is registered;
+    /// entry fees paid are forfeited to the prize pool
+    /// @param contestant Contestant to flag
+    function flagEntry(address contestant) external onlyCreator nonReentrant {
+        if (finalized || winners.length != 0) revert ContestAlreadyFinalized();
+        uint256 entries = entriesOf[contestant];
+        if (entries == 0) revert NotFound();
+
+        flagged[contestant] = true;
+        entryCount -= entries;
+        entriesOf[contestant] = 0;
+        _removeContestant(contestant);
+
+        entryFeesCollected -= entryFeesPaid[contestant];
+        entryFeesPaid[contestant] = 0;
+
+        uint256 slashed = stakeOf[contestant];
+        stakeOf[contestant] = 0;
+        stakesHeld -= slashed;
+        address treasury = core.getService(MODULE_ID, 'Treasury');
+        if (slashed > 0) {
+            if (treasury == address(0)) {
+                prizes[0].amount += slashed;
+            } else {
+                _sendPrizeToken(prizes[0].token, treasury, slashed);
+            }
+        }
+
+        emit EntryFlagged(contestant, slashed, treasury == address(0) ? address(this) : treasury);
+    }
+
+    /// @notice Reclaim entry stakes once the contest is finalized or cancelled
+    function claimStake() external nonReentrant {
+        if (!finalized) revert InvalidState();
+        uint256 amount = stakeOf[msg.sender];
+        if (amount == 0) revert NothingToWithdraw();
+
+        stakeOf[msg.sender] = 0;
+        stakesHeld -= amount;
+        _sendPrizeToken(prizes[0].token, msg.sender, amount);
+
+        emit StakeReturned(msg.sender, amount);
+    }
+
+    /// @notice Pay monetary prizes out linearly instead of as a lump sum
+    /// @param cliff Seconds after winners are set before anything can be claimed
+    /// @param duration Seconds over which prizes vest in full (0 = immediate payout)
+    function setPrizeVesting(uint40 cliff, uint40 duration) external onlyCreator {
+        if (finalized || winners.length != 0) revert ContestAlreadyFinalized();
+        if (cliff > duration) revert InvalidParameters();
+
+        vestingCliff = cliff;
+        vestingDuration = duration;
+        emit PrizeVestingUpdated(cliff, duration);
+    }
+
+    /// @notice Amount of a vesting prize that its winner can claim now
+    /// @param prizeIndex Prize slot
+    /// @return Claimable amount
+    function claimableVestedPrize(uint256 prizeIndex) public view returns (uint256) {
+        uint256 total = vestedPrizeAmount[prizeIndex];
+        if (total == 0) return 0;
+
+        uint256 elapsed = block.timestamp - vestingStart;
+        if (elapsed < vestingCliff) return 0;
+        uint256 vested = elapsed >= vestingDuration ? total : (total * elapsed) / vestingDuration;
+        return vested - vestedPrizeClaimed[prizeIndex];
+    }
+
+    /// @notice Withdraw the vested part of a prize won by the caller
+    /// @param prizeIndex Prize slot
+    function claimVestedPrize(uint256 prizeIndex) external nonReentrant {
+        if (prizeIndex >= winners.length || winners[prizeIndex] != msg.sender) revert Unauthorized();
+        uint256 amount = claimableVestedPrize(prizeIndex);
+        if (amount == 0) revert NothingToWithdraw();
+
+        vestedPrizeClaimed[prizeIndex] += amount;
+        _sendPrizeToken(prizes[prizeIndex].token, msg.sender, amount);
+
+        emit VestedPrizeClaimed(prizeIndex, msg.sender, amount);
+    }
+
+    /// @notice Cap the total number of entries and the entries allowed per contestant
+    /// @dev Judged contests must keep a total cap of at most MAX_JUDGED_ENTRIES
+    /// @param total Maximum entries overall (0 = unlimited)
+    /// @param perContestant Maximum entries per address (0 = unlimited)
+    function setEntryLimits(uint32 total, uint32 perContestant) external onlyCreator {
+        if (finalized) revert ContestAlreadyFinalized();
+        if (total != 0 && total < entryCount) revert InvalidParameters();
+        if (judges.length > 0 && (total == 0 || total > MAX_JUDGED_ENTRIES)) revert InvalidParameters();
+
+        maxEntries = total;
+        maxEntriesPerContestant = perContestant;
+        emit EntryLimitsUpdated(total, perContestant);
+    }
+
+    /// @notice Enter the contest, paying the entry fee into prize slot 0
+    function enter() external payable nonReentrant {
+        if (finalized || processedWinners > 0) revert ContestAlreadyFinalized();
+        if (block.timestamp > deadline) revert DeadlineExpired(deadline, block.timestamp);
+        if (flagged[msg.sender]) revert Forbidden();
+
+        if (maxEntries != 0 && entryCount >= maxEntries) revert LimitExceeded();
+        if (maxEntriesPerContestant != 0 && entriesOf[msg.sender] >= maxEntriesPerContestant) revert LimitExceeded();
+
+        uint256 received = _collectEntryFee();
+        if (entriesOf[msg.sender] == 0) {
+            contestants.push(msg.sender);
+            contestantIndex[msg.sender] = contestants.length;
+        }
+        entryCount += 1;
+        entriesOf[msg.sender] += 1;
+
+        emit ContestEntered(msg.sender, entryCount, received);
+    }
+
+    /// @notice Set the share of the entry fee refunded when a contestant withdraws
+    /// @param refundBps Basis points refunded; the rest is forfeited to the prize pool
+    function setWithdrawalRefund(uint16 refundBps) external onlyCreator {
+        if (finalized || entryCount > 0) revert Forbidden();
+        if (refundBps > 10_000) revert InvalidParameters();
+
+        withdrawalRefundBps = refundBps;
+        emit WithdrawalRefundUpdated(refundBps);
+    }
+
+    /// @notice Withdraw all of the caller's entries before the deadline
+    /// @dev Not available once judges have started scoring
+    function withdrawEntry() external nonReentrant {
+        if (finalized || processedWinners > 0) revert ContestAlreadyFinalized();
+        if (block.timestamp > deadline) revert DeadlineExpired(deadline, block.timestamp);
+        if (judgesVoted > 0) revert Forbidden();
+        uint256 entries = entriesOf[msg.sender];
+        if (entries == 0) revert NotFound();
+
+        entryCount -= entries;
+        entriesOf[msg.sender] = 0;
+        _removeContestant(msg.sender);
+
+        uint256 paid = entryFeesPaid[msg.sender];
+        uint256 refund = (paid * withdrawalRefundBps) / 10_000;
+        entryFeesPaid[msg.sender] = 0;
+        // the forfeited remainder stays in prize slot 0 as regular prize funds
+        entryFeesCollected -= paid;
+        if (refund > 0) {
+            prizes[0].amount -= refund;
+            _sendPrizeToken(prizes[0].token, msg.sender, refund);
+        }
+
+        uint256 stake = stakeOf[msg.sender];
+        if (stake > 0) {
+            stakeOf[msg.sender] = 0;
+            stakesHeld -= stake;
+            _sendPrizeToken(prizes[0].token, msg.sender, stake);
+            emit StakeReturned(msg.sender, stake);
+        }
+
+        emit EntryWithdrawn(msg.sender, entries, refund, paid - refund);
+    }
+
+    /// @notice Switch the contest to committee judging
+    /// @dev Requires a total entry cap of at most MAX_JUDGED_ENTRIES so that `tally` stays executable
+    /// @param judgeList Judges allowed to score contestants
+    /// @param quorum Number of judges that must vote before an early tally
+    /// @param votingEnd Timestamp after which the tally may run without quorum
+    function setJudges(address[] calldata judgeList, uint8 quorum, uint40 votingEnd) external onlyCreator {
+        if (finalized || judges.length > 0) revert Forbidden();
+        uint256 len = judgeList.length;
+        if (len == 0 || len > MAX_JUDGES) revert InvalidParameters();
+        if (quorum == 0 || quorum > len) revert InvalidParameters();
+        if (votingEnd <= block.timestamp) revert DeadlineInPast();
+        if (maxEntries == 0 || maxEntries > MAX_JUDGED_ENTRIES) revert LimitExceeded();
+
+        for (uint256 i = 0; i < len; i++) {
+            address judge = judgeList[i];
+            if (judge == address(0)) revert ZeroAddress();
+            if (isJudge[judge]) revert InvalidParameters();
+            isJudge[judge] = true;
+            judges.push(judge);
+        }
+        judgeQuorum = quorum;
+        votingEndsAt = votingEnd;
+
+        emit JudgesConfigured(judgeList, quorum, votingEnd);
+    }
+
+    /// @notice Score a contestant; a judge may revise their score until the tally
+    function voteEntry(address contestant, uint32 score) external {
+        if (!isJudge[msg.sender]) revert Unauthorized();
+        if (finalized || winners.length != 0) revert ContestAlreadyFinalized();
+        if (block.timestamp > votingEndsAt) revert DeadlineExpired(votingEndsAt, block.timestamp);
+        if (entriesOf[contestant] == 0) revert NotFound();
+
+        if (!hasJudgeVoted[msg.sender]) {
+            hasJudgeVoted[msg.sender] = true;
+            judgesVoted += 1;
+        }
+        totalScores[contestant] = totalScores[contestant] - judgeScores[msg.sender][contestant] + score;
+        judgeScores[msg.sender][contestant] = score;
+
+        emit EntryScored(msg.sender, contestant, score);
+    }
+
+    /// @notice Reclaim entry fees after the contest was cancelled
+    function claimEntryRefund() external nonReentrant {
+        if (!cancelled) revert InvalidState();
+        uint256 amount = entryFeesPaid[msg.sender];
+        if (amount == 0) revert NothingToWithdraw();
+
+        entryFeesPaid[msg.sender] = 0;
+        _sendPrizeToken(prizes[0].token, msg.sender, amount);
+
+        emit EntryFeeRefunded(msg.sender, amount);
+    }
+
+    /// @notice Reclaim a sponsorship after the contest was cancelled
+    /// @param sponsorshipId Index in the sponsorships array
+    function claimSponsorRefund(uint256 sponsorshipId) external nonReentrant {
+        if (!cancelled) revert InvalidState();
+        if (sponsorshipId >= sponsorships.length) revert InvalidParameters();
+        Sponsorship memory sp = sponsorships[sponsorshipId];
+        if (sp.sponsor != msg.sender) revert Unauthorized();
+        if (sponsorshipRefunded[sponsorshipId]) revert NothingToWithdraw();
+
+        sponsorshipRefunded[sponsorshipId] = true;
+        _sendPrizeToken(prizes[sp.prizeIndex].token, msg.sender, sp.amount);
+
+        emit SponsorRefunded(msg.sender, sp.prizeIndex, sp.amount);
+    }
+
+    /// @notice Number of sponsorships recorded
+    /// @return Length of the sponsorships array
+    function sponsorshipsLength() external view returns (uint256) {
+        return sponsorships.length;
+    }
+
     /// @notice Number of prizes configured
     /// @return Length of the prizes array
     function prizesLength() external view returns (uint256) {
         return prizes.length;
     }
 
+    /// @notice Number of distinct contestants
+    /// @return Length of the contestants array
+    function contestantsLength() external view returns (uint256) {
+        return contestants.length;
+    }
+
     /// @notice Number of winners processed
     /// @return Length of the winners array
     function winnersLength() external view returns (uint256) {
@@ -210,25 +634,118 @@ contract ContestEscrow is ReentrancyGuard {
         return (amount * rankWeight) / sumWeights;
     }
 
+    /// @dev Order contestants by total score, one per prize slot; ties go to the earlier entrant
+    function _rankContestants() internal view returns (address[] memory ranked) {
+        uint256 slots = prizes.length;
+        uint256 count = contestants.length;
+        if (count < slots) revert WrongWinnersCount();
+
+        ranked = new address[](slots);
+        bool[] memory taken = new bool[](count);
+        for (uint256 slot = 0; slot < slots; slot++) {
+            uint256 best;
+            bool found;
+            for (uint256 i = 0; i < count; i++) {
+                if (taken[i]) continue;
+                if (!found || totalScores[contestants[i]] > totalScores[contestants[best]]) {
+                    best = i;
+                    found = true;
+                }
+            }
+            taken[best] = true;
+            ranked[slot] = contestants[best];
+        }
+    }
+
+    function _removeContestant(address contestant) internal {
+        uint256 index = contestantIndex[contestant];
+        uint256 lastIndex = contestants.length;
+        if (index != lastIndex) {
+            address last = contestants[lastIndex - 1];
+            contestants[index - 1] = last;
+            contestantIndex[last] = index;
+        }
+        contestants.pop();
+        contestantIndex[contestant] = 0;
+    }
+
+    /// @dev Pull the entry fee and stake from the contestant; the fee is added to prize slot 0
+    /// @return received Entry fee received, excluding the stake
+    function _collectEntryFee() internal returns (uint256 received) {
+        uint256 due = entryFee + entryStake;
+        if (due == 0) {
+            if (msg.value != 0) revert InvalidAmount();
+            return 0;
+        }
+
+        PrizeInfo storage p = prizes[0];
+        if (p.token == address(0)) {
+            if (msg.value != due) revert InvalidAmount();
+            received = due;
+        } else {
+            if (msg.value != 0) revert InvalidAmount();
+            uint256 beforeBal = IERC20(p.token).balanceOf(address(this));
+            IERC20(p.token).safeTransferFrom(msg.sender, address(this), due);
+            received = IERC20(p.token).balanceOf(address(this)) - beforeBal;
+            if (received == 0) revert ContestFundingMissing();
+        }
+
+        // the stake is filled first so that transfer fees are borne by the prize pool
+        uint256 staked = received > entryStake ? entryStake : received;
+        stakeOf[msg.sender] += staked;
+        stakesHeld += staked;
+        received -= staked;
+
+        p.amount += received;
+        entryFeesCollected += received;
+        entryFeesPaid[msg.sender] += received;
+    }
+
+    /// @dev Route the platform share of entry fees to the Treasury service, if one is registered
+    function _collectPlatformFee() internal {
+        uint256 cut = (entryFeesCollected * platformFeeBps) / 10_000;
+        if (cut == 0) return;
+        address treasury = core.getService(MODULE_ID, 'Treasury');
+        if (treasury == address(0)) return;
+
+        PrizeInfo storage p = prizes[0];
+        p.amount -= cut;
+        _sendPrizeToken(p.token, treasury, cut);
+
+        emit PlatformFeeCollected(treasury, cut);
+    }
+
+    function _sendPrizeToken(address token, address to, uint256 amount) internal {
+        if (token == address(0)) {
+            (bool success, ) = payable(to).call{value: amount}('');
+            if (!success) revert TransferFailed();
+        } else {
+            IERC20(token).safeTransfer(to, amount);
+        }
+    }
+
     /// @notice Emergency withdrawal if the contest was not finalized in time
     function emergencyWithdraw() external onlyCreator nonReentrant {
-        if (finalized) revert ContestAlreadyFinalized();
+        if (finalized || processedWinners > 0 || winners.length != 0) revert ContestAlreadyFinalized();
         if (block.timestamp <= deadline + GRACE_PERIOD) revert GracePeriodNotExpired();
 
         // Set finalized before external calls (CEI pattern)
         finalized = true;
+        cancelled = true;
 
-        // Return all monetary prizes to the creator
+        // Return all monetary prizes to the creator, sponsored shares and entry fees
+        // stay in escrow until sponsors and contestants claim them
         for (uint256 i = 0; i < prizes.length; i++) {
             PrizeInfo memory p = prizes[i];
-            if (p.prizeType == PrizeType.MONETARY && p.amount > 0) {
+            uint256 creatorAmount = p.amount - sponsoredAmount[i] - (i == 0 ? entryFeesCollected : 0);
+            if (p.prizeType == PrizeType.MONETARY && creatorAmount > 0) {
                 if (p.token == address(0)) {
                     // Handle native ETH
-                    (bool success, ) = payable(creator).call{value: p.amount}('');
+                    (bool success, ) = payable(creator).call{value: creatorAmount}('');
                     if (!success) revert TransferFailed();
                 } else {
                     // Handle ERC20 tokens
-                    IERC20(p.token).safeTransfer(creator, p.amount);
+                    IERC20(p.token).safeTransfer(creator, creatorAmount);
                 }
             }
         }
@@ -41,6 +41,10 @@ contract ContestFactory is BaseFactory {
 
     event ContestCreated(uint256 contestId, address manager, bytes32 category, bytes metadata, bytes32 moduleId);
 
+    /// @notice Emitted when the default contest duration changes
+    /// @param duration Duration in seconds
+    event DefaultContestDurationUpdated(uint256 duration);
+
     constructor(address core, address feeManager) BaseFactory(core, feeManager, CoreDefs.CONTEST_MODULE_ID) {}
 
     /// @notice Creates a new contest with specified prizes
@@ -54,7 +58,7 @@ contract ContestFactory is BaseFactory {
     function createContest(
         PrizeInfo[] calldata _prizes,
         bytes calldata /* metadata */
-    ) external payable onlyFactoryAdmin nonReentrant returns (address escrow) {
+    ) external payable onlyFactoryAdmin whenModuleActive nonReentrant returns (address escrow) {
         // Check prizes array length first to save gas (cheapest check)
 
         uint256 prizesLen = _prizes.length;
@@ -212,6 +216,8 @@ contract ContestFactory is BaseFactory {
 
     function setDefaultContestDuration(uint256 duration) external onlyFactoryAdmin {
         defaultContestDuration = duration;
+
+        emit DefaultContestDurationUpdated(duration);
     }
 
     /// @notice Allows contract to receive ETH (needed for native currency contests)
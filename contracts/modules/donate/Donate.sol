@@ -32,6 +32,7 @@ contract Donate is ReentrancyGuard {
         uint256 timestamp,
         bytes32 moduleId
     );
+    event TokensRescued(address indexed token, address indexed to, uint256 amount);
 
     modifier onlyAdmin() {
         if (!core.hasRole(core.DEFAULT_ADMIN_ROLE(), msg.sender)) revert NotAdmin();
@@ -66,7 +67,7 @@ contract Donate is ReentrancyGuard {
         uint256 netAmount;
 
         if (isNative) {
-            if (msg.value < amount) revert InsufficientBalance();
+            if (msg.value < amount) revert InsufficientBalance(amount, msg.value);
 
             netAmount = paymentGateway.processPayment{value: amount}(MODULE_ID, address(0), msg.sender, amount, '');
 
@@ -113,6 +114,8 @@ contract Donate is ReentrancyGuard {
         } else {
             IERC20(token).safeTransfer(to, amount);
         }
+
+        emit TokensRescued(token, to, amount);
     }
 
     receive() external payable {}
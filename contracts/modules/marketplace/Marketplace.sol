@@ -5,11 +5,14 @@ import '../../core/CoreSystem.sol';
 import '../../pay/interfaces/IPaymentGateway.sol';
 import '@openzeppelin/contracts/token/ERC20/IERC20.sol';
 import '@openzeppelin/contracts/token/ERC20/utils/SafeERC20.sol';
+import '@openzeppelin/contracts/token/ERC721/IERC721.sol';
 import '@openzeppelin/contracts/utils/cryptography/ECDSA.sol';
+import '@openzeppelin/contracts/utils/cryptography/MessageHashUtils.sol';
 import '@openzeppelin/contracts/utils/ReentrancyGuard.sol';
 import '../../lib/SignatureLib.sol';
 import '../../core/CoreDefs.sol';
 import '../../errors/Errors.sol';
+import './interfaces/IMarketplaceServices.sol';
 
 // Event payload helper
 interface IEventPayload {
@@ -42,6 +45,18 @@ interface IDiscountManager {
     function getDiscountedPrice(bytes32 sku, uint256 originalPrice) external view returns (uint256 discountedPrice);
 }
 
+// Settlement hook called atomically after a sale (game servers, license registries)
+interface ISettlementHook {
+    function onSettlement(
+        bytes32 listingHash,
+        address buyer,
+        address seller,
+        bytes32 sku,
+        address paymentToken,
+        uint256 paymentAmount
+    ) external;
+}
+
 /// @title Marketplace
 /// @notice Marketplace working only with off-chain listings via signatures
 contract Marketplace is ReentrancyGuard {
@@ -54,6 +69,8 @@ contract Marketplace is ReentrancyGuard {
     bytes32 public immutable MODULE_ID;
     IPaymentGateway public immutable paymentGateway;
 
+    uint8 public constant MAX_CART_SIZE = 5;
+
     // EIP-712 domain separator
     bytes32 public immutable DOMAIN_SEPARATOR;
 
@@ -63,6 +80,143 @@ contract Marketplace is ReentrancyGuard {
     mapping(bytes32 => bool) public listingConsumed;
     mapping(bytes32 => bool) public revokedListings;
 
+    // Sponsored purchases: a relayer submits a buyer-signed purchase intent and pays the gas
+    struct PurchaseIntent {
+        address buyer;
+        bytes32 listingHash;
+        address paymentToken;
+        uint256 maxPaymentAmount;
+        uint256 nonce;
+        uint64 deadline;
+    }
+
+    bytes32 public constant PURCHASE_INTENT_TYPEHASH =
+        keccak256(
+            'PurchaseIntent(address buyer,bytes32 listingHash,address paymentToken,uint256 maxPaymentAmount,uint256 nonce,uint64 deadline)'
+        );
+    mapping(address => mapping(uint256 => bool)) public usedNonces; // signer => nonce => consumed
+
+    // Buyer-supplied order details (shipping address, license request) kept off-chain, keyed by hash
+    mapping(bytes32 => mapping(address => bytes32)) public orderMemos; // listingHash => buyer => memo hash
+
+    // Settlement hooks: governor-approved allowlist, selected per seller SKU
+    mapping(address => bool) public approvedHooks;
+    mapping(address => mapping(bytes32 => address)) public settlementHooks; // seller => sku => hook
+
+    // Seller holdback: part of each payout vests linearly over a configured duration
+    struct HoldbackConfig {
+        uint16 bps;
+        uint32 duration;
+    }
+
+    // Every settlement locks its own tranche, so later sales never delay funds that are already vesting
+    struct HoldbackTranche {
+        uint256 amount;
+        uint256 claimed;
+        uint64 start;
+        uint64 end;
+    }
+
+    uint256 public constant MAX_HOLDBACK_TRANCHES_PER_CLAIM = 50;
+
+    mapping(address => HoldbackConfig) public holdbackConfigs; // seller => config
+    mapping(address => mapping(address => HoldbackTranche[])) public holdbackTranches; // seller => token => tranches
+    mapping(address => mapping(address => uint256)) public holdbackCursor; // seller => token => first open tranche
+
+    // Seller royalties: a share of every seller payout goes to a configured recipient
+    struct RoyaltyConfig {
+        address recipient;
+        uint16 bps;
+    }
+
+    mapping(address => RoyaltyConfig) public royaltyConfigs; // seller => config
+
+    // Curated collections: a curator attests seller SKUs and earns a collection royalty on their sales
+    struct Collection {
+        address curator;
+        bytes32 nameHash;
+        uint16 royaltyBps;
+        bool verified;
+    }
+
+    uint16 public constant MAX_COLLECTION_ROYALTY_BPS = 1000;
+    uint256 public collectionCount;
+    mapping(uint256 => Collection) public collections;
+    mapping(address => mapping(bytes32 => uint256)) public skuCollections; // seller => sku => collectionId
+
+    // NFT-backed SKUs: the token is held here and delivered to the buyer with the sale
+    struct EscrowedAsset {
+        address collection;
+        uint256 tokenId;
+        bool delivered;
+        uint256 pendingOrder; // milestone order holding the NFT until it closes (0 = none)
+    }
+
+    mapping(address => mapping(bytes32 => EscrowedAsset)) public escrowedAssets; // seller => sku => asset
+
+    // Pay-what-you-want SKUs: listing price acts as the minimum
+    mapping(address => mapping(bytes32 => bool)) public openPricing; // seller => sku => enabled
+
+    // Dutch pricing: listing price declines linearly to endPrice between startTime and endTime
+    struct DutchSchedule {
+        uint256 endPrice;
+        uint64 startTime;
+        uint64 endTime;
+    }
+
+    mapping(address => mapping(bytes32 => DutchSchedule)) public dutchSchedules; // seller => sku => schedule
+
+    // Per-buyer purchase limits for limited drops, optionally shared across a registered wallet cluster
+    struct PurchaseLimit {
+        uint32 maxPerBuyer;
+        bool clusterWide;
+    }
+
+    mapping(address => mapping(bytes32 => PurchaseLimit)) public purchaseLimits; // seller => sku => limit
+    mapping(address => mapping(bytes32 => mapping(bytes32 => uint32))) public purchaseCounts; // seller => sku => key
+    mapping(address => bytes32) public walletClusters; // wallet => cluster id
+
+    // Listing reservations: a buyer holds a listing for a short window against a native deposit
+    struct Reservation {
+        address buyer;
+        address seller;
+        uint64 expiresAt;
+        uint256 deposit;
+    }
+
+    uint32 public constant MAX_RESERVATION_WINDOW = 1 days;
+    uint32 public constant RESERVATION_COOLDOWN = 1 days;
+    uint256 public reservationDeposit; // 0 disables reservations
+    mapping(bytes32 => Reservation) public reservations; // listingHash => reservation
+    mapping(bytes32 => mapping(address => uint64)) public reservationCooldowns; // listingHash => buyer => next allowed
+
+    // Sale receipts for off-chain license issuance
+    mapping(bytes32 => bytes32) public saleReceipts; // listingHash => receipt
+
+    // Referral program; the referrer's share of the platform fee is paid by the gateway's FeeProcessor
+    mapping(address => bool) public registeredReferrers;
+    mapping(address => mapping(address => bool)) public approvedReferrers; // seller => referrer => approved
+    mapping(address => uint256) public referralSales; // referrer => number of referred sales
+    mapping(address => mapping(address => uint256)) public referralVolume; // referrer => token => volume
+
+    // Seller reputation counters readable without an indexer
+    struct SellerStats {
+        uint64 completedSales;
+        uint64 disputesLost;
+    }
+
+    mapping(address => SellerStats) public sellerStats;
+    mapping(address => mapping(address => uint256)) public sellerVolume; // seller => token => net proceeds
+
+    // Sale payment commitments, so satellite services (insurance claims) can check what a buyer paid
+    mapping(bytes32 => bytes32) public salePayments; // saleHash => hashSalePayment(buyer, seller, token, amount)
+
+    // Optional satellite services registered in core under MODULE_ID; an unregistered service turns its feature off
+    string private constant ESCROW_SERVICE = 'MarketplaceEscrow';
+    string private constant AUCTIONS_SERVICE = 'MarketplaceAuctions';
+    string private constant PROMOTIONS_SERVICE = 'MarketplacePromotions';
+    string private constant CREDIT_SERVICE = 'MarketplaceCredit';
+
     // Marketplace events
     event MarketplaceSale(
         bytes32 indexed sku,
@@ -87,6 +241,74 @@ contract Marketplace is ReentrancyGuard {
         bytes32 listingHash,
         bytes32 moduleId
     );
+
+    event HookApprovalUpdated(address indexed hook, bool approved);
+    event SettlementHookUpdated(address indexed seller, bytes32 indexed sku, address hook);
+    event HoldbackConfigured(address indexed seller, uint16 bps, uint32 duration);
+    event HoldbackLocked(address indexed seller, address indexed token, uint256 amount, uint64 vestingEnd);
+    event HoldbackClaimed(address indexed seller, address indexed token, uint256 amount);
+    event RoyaltyConfigured(address indexed seller, address indexed recipient, uint16 bps);
+    event RoyaltyPaid(address indexed seller, address indexed recipient, address token, uint256 amount);
+    event CollectionCreated(uint256 indexed collectionId, address indexed curator, bytes32 nameHash, uint16 royaltyBps);
+    event CollectionVerified(uint256 indexed collectionId, bool verified);
+    event SkuCollectionUpdated(address indexed seller, bytes32 indexed sku, uint256 indexed collectionId);
+    event CollectionRoyaltyPaid(uint256 indexed collectionId, address indexed curator, address token, uint256 amount);
+    event AssetEscrowed(address indexed seller, bytes32 indexed sku, address collection, uint256 tokenId);
+    event AssetReleased(address indexed seller, bytes32 indexed sku, address indexed to);
+    event OpenPricingUpdated(address indexed seller, bytes32 indexed sku, bool enabled);
+    event DutchScheduleUpdated(
+        address indexed seller,
+        bytes32 indexed sku,
+        uint256 endPrice,
+        uint64 startTime,
+        uint64 endTime
+    );
+    event PurchaseLimitUpdated(address indexed seller, bytes32 indexed sku, uint32 maxPerBuyer, bool clusterWide);
+    event WalletClusterUpdated(address indexed wallet, bytes32 indexed clusterId);
+    event ReservationDepositUpdated(uint256 deposit);
+    event ListingReserved(bytes32 indexed listingHash, address indexed buyer, uint64 expiresAt, uint256 deposit);
+    event ReservationReleased(bytes32 indexed listingHash, address indexed buyer, address depositRecipient);
+    event SaleReceipt(bytes32 indexed listingHash, address indexed buyer, bytes32 indexed receipt);
+    event ReferrerRegistered(address indexed referrer);
+    event ReferrerApprovalUpdated(address indexed seller, address indexed referrer, bool approved);
+    event ReferralRecorded(
+        address indexed referrer,
+        address indexed buyer,
+        bytes32 indexed listingHash,
+        address token,
+        uint256 amount
+    );
+    event CartCheckout(address indexed buyer, bytes32[] listingHashes, bytes32 moduleId);
+    event OrderMemoAttached(
+        bytes32 indexed listingHash,
+        address indexed buyer,
+        address indexed seller,
+        bytes32 memoHash
+    );
+    event SponsoredPurchase(bytes32 indexed listingHash, address indexed buyer, address indexed relayer, uint256 nonce);
+    event NonceUsed(address indexed signer, uint256 indexed nonce);
+
+
+    modifier onlyOperator() {
+        if (!core.hasRole(CoreDefs.OPERATOR_ROLE, msg.sender)) revert NotOperator();
+        _;
+    }
+
+    modifier whenModuleActive() {
+        if (core.isModulePaused(MODULE_ID)) revert ModulePaused();
+        _;
+    }
+
+    modifier onlyGovernor() {
+        if (!core.hasRole(CoreDefs.GOVERNOR_ROLE, msg.sender)) revert NotGovernor();
+        _;
+    }
+
+    modifier onlyService(string memory name) {
+        if (msg.sender != _service(name)) revert Unauthorized();
+        _;
+    }
+
     constructor(address _core, address _paymentGateway, bytes32 moduleId) {
         if (_core == address(0)) revert ZeroAddress();
         if (_paymentGateway == address(0)) revert ZeroAddress();
@@ -115,29 +337,347 @@ contract Marketplace is ReentrancyGuard {
         bytes calldata sellerSignature,
         address paymentToken,
         uint256 maxPaymentAmount
-    ) external payable nonReentrant {
+    ) external payable whenModuleActive nonReentrant {
+        (uint256 nativeSpent, ) = _buy(
+            listing,
+            sellerSignature,
+            paymentToken,
+            maxPaymentAmount,
+            msg.value,
+            false,
+            0,
+            address(0),
+            bytes32(0),
+            msg.sender
+        );
+        _refundExcess(nativeSpent);
+    }
+
+    /// @notice Purchase an item on behalf of a registered referrer
+    /// @dev The referrer must be approved by the seller, so buyers cannot refer themselves through a second wallet
+    /// @param listing Listing structure
+    /// @param sellerSignature Seller signature
+    /// @param paymentToken Preferred payment token (0 to use listing currency)
+    /// @param maxPaymentAmount Maximum allowed payment amount
+    /// @param referrer Registered, seller-approved referrer credited with the sale
+    function buyWithReferral(
+        SignatureLib.Listing calldata listing,
+        bytes calldata sellerSignature,
+        address paymentToken,
+        uint256 maxPaymentAmount,
+        address referrer
+    ) external payable whenModuleActive nonReentrant {
+        if (!registeredReferrers[referrer]) revert NotFound();
+        if (referrer == msg.sender || referrer == listing.seller) revert InvalidAddress();
+        if (!approvedReferrers[listing.seller][referrer]) revert Unauthorized();
+        (uint256 nativeSpent, ) = _buy(
+            listing,
+            sellerSignature,
+            paymentToken,
+            maxPaymentAmount,
+            msg.value,
+            false,
+            0,
+            referrer,
+            bytes32(0),
+            msg.sender
+        );
+        _refundExcess(nativeSpent);
+    }
+
+    /// @notice Purchase an item applying a seller coupon
+    /// @param listing Listing structure
+    /// @param sellerSignature Seller signature
+    /// @param paymentToken Preferred payment token (0 to use listing currency)
+    /// @param maxPaymentAmount Maximum allowed payment amount
+    /// @param couponId Coupon identifier in the listing seller's namespace
+    function buyWithCoupon(
+        SignatureLib.Listing calldata listing,
+        bytes calldata sellerSignature,
+        address paymentToken,
+        uint256 maxPaymentAmount,
+        bytes32 couponId
+    ) external payable whenModuleActive nonReentrant {
+        if (couponId == bytes32(0)) revert InvalidArgument();
+        (uint256 nativeSpent, ) = _buy(
+            listing,
+            sellerSignature,
+            paymentToken,
+            maxPaymentAmount,
+            msg.value,
+            false,
+            0,
+            address(0),
+            couponId,
+            msg.sender
+        );
+        _refundExcess(nativeSpent);
+    }
+
+    /// @notice Purchase an item attaching the hash of off-chain order details for the seller
+    /// @param listing Listing structure
+    /// @param sellerSignature Seller signature
+    /// @param paymentToken Preferred payment token (0 to use listing currency)
+    /// @param maxPaymentAmount Maximum allowed payment amount
+    /// @param memoHash Hash of the delivery details the seller fetches off-chain
+    function buyWithMemo(
+        SignatureLib.Listing calldata listing,
+        bytes calldata sellerSignature,
+        address paymentToken,
+        uint256 maxPaymentAmount,
+        bytes32 memoHash
+    ) external payable whenModuleActive nonReentrant {
+        if (memoHash == bytes32(0)) revert InvalidArgument();
+        (uint256 nativeSpent, bytes32 listingHash) = _buy(
+            listing,
+            sellerSignature,
+            paymentToken,
+            maxPaymentAmount,
+            msg.value,
+            false,
+            0,
+            address(0),
+            bytes32(0),
+            msg.sender
+        );
+        orderMemos[listingHash][msg.sender] = memoHash;
+        emit OrderMemoAttached(listingHash, msg.sender, listing.seller, memoHash);
+        _refundExcess(nativeSpent);
+    }
+
+    /// @notice Register the caller as a referrer
+    function registerReferrer() external {
+        if (registeredReferrers[msg.sender]) revert InvalidState();
+        registeredReferrers[msg.sender] = true;
+        emit ReferrerRegistered(msg.sender);
+    }
+
+    /// @notice Allow or stop a referrer earning referral fees on the caller's listings
+    /// @param referrer Referrer address
+    /// @param approved Approval flag
+    function setReferrerApproval(address referrer, bool approved) external {
+        if (referrer == address(0)) revert ZeroAddress();
+        approvedReferrers[msg.sender][referrer] = approved;
+        emit ReferrerApprovalUpdated(msg.sender, referrer, approved);
+    }
+
+    /// @notice Purchase an item for a buyer who signed a purchase intent, with the caller paying gas
+    /// @dev The buyer must have approved the gateway for the payment token; native payments are not sponsored
+    /// @param listing Listing structure
+    /// @param sellerSignature Seller signature
+    /// @param intent Purchase intent signed by the buyer
+    /// @param buyerSignature Buyer signature over the intent
+    function buySponsored(
+        SignatureLib.Listing calldata listing,
+        bytes calldata sellerSignature,
+        PurchaseIntent calldata intent,
+        bytes calldata buyerSignature
+    ) external whenModuleActive nonReentrant {
+        if (intent.deadline < block.timestamp) revert DeadlineExpired(intent.deadline, block.timestamp);
+        if (intent.listingHash != hashListing(listing)) revert InvalidArgument();
+        if (ECDSA.recover(hashPurchaseIntent(intent), buyerSignature) != intent.buyer) revert InvalidSignature();
+        _useNonce(intent.buyer, intent.nonce);
+
+        _buy(
+            listing,
+            sellerSignature,
+            intent.paymentToken,
+            intent.maxPaymentAmount,
+            0,
+            false,
+            0,
+            address(0),
+            bytes32(0),
+            intent.buyer
+        );
+
+        emit SponsoredPurchase(intent.listingHash, intent.buyer, msg.sender, intent.nonce);
+    }
+
+    /// @notice Burn one of the caller's nonces so any intent signed with it can no longer be submitted
+    /// @param nonce Nonce to invalidate
+    function invalidateNonce(uint256 nonce) external {
+        _useNonce(msg.sender, nonce);
+    }
+
+    /// @notice EIP-712 digest a buyer signs to authorize a sponsored purchase
+    /// @param intent Purchase intent
+    /// @return digest Typed data hash
+    function hashPurchaseIntent(PurchaseIntent calldata intent) public view returns (bytes32) {
+        bytes32 structHash = keccak256(
+            abi.encode(
+                PURCHASE_INTENT_TYPEHASH,
+                intent.buyer,
+                intent.listingHash,
+                intent.paymentToken,
+                intent.maxPaymentAmount,
+                intent.nonce,
+                intent.deadline
+            )
+        );
+        return SignatureLib._hashTypedDataV4(DOMAIN_SEPARATOR, structHash);
+    }
+
+    /// @notice Purchase a pay-what-you-want item for a buyer-chosen amount
+    /// @param listing Listing structure, `price` is the minimum accepted amount
+    /// @param sellerSignature Seller signature
+    /// @param paymentToken Preferred payment token (0 to use listing currency)
+    /// @param amount Chosen amount in listing currency, at least `listing.price`
+    /// @param maxPaymentAmount Maximum allowed payment amount
+    function buyWithAmount(
+        SignatureLib.Listing calldata listing,
+        bytes calldata sellerSignature,
+        address paymentToken,
+        uint256 amount,
+        uint256 maxPaymentAmount
+    ) external payable whenModuleActive nonReentrant {
+        if (amount == 0) revert AmountZero();
+        (uint256 nativeSpent, ) = _buy(
+            listing,
+            sellerSignature,
+            paymentToken,
+            maxPaymentAmount,
+            msg.value,
+            false,
+            amount,
+            address(0),
+            bytes32(0),
+            msg.sender
+        );
+        _refundExcess(nativeSpent);
+    }
+
+    /// @notice Purchase an item drawing as much of the price as possible from prepaid credit
+    /// @dev For ERC-20 payments the uncovered remainder is pulled by the marketplace, which needs the allowance
+    /// @param listing Listing structure
+    /// @param sellerSignature Seller signature
+    /// @param paymentToken Preferred payment token (0 to use listing currency)
+    /// @param maxPaymentAmount Maximum allowed payment amount
+    function buyWithCredit(
+        SignatureLib.Listing calldata listing,
+        bytes calldata sellerSignature,
+        address paymentToken,
+        uint256 maxPaymentAmount
+    ) external payable whenModuleActive nonReentrant {
+        (uint256 nativeSpent, ) = _buy(
+            listing,
+            sellerSignature,
+            paymentToken,
+            maxPaymentAmount,
+            msg.value,
+            true,
+            0,
+            address(0),
+            bytes32(0),
+            msg.sender
+        );
+        _refundExcess(nativeSpent);
+    }
+
+    /// @notice Purchase several listings atomically (cart checkout)
+    /// @param listings Listing structures, sellers may differ
+    /// @param sellerSignatures Seller signatures, one per listing
+    /// @param paymentTokens Preferred payment tokens, one per listing (0 to use listing currency)
+    /// @param maxPaymentAmounts Maximum allowed payment amounts, one per listing
+    function buyBatch(
+        SignatureLib.Listing[] calldata listings,
+        bytes[] calldata sellerSignatures,
+        address[] calldata paymentTokens,
+        uint256[] calldata maxPaymentAmounts
+    ) external payable whenModuleActive nonReentrant {
+        uint256 count = listings.length;
+        if (count == 0) revert InvalidArgument();
+        if (count > MAX_CART_SIZE) revert BatchTooLarge();
+        if (
+            sellerSignatures.length != count || paymentTokens.length != count || maxPaymentAmounts.length != count
+        ) {
+            revert LengthMismatch();
+        }
+
+        bytes32[] memory listingHashes = new bytes32[](count);
+        uint256 nativeSpent;
+        for (uint256 i = 0; i < count; ) {
+            (uint256 spent, bytes32 listingHash) = _buy(
+                listings[i],
+                sellerSignatures[i],
+                paymentTokens[i],
+                maxPaymentAmounts[i],
+                msg.value - nativeSpent,
+                false,
+                0,
+                address(0),
+                bytes32(0),
+                msg.sender
+            );
+            nativeSpent += spent;
+            listingHashes[i] = listingHash;
+            unchecked {
+                ++i;
+            }
+        }
+
+        _refundExcess(nativeSpent);
+
+        emit CartCheckout(msg.sender, listingHashes, MODULE_ID);
+    }
+
+    /// @dev Purchase a single listing using at most `availableValue` of the attached native currency
+    /// @dev When `useCredit` is set, prepaid credit in the payment token covers the price first
+    /// @dev A non-zero `chosenPrice` replaces the listing price for pay-what-you-want SKUs
+    /// @dev A non-zero `referrer` is forwarded to the gateway as payment metadata
+    /// @dev A non-zero `couponId` applies that seller coupon after any cross-sell discount
+    /// @dev `buyer` pays through the gateway and is credited with the purchase
+    /// @return nativeSpent Native currency consumed by this purchase
+    /// @return buyListingHash Hash of the purchased listing
+    function _buy(
+        SignatureLib.Listing calldata listing,
+        bytes calldata sellerSignature,
+        address paymentToken,
+        uint256 maxPaymentAmount,
+        uint256 availableValue,
+        bool useCredit,
+        uint256 chosenPrice,
+        address referrer,
+        bytes32 couponId,
+        address buyer
+    ) internal returns (uint256 nativeSpent, bytes32 buyListingHash) {
         // Cheap checks before expensive operations
         if (listing.price == 0) revert InvalidArgument();
         if (listing.seller == address(0)) revert ZeroAddress();
 
         // Compute listing hash once
-        bytes32 buyListingHash = hashListing(listing);
+        buyListingHash = hashListing(listing);
 
         // Validate listing (signature checked last)
-        _validateListing(listing, sellerSignature, buyListingHash);
+        _validateListing(listing, sellerSignature, buyListingHash, buyer);
+
+        // Honour an active reservation held by another buyer
+        _settleReservation(buyListingHash, buyer);
 
         // Mark listing as consumed
-        consumed[buyListingHash][msg.sender] = true;
+        consumed[buyListingHash][buyer] = true;
         listingConsumed[buyListingHash] = true;
         revokedListings[buyListingHash] = true;
 
+        _recordPurchase(buyer, listing.seller, listing.sku);
+
+        uint256 basePrice = _scheduledPrice(listing.seller, listing.sku, listing.price);
+        if (chosenPrice > 0) {
+            if (!openPricing[listing.seller][listing.sku]) revert InvalidArgument();
+            if (chosenPrice < basePrice) revert InvalidPrice();
+            basePrice = chosenPrice;
+        }
+
+        // Apply cross-sell and coupon discounts and record purchase history
+        uint256 price = _applyDiscounts(buyer, listing.seller, listing.sku, buyListingHash, couponId, basePrice);
+
         // Determine token and amount for payment
         address actualPaymentToken = paymentToken == address(0) ? listing.token : paymentToken;
 
-        uint256 paymentAmount = listing.price;
+        uint256 paymentAmount = price;
 
-        if (maxPaymentAmount > 0 && actualPaymentToken == listing.token && listing.price > maxPaymentAmount) {
-            revert PriceExceedsMaximum();
+        if (maxPaymentAmount > 0 && actualPaymentToken == listing.token && price > maxPaymentAmount) {
+            revert PriceExceedsMaximum(maxPaymentAmount, price);
         }
 
         // Convert amount if payment token differs from listing token
@@ -146,52 +686,76 @@ contract Marketplace is ReentrancyGuard {
                 revert UnsupportedPair();
             }
 
-            paymentAmount = paymentGateway.convertAmount(MODULE_ID, listing.token, actualPaymentToken, listing.price);
+            paymentAmount = paymentGateway.convertAmount(MODULE_ID, listing.token, actualPaymentToken, price);
             if (paymentAmount == 0) revert InvalidPrice();
 
             if (maxPaymentAmount > 0 && paymentAmount > maxPaymentAmount) {
-                revert PriceExceedsMaximum();
+                revert PriceExceedsMaximum(maxPaymentAmount, paymentAmount);
             }
         }
 
-        address buyer = msg.sender;
         address seller = listing.seller;
 
         bool isNativeToken = actualPaymentToken == address(0) ||
             actualPaymentToken == 0xEeeeeEeeeEeEeeEeEeEeeEEEeeeeEeeeeeeeEEeE;
 
+        uint256 creditUsed = useCredit
+            ? _drawCredit(buyer, isNativeToken ? address(0) : actualPaymentToken, paymentAmount)
+            : 0;
+
+        bytes memory paymentMetadata = referrer == address(0) ? bytes('') : abi.encode(referrer);
         uint256 netAmount;
+        uint256 escrowOrderId;
         if (isNativeToken) {
-            if (msg.value < paymentAmount) revert InsufficientBalance();
+            if (availableValue + creditUsed < paymentAmount) {
+                revert InsufficientBalance(paymentAmount, availableValue + creditUsed);
+            }
+            nativeSpent = paymentAmount - creditUsed;
 
             netAmount = paymentGateway.processPayment{value: paymentAmount}(
                 MODULE_ID,
                 address(0),
                 buyer,
                 paymentAmount,
-                ''
+                paymentMetadata
             );
 
-            (bool success, ) = payable(seller).call{value: netAmount}('');
-            if (!success) revert RefundDisabled();
-
-            uint256 excess = msg.value - paymentAmount;
-            if (excess > 0) {
-                (bool refundSuccess, ) = payable(buyer).call{value: excess}('');
-                if (!refundSuccess) revert RefundDisabled();
-            }
+            escrowOrderId = _paySeller(buyer, seller, listing.sku, address(0), netAmount, buyListingHash);
         } else {
-            netAmount = paymentGateway.processPayment(MODULE_ID, actualPaymentToken, buyer, paymentAmount, '');
+            if (creditUsed > 0) {
+                // Credit is already held here; only the uncovered remainder is pulled from the buyer
+                uint256 remainder = paymentAmount - creditUsed;
+                if (remainder > 0) IERC20(actualPaymentToken).safeTransferFrom(buyer, address(this), remainder);
+                netAmount = _processEscrowedPayment(actualPaymentToken, buyer, paymentAmount);
+            } else {
+                netAmount = paymentGateway.processPayment(
+                    MODULE_ID,
+                    actualPaymentToken,
+                    buyer,
+                    paymentAmount,
+                    paymentMetadata
+                );
+            }
+
+            escrowOrderId = _paySeller(buyer, seller, listing.sku, actualPaymentToken, netAmount, buyListingHash);
+        }
 
-            IERC20(actualPaymentToken).safeTransfer(seller, netAmount);
+        if (referrer != address(0)) {
+            referralSales[referrer] += 1;
+            referralVolume[referrer][actualPaymentToken] += paymentAmount;
+            emit ReferralRecorded(referrer, buyer, buyListingHash, actualPaymentToken, paymentAmount);
         }
 
+        _payCashback(buyer, seller, isNativeToken ? address(0) : actualPaymentToken, paymentAmount, escrowOrderId);
+
+        _completeSale(buyListingHash, buyer, seller, listing.sku, actualPaymentToken, paymentAmount);
+
         // Emit event directly
         emit MarketplaceSale(
             listing.sku,
             listing.seller,
-            msg.sender,
-            listing.price,
+            buyer,
+            basePrice,
             actualPaymentToken,
             paymentAmount,
             block.timestamp,
@@ -200,6 +764,109 @@ contract Marketplace is ReentrancyGuard {
         );
     }
 
+    /// @notice Receipt binding a sale to its buyer, verifiable by off-chain license servers
+    /// @param listingHash Hash of the purchased listing
+    /// @param sku Item SKU
+    /// @param buyer Buyer address
+    /// @return Receipt hash
+    function computeReceipt(bytes32 listingHash, bytes32 sku, address buyer) public pure returns (bytes32) {
+        return keccak256(abi.encodePacked(listingHash, sku, buyer));
+    }
+
+    /// @notice Commitment stored in `salePayments` for a sale
+    /// @param buyer Buyer address
+    /// @param seller Seller address
+    /// @param token Payment token (zero address for native currency)
+    /// @param amount Amount the buyer paid
+    /// @return Payment commitment
+    function hashSalePayment(
+        address buyer,
+        address seller,
+        address token,
+        uint256 amount
+    ) public pure returns (bytes32) {
+        return keccak256(abi.encode(buyer, seller, token, amount));
+    }
+
+    /// @notice Settle a sale paid with funds a satellite service escrowed (winning bids, accepted offers)
+    /// @dev Only callable by the auctions service, which sends `amount` along (as value for native currency).
+    /// Allowed while the module is paused so escrowed funds never get stuck.
+    /// @param buyer Buyer address
+    /// @param seller Seller address
+    /// @param sku Item SKU
+    /// @param token Payment token (0 for native currency)
+    /// @param amount Gross amount paid by the buyer
+    /// @param saleHash Identifier the sale is recorded under
+    /// @return netAmount Seller proceeds after gateway fees
+    function settleEscrowedSale(
+        address buyer,
+        address seller,
+        bytes32 sku,
+        address token,
+        uint256 amount,
+        bytes32 saleHash
+    ) external payable onlyService(AUCTIONS_SERVICE) nonReentrant returns (uint256 netAmount) {
+        if (msg.value != (token == address(0) ? amount : 0)) revert InvalidAmount();
+
+        _recordPurchase(buyer, seller, sku);
+        netAmount = _processEscrowedPayment(token, buyer, amount);
+        _paySeller(buyer, seller, sku, token, netAmount, saleHash);
+        _completeSale(saleHash, buyer, seller, sku, token, amount);
+
+        emit MarketplaceSale(sku, seller, buyer, amount, token, amount, block.timestamp, saleHash, MODULE_ID);
+    }
+
+    /// @notice Pay out milestone escrow released by the escrow service
+    /// @dev Only callable by the escrow service, which sends `amount` along (as value for native currency)
+    /// @param seller Seller address
+    /// @param sku Item SKU
+    /// @param token Payout token (0 for native currency)
+    /// @param amount Released amount
+    function releaseEscrow(
+        address seller,
+        bytes32 sku,
+        address token,
+        uint256 amount
+    ) external payable onlyService(ESCROW_SERVICE) nonReentrant {
+        if (msg.value != (token == address(0) ? amount : 0)) revert InvalidAmount();
+        _releaseToSeller(seller, sku, token, amount);
+    }
+
+    /// @notice Record the outcome of a closed milestone order
+    /// @dev Only callable by the escrow service. A sold order counts as a completed sale and receives the NFT
+    /// held for it; the NFT of a fully refunded order becomes available again.
+    /// @param orderId Milestone order identifier
+    /// @param buyer Buyer address
+    /// @param seller Seller address
+    /// @param sku Item SKU
+    /// @param sold Whether any part of the escrow went to the seller
+    /// @param disputeLost Whether a dispute was ruled at least partly for the buyer
+    function closeEscrowOrder(
+        uint256 orderId,
+        address buyer,
+        address seller,
+        bytes32 sku,
+        bool sold,
+        bool disputeLost
+    ) external onlyService(ESCROW_SERVICE) nonReentrant {
+        if (sold) sellerStats[seller].completedSales += 1;
+        if (disputeLost) sellerStats[seller].disputesLost += 1;
+
+        address promotions = _service(PROMOTIONS_SERVICE);
+        if (promotions != address(0)) IMarketplacePromotions(promotions).settleCashback(orderId, sold && !disputeLost);
+
+        EscrowedAsset storage asset = escrowedAssets[seller][sku];
+        if (asset.pendingOrder != orderId) return;
+
+        asset.pendingOrder = 0;
+        if (!sold) return;
+
+        asset.delivered = true;
+        IERC721(asset.collection).safeTransferFrom(address(this), buyer, asset.tokenId);
+
+        emit AssetReleased(seller, sku, buyer);
+    }
+
     /// @notice Get item price in a preferred currency
     /// @param listing Listing data
     /// @param preferredCurrency Preferred payment token
@@ -290,6 +957,302 @@ contract Marketplace is ReentrancyGuard {
         );
     }
 
+    /// @notice Back one of the caller's SKUs with an NFT delivered to the buyer on purchase
+    /// @dev The marketplace must be approved for the token; a delivered entry may be replaced
+    /// @param sku Item SKU
+    /// @param collection ERC-721 contract
+    /// @param tokenId Token identifier
+    function escrowAsset(bytes32 sku, address collection, uint256 tokenId) external nonReentrant {
+        if (collection == address(0)) revert ZeroAddress();
+        EscrowedAsset storage asset = escrowedAssets[msg.sender][sku];
+        if (asset.collection != address(0) && !asset.delivered) revert InvalidState();
+
+        escrowedAssets[msg.sender][sku] = EscrowedAsset({
+            collection: collection,
+            tokenId: tokenId,
+            delivered: false,
+            pendingOrder: 0
+        });
+        IERC721(collection).transferFrom(msg.sender, address(this), tokenId);
+
+        emit AssetEscrowed(msg.sender, sku, collection, tokenId);
+    }
+
+    /// @notice Return an unsold escrowed NFT to the caller
+    /// @dev The entry stays behind marked as delivered, so listings still signed for the SKU revert instead of
+    /// selling without the NFT until the seller escrows a new one
+    /// @param sku Item SKU
+    function withdrawAsset(bytes32 sku) external nonReentrant {
+        EscrowedAsset storage asset = escrowedAssets[msg.sender][sku];
+        if (asset.collection == address(0)) revert NotFound();
+        if (asset.delivered || asset.pendingOrder != 0) revert InvalidState();
+
+        asset.delivered = true;
+        IERC721(asset.collection).safeTransferFrom(address(this), msg.sender, asset.tokenId);
+        emit AssetReleased(msg.sender, sku, msg.sender);
+    }
+
+    /// @notice Route a share of the caller's payouts to a royalty recipient
+    /// @param recipient Royalty recipient (ignored when `bps` is 0)
+    /// @param bps Royalty share of each payout in basis points
+    function setRoyalty(address recipient, uint16 bps) external {
+        if (bps > 10000) revert InvalidArgument();
+        if (bps > 0 && recipient == address(0)) revert ZeroAddress();
+        royaltyConfigs[msg.sender] = RoyaltyConfig({recipient: recipient, bps: bps});
+        emit RoyaltyConfigured(msg.sender, recipient, bps);
+    }
+
+    /// @notice Create a curated collection owned by the caller
+    /// @param nameHash Hash of the collection name
+    /// @param royaltyBps Curator royalty on sales of member SKUs in basis points
+    /// @return collectionId Identifier of the new collection
+    function createCollection(bytes32 nameHash, uint16 royaltyBps) external returns (uint256 collectionId) {
+        if (royaltyBps > MAX_COLLECTION_ROYALTY_BPS) revert InvalidArgument();
+
+        collectionId = ++collectionCount;
+        collections[collectionId] = Collection({
+            curator: msg.sender,
+            nameHash: nameHash,
+            royaltyBps: royaltyBps,
+            verified: false
+        });
+
+        emit CollectionCreated(collectionId, msg.sender, nameHash, royaltyBps);
+    }
+
+    /// @notice Mark a collection as verified so frontends can filter on it
+    /// @param collectionId Collection identifier
+    /// @param verified Verification flag
+    function setCollectionVerified(uint256 collectionId, bool verified) external onlyOperator {
+        if (collections[collectionId].curator == address(0)) revert NotFound();
+        collections[collectionId].verified = verified;
+        emit CollectionVerified(collectionId, verified);
+    }
+
+    /// @notice Place one of the caller's SKUs in a collection
+    /// @param sku Item SKU
+    /// @param collectionId Collection identifier (0 removes the SKU from its collection)
+    /// @param curatorSignature Curator signature over `collectionAttestationHash`
+    function setSkuCollection(bytes32 sku, uint256 collectionId, bytes calldata curatorSignature) external {
+        if (collectionId != 0) {
+            address curator = collections[collectionId].curator;
+            if (curator == address(0)) revert NotFound();
+            bytes32 digest = MessageHashUtils.toEthSignedMessageHash(
+                collectionAttestationHash(collectionId, msg.sender, sku)
+            );
+            if (ECDSA.recover(digest, curatorSignature) != curator) revert InvalidSignature();
+        }
+
+        skuCollections[msg.sender][sku] = collectionId;
+        emit SkuCollectionUpdated(msg.sender, sku, collectionId);
+    }
+
+    /// @notice Message a curator signs to admit a seller SKU into a collection
+    /// @param collectionId Collection identifier
+    /// @param seller Seller address
+    /// @param sku Item SKU
+    /// @return Attestation hash
+    function collectionAttestationHash(
+        uint256 collectionId,
+        address seller,
+        bytes32 sku
+    ) public view returns (bytes32) {
+        return keccak256(abi.encode(DOMAIN_SEPARATOR, collectionId, seller, sku));
+    }
+
+    /// @notice Enable or disable pay-what-you-want pricing for one of the caller's SKUs
+    /// @param sku Item SKU
+    /// @param enabled Whether buyers may pay more than the listing price
+    function setOpenPricing(bytes32 sku, bool enabled) external {
+        openPricing[msg.sender][sku] = enabled;
+        emit OpenPricingUpdated(msg.sender, sku, enabled);
+    }
+
+    /// @notice Let prices of one of the caller's SKUs decline over time (Dutch auction)
+    /// @dev Signed listing prices act as the start price; endTime 0 clears the schedule
+    /// @param sku Item SKU
+    /// @param endPrice Floor price reached at `endTime`
+    /// @param startTime Time the decline starts
+    /// @param endTime Time the floor is reached
+    function setDutchSchedule(bytes32 sku, uint256 endPrice, uint64 startTime, uint64 endTime) external {
+        if (endTime == 0) {
+            delete dutchSchedules[msg.sender][sku];
+        } else {
+            if (endTime <= startTime || endPrice == 0) revert InvalidArgument();
+            dutchSchedules[msg.sender][sku] = DutchSchedule({
+                endPrice: endPrice,
+                startTime: startTime,
+                endTime: endTime
+            });
+        }
+        emit DutchScheduleUpdated(msg.sender, sku, endPrice, startTime, endTime);
+    }
+
+    /// @notice Current price of a listing after any Dutch schedule is applied
+    /// @param listing Listing data
+    /// @return price Price in listing currency
+    function currentListingPrice(SignatureLib.Listing calldata listing) external view returns (uint256 price) {
+        return _scheduledPrice(listing.seller, listing.sku, listing.price);
+    }
+
+    /// @notice Cap how many units of one of the caller's SKUs a single buyer may purchase
+    /// @param sku Item SKU
+    /// @param maxPerBuyer Maximum purchases per buyer (0 disables the limit)
+    /// @param clusterWide Count purchases across the buyer's registered wallet cluster
+    function setPurchaseLimit(bytes32 sku, uint32 maxPerBuyer, bool clusterWide) external {
+        purchaseLimits[msg.sender][sku] = PurchaseLimit({maxPerBuyer: maxPerBuyer, clusterWide: clusterWide});
+        emit PurchaseLimitUpdated(msg.sender, sku, maxPerBuyer, clusterWide);
+    }
+
+    /// @notice Assign wallets to a cluster treated as one buyer by cluster-wide purchase limits
+    /// @param wallets Wallet addresses
+    /// @param clusterId Cluster identifier (0 removes the wallets from their cluster)
+    function setWalletCluster(address[] calldata wallets, bytes32 clusterId) external onlyOperator {
+        for (uint256 i = 0; i < wallets.length; i++) {
+            walletClusters[wallets[i]] = clusterId;
+            emit WalletClusterUpdated(wallets[i], clusterId);
+        }
+    }
+
+    /// @notice Set the native deposit required to reserve a listing
+    /// @param deposit Deposit amount in wei (0 disables reservations)
+    function setReservationDeposit(uint256 deposit) external onlyOperator {
+        reservationDeposit = deposit;
+        emit ReservationDepositUpdated(deposit);
+    }
+
+    /// @notice Lock a listing for the caller while an off-chain payment (e.g. fiat on-ramp) settles
+    /// @dev The deposit is returned when the caller buys the listing and forfeited to the seller on expiry.
+    /// The same buyer cannot reserve the listing again until RESERVATION_COOLDOWN after the reservation ends.
+    /// @param listing Listing structure
+    /// @param sellerSignature Seller signature
+    /// @param duration Reservation window in seconds
+    function reserveListing(
+        SignatureLib.Listing calldata listing,
+        bytes calldata sellerSignature,
+        uint32 duration
+    ) external payable whenModuleActive nonReentrant {
+        if (duration == 0 || duration > MAX_RESERVATION_WINDOW) revert InvalidArgument();
+        if (reservationDeposit == 0) revert InvalidState();
+        if (msg.value != reservationDeposit) revert InvalidAmount();
+
+        bytes32 listingHash = hashListing(listing);
+        if (block.timestamp < reservationCooldowns[listingHash][msg.sender]) revert NotDue();
+        _validateListing(listing, sellerSignature, listingHash, msg.sender);
+        Reservation storage existing = reservations[listingHash];
+        if (existing.buyer != address(0) && existing.expiresAt > block.timestamp) revert InvalidState();
+        _settleReservation(listingHash, msg.sender);
+
+        uint64 expiresAt = uint64(block.timestamp) + duration;
+        if (listing.expiry > 0 && expiresAt > listing.expiry) revert Expired();
+
+        reservationCooldowns[listingHash][msg.sender] = expiresAt + RESERVATION_COOLDOWN;
+        reservations[listingHash] = Reservation({
+            buyer: msg.sender,
+            seller: listing.seller,
+            expiresAt: expiresAt,
+            deposit: msg.value
+        });
+
+        emit ListingReserved(listingHash, msg.sender, expiresAt, msg.value);
+    }
+
+    /// @notice Release an expired reservation, forfeiting its deposit to the seller
+    /// @dev Callable by anyone so keepers can clear stale locks
+    /// @param listingHash Hash of the reserved listing
+    function releaseReservation(bytes32 listingHash) external nonReentrant {
+        Reservation storage r = reservations[listingHash];
+        if (r.buyer == address(0)) revert NotFound();
+        if (r.expiresAt > block.timestamp) revert NotDue();
+        _settleReservation(listingHash, msg.sender);
+    }
+
+    /// @notice Approve or revoke a settlement hook contract
+    /// @dev Revoking a hook stops calls to it; SKUs configured with it keep selling without the hook
+    /// @param hook Hook contract address
+    /// @param approved Approval flag
+    function setHookApproval(address hook, bool approved) external onlyGovernor {
+        if (hook == address(0)) revert ZeroAddress();
+        approvedHooks[hook] = approved;
+        emit HookApprovalUpdated(hook, approved);
+    }
+
+    /// @notice Select the settlement hook called after sales of one of the caller's SKUs
+    /// @param sku Item SKU
+    /// @param hook Approved hook contract (0 to disable)
+    function setSettlementHook(bytes32 sku, address hook) external {
+        if (hook != address(0) && !approvedHooks[hook]) revert InvalidModule();
+        settlementHooks[msg.sender][sku] = hook;
+        emit SettlementHookUpdated(msg.sender, sku, hook);
+    }
+
+    /// @notice Configure the payout holdback for a seller
+    /// @param seller Seller address
+    /// @param bps Share of each payout held back, in basis points (0 disables)
+    /// @param duration Linear vesting duration in seconds
+    function setSellerHoldback(address seller, uint16 bps, uint32 duration) external onlyOperator {
+        if (seller == address(0)) revert ZeroAddress();
+        if (bps > 10000) revert InvalidParameters();
+        if (bps > 0 && duration == 0) revert InvalidParameters();
+
+        holdbackConfigs[seller] = HoldbackConfig({bps: bps, duration: duration});
+
+        emit HoldbackConfigured(seller, bps, duration);
+    }
+
+    /// @notice Claim vested holdback funds
+    /// @dev Scans at most MAX_HOLDBACK_TRANCHES_PER_CLAIM open tranches; later ones are claimable afterwards
+    /// @param token Payout token (0 for native currency)
+    function claimHoldback(address token) external nonReentrant {
+        HoldbackTranche[] storage tranches = holdbackTranches[msg.sender][token];
+        uint256 cursor = holdbackCursor[msg.sender][token];
+        uint256 last = _holdbackScanEnd(cursor, tranches.length);
+        uint256 amount;
+        bool open;
+
+        for (uint256 i = cursor; i < last; i++) {
+            HoldbackTranche storage tranche = tranches[i];
+            uint256 vested = _vestedHoldback(tranche);
+            amount += vested - tranche.claimed;
+            tranche.claimed = vested;
+            if (!open && vested == tranche.amount) {
+                cursor = i + 1;
+            } else {
+                open = true;
+            }
+        }
+        holdbackCursor[msg.sender][token] = cursor;
+
+        if (amount == 0) revert NothingToWithdraw();
+        _transferOut(token, msg.sender, amount);
+
+        emit HoldbackClaimed(msg.sender, token, amount);
+    }
+
+    /// @notice Holdback amount a seller can claim right now
+    /// @param seller Seller address
+    /// @param token Payout token (0 for native currency)
+    /// @return amount Claimable amount
+    function claimableHoldback(address seller, address token) external view returns (uint256 amount) {
+        HoldbackTranche[] storage tranches = holdbackTranches[seller][token];
+        uint256 cursor = holdbackCursor[seller][token];
+        uint256 last = _holdbackScanEnd(cursor, tranches.length);
+        for (uint256 i = cursor; i < last; i++) {
+            amount += _vestedHoldback(tranches[i]) - tranches[i].claimed;
+        }
+    }
+
+    /// @notice Holdback amount of a seller that has not vested yet
+    /// @param seller Seller address
+    /// @param token Payout token (0 for native currency)
+    /// @return amount Locked amount
+    function lockedHoldback(address seller, address token) external view returns (uint256 amount) {
+        HoldbackTranche[] storage tranches = holdbackTranches[seller][token];
+        for (uint256 i = holdbackCursor[seller][token]; i < tranches.length; i++) {
+            amount += tranches[i].amount - _vestedHoldback(tranches[i]);
+        }
+    }
+
     /// @notice Hash listing according to EIP-712
     /// @param listing Listing data
     /// @return Listing hash with domain separator
@@ -302,18 +1265,19 @@ contract Marketplace is ReentrancyGuard {
     function _validateListing(
         SignatureLib.Listing calldata listing,
         bytes calldata sellerSignature,
-        bytes32 listingHash
+        bytes32 listingHash,
+        address buyer
     ) internal view {
         // Optimized validation sequence - start with cheap checks
 
         // 1. Ensure listing not consumed by this buyer
-        if (consumed[listingHash][msg.sender]) {
+        if (consumed[listingHash][buyer]) {
             revert AlreadyPurchased();
         }
 
         // 2. Check expiry (0 = бессрочный листинг)
         if (listing.expiry > 0 && listing.expiry < block.timestamp) {
-            revert Expired();
+            revert DeadlineExpired(listing.expiry, block.timestamp);
         }
 
         // 3. Check global SKU revocation
@@ -344,6 +1308,308 @@ contract Marketplace is ReentrancyGuard {
         }
     }
 
+    /// @dev Forward sale proceeds to the seller, or to the escrow service if the SKU uses milestones
+    /// @return orderId Milestone order holding the proceeds (0 when the seller was paid directly)
+    function _paySeller(
+        address buyer,
+        address seller,
+        bytes32 sku,
+        address token,
+        uint256 netAmount,
+        bytes32 listingHash
+    ) internal returns (uint256 orderId) {
+        address escrow = _service(ESCROW_SERVICE);
+        if (escrow == address(0) || !IMarketplaceEscrow(escrow).hasMilestones(seller, sku)) {
+            sellerStats[seller].completedSales += 1;
+            _releaseToSeller(seller, sku, token, netAmount);
+            _deliverAsset(seller, sku, buyer);
+            return 0;
+        }
+
+        uint256 value = token == address(0) ? netAmount : 0;
+        if (token != address(0)) IERC20(token).safeTransfer(escrow, netAmount);
+        orderId = IMarketplaceEscrow(escrow).openOrder{value: value}(
+            buyer,
+            seller,
+            sku,
+            token,
+            netAmount,
+            listingHash
+        );
+        _holdAsset(seller, sku, orderId);
+    }
+
+    /// @dev Side effects shared by every sale path: the license receipt, the payment commitment and the seller's
+    /// settlement hook
+    function _completeSale(
+        bytes32 saleHash,
+        address buyer,
+        address seller,
+        bytes32 sku,
+        address token,
+        uint256 paymentAmount
+    ) internal {
+        bytes32 receipt = computeReceipt(saleHash, sku, buyer);
+        saleReceipts[saleHash] = receipt;
+        emit SaleReceipt(saleHash, buyer, receipt);
+
+        address paidToken = token == 0xEeeeeEeeeEeEeeEeEeEeeEEEeeeeEeeeeeeeEEeE ? address(0) : token;
+        salePayments[saleHash] = hashSalePayment(buyer, seller, paidToken, paymentAmount);
+
+        address promotions = _service(PROMOTIONS_SERVICE);
+        if (promotions != address(0)) IMarketplacePromotions(promotions).recordPurchase(buyer, seller, sku);
+
+        _callSettlementHook(saleHash, buyer, seller, sku, token, paymentAmount);
+    }
+
+    /// @dev Notify the hook selected for a seller SKU; hooks revoked by the governor are skipped so that
+    /// the SKU stays purchasable until the seller picks another one
+    function _callSettlementHook(
+        bytes32 saleHash,
+        address buyer,
+        address seller,
+        bytes32 sku,
+        address token,
+        uint256 paymentAmount
+    ) internal {
+        address hook = settlementHooks[seller][sku];
+        if (hook == address(0) || !approvedHooks[hook]) return;
+        ISettlementHook(hook).onSettlement(saleHash, buyer, seller, sku, token, paymentAmount);
+    }
+
+    /// @dev Hand the escrowed NFT of a seller SKU to the buyer; sold-out SKUs cannot be bought again
+    function _deliverAsset(address seller, bytes32 sku, address buyer) internal {
+        EscrowedAsset storage asset = escrowedAssets[seller][sku];
+        if (asset.collection == address(0)) return;
+        if (asset.delivered || asset.pendingOrder != 0) revert NotListed();
+
+        asset.delivered = true;
+        IERC721(asset.collection).safeTransferFrom(address(this), buyer, asset.tokenId);
+
+        emit AssetReleased(seller, sku, buyer);
+    }
+
+    /// @dev Reserve the escrowed NFT of a seller SKU for a milestone order until the order closes
+    function _holdAsset(address seller, bytes32 sku, uint256 orderId) internal {
+        EscrowedAsset storage asset = escrowedAssets[seller][sku];
+        if (asset.collection == address(0)) return;
+        if (asset.delivered || asset.pendingOrder != 0) revert NotListed();
+
+        asset.pendingOrder = orderId;
+    }
+
+    /// @dev Pay out seller proceeds: royalties first, then the holdback share, the rest goes to the seller
+    function _releaseToSeller(address seller, bytes32 sku, address token, uint256 amount) internal {
+        sellerVolume[seller][token] += amount;
+        amount -= _payRoyalty(seller, sku, token, amount);
+        amount -= _lockHoldback(seller, token, amount);
+        if (amount > 0) {
+            _transferOut(token, seller, amount);
+        }
+    }
+
+    /// @dev Pay the collection and seller royalty shares of `amount`; the caller sends the remainder to the seller
+    /// @dev The seller royalty applies to what is left after the collection royalty
+    function _payRoyalty(
+        address seller,
+        bytes32 sku,
+        address token,
+        uint256 amount
+    ) internal returns (uint256 royalty) {
+        uint256 collectionId = skuCollections[seller][sku];
+        if (collectionId != 0) {
+            Collection memory collection = collections[collectionId];
+            royalty = (amount * collection.royaltyBps) / 10000;
+            if (royalty > 0) {
+                _transferOut(token, collection.curator, royalty);
+                emit CollectionRoyaltyPaid(collectionId, collection.curator, token, royalty);
+            }
+        }
+
+        RoyaltyConfig memory config = royaltyConfigs[seller];
+        if (config.bps == 0) return royalty;
+
+        uint256 sellerRoyalty = ((amount - royalty) * config.bps) / 10000;
+        if (sellerRoyalty == 0) return royalty;
+
+        _transferOut(token, config.recipient, sellerRoyalty);
+        emit RoyaltyPaid(seller, config.recipient, token, sellerRoyalty);
+        royalty += sellerRoyalty;
+    }
+
+    /// @dev Move the configured share of a payout into a new vesting tranche of the seller's holdback
+    function _lockHoldback(address seller, address token, uint256 netAmount) internal returns (uint256 held) {
+        HoldbackConfig memory config = holdbackConfigs[seller];
+        if (config.bps == 0) return 0;
+
+        held = (netAmount * config.bps) / 10000;
+        if (held == 0) return 0;
+
+        uint64 end = uint64(block.timestamp + config.duration);
+        holdbackTranches[seller][token].push(
+            HoldbackTranche({amount: held, claimed: 0, start: uint64(block.timestamp), end: end})
+        );
+
+        emit HoldbackLocked(seller, token, held, end);
+    }
+
+    /// @dev Amount of a tranche vested so far, including what was already claimed
+    function _vestedHoldback(HoldbackTranche storage tranche) internal view returns (uint256) {
+        if (block.timestamp >= tranche.end) return tranche.amount;
+        return (tranche.amount * (block.timestamp - tranche.start)) / (tranche.end - tranche.start);
+    }
+
+    /// @dev Exclusive end of the bounded tranche scan starting at `cursor`
+    function _holdbackScanEnd(uint256 cursor, uint256 length) internal pure returns (uint256) {
+        uint256 end = cursor + MAX_HOLDBACK_TRANCHES_PER_CLAIM;
+        return end < length ? end : length;
+    }
+
+    /// @dev Draw up to `amount` of the user's prepaid credit from the credit service into this contract
+    function _drawCredit(address user, address token, uint256 amount) internal returns (uint256) {
+        address credit = _service(CREDIT_SERVICE);
+        if (credit == address(0)) revert ServiceNotFound();
+        return IMarketplaceCredit(credit).drawCredit(user, token, amount);
+    }
+
+    /// @dev Refund native currency attached above what the purchase consumed
+    function _refundExcess(uint256 nativeSpent) internal {
+        uint256 excess = msg.value - nativeSpent;
+        if (excess > 0) {
+            (bool refundSuccess, ) = payable(msg.sender).call{value: excess}('');
+            if (!refundSuccess) revert RefundDisabled();
+        }
+    }
+
+    /// @dev Apply cross-sell and coupon discounts through the promotions service, which records the purchase
+    function _applyDiscounts(
+        address buyer,
+        address seller,
+        bytes32 sku,
+        bytes32 listingHash,
+        bytes32 couponId,
+        uint256 price
+    ) internal returns (uint256) {
+        address promotions = _service(PROMOTIONS_SERVICE);
+        if (promotions == address(0)) {
+            if (couponId != bytes32(0)) revert ServiceNotFound();
+            return price;
+        }
+        return IMarketplacePromotions(promotions).applyDiscounts(buyer, seller, sku, listingHash, couponId, price);
+    }
+
+    /// @dev Pay cashback through the promotions service, if one is registered; cashback on a milestone order is
+    /// held until the order closes
+    function _payCashback(
+        address buyer,
+        address seller,
+        address token,
+        uint256 paymentAmount,
+        uint256 escrowOrderId
+    ) internal {
+        address promotions = _service(PROMOTIONS_SERVICE);
+        if (promotions == address(0)) return;
+        IMarketplacePromotions(promotions).payCashback(buyer, seller, token, paymentAmount, escrowOrderId);
+    }
+
+    /// @dev Run funds escrowed in this contract through the gateway on behalf of `payer`
+    /// @dev Discounts or unused fee budget returned by the gateway are forwarded to `payer`
+    function _processEscrowedPayment(
+        address token,
+        address payer,
+        uint256 amount
+    ) internal returns (uint256 netAmount) {
+        if (token == address(0)) {
+            return paymentGateway.processPayment{value: amount}(MODULE_ID, address(0), payer, amount, '');
+        }
+
+        uint256 balanceBefore = IERC20(token).balanceOf(address(this));
+        IERC20(token).forceApprove(address(paymentGateway), amount);
+        netAmount = paymentGateway.processPayment(MODULE_ID, token, address(this), amount, '');
+
+        uint256 returned = IERC20(token).balanceOf(address(this)) + amount - balanceBefore - netAmount;
+        if (returned > 0) IERC20(token).safeTransfer(payer, returned);
+    }
+
+    /// @dev Linearly interpolate from `startPrice` to the scheduled floor for the seller SKU
+    function _scheduledPrice(address seller, bytes32 sku, uint256 startPrice) internal view returns (uint256) {
+        DutchSchedule memory schedule = dutchSchedules[seller][sku];
+        if (schedule.endTime == 0 || schedule.endPrice >= startPrice || block.timestamp <= schedule.startTime) {
+            return startPrice;
+        }
+        if (block.timestamp >= schedule.endTime) return schedule.endPrice;
+
+        uint256 elapsed = block.timestamp - schedule.startTime;
+        uint256 span = schedule.endTime - schedule.startTime;
+        return startPrice - ((startPrice - schedule.endPrice) * elapsed) / span;
+    }
+
+    /// @dev Count a purchase against the seller's per-buyer limit for `sku`
+    function _recordPurchase(address buyer, address seller, bytes32 sku) internal {
+        PurchaseLimit memory limit = purchaseLimits[seller][sku];
+        if (limit.maxPerBuyer == 0) return;
+
+        bytes32 key = _purchaseKey(buyer, limit);
+        uint32 count = purchaseCounts[seller][sku][key] + 1;
+        if (count > limit.maxPerBuyer) revert LimitExceeded();
+        purchaseCounts[seller][sku][key] = count;
+    }
+
+    /// @notice Whether `buyer` can still make one more purchase of the seller SKU
+    /// @param buyer Buyer address
+    /// @param seller Seller address
+    /// @param sku Item SKU
+    function withinPurchaseLimit(address buyer, address seller, bytes32 sku) public view returns (bool) {
+        PurchaseLimit memory limit = purchaseLimits[seller][sku];
+        if (limit.maxPerBuyer == 0) return true;
+        return purchaseCounts[seller][sku][_purchaseKey(buyer, limit)] < limit.maxPerBuyer;
+    }
+
+    /// @dev Purchase counter key: the buyer's wallet cluster for cluster-wide limits, else the wallet itself
+    function _purchaseKey(address buyer, PurchaseLimit memory limit) internal view returns (bytes32) {
+        bytes32 cluster = walletClusters[buyer];
+        return limit.clusterWide && cluster != bytes32(0) ? cluster : bytes32(uint256(uint160(buyer)));
+    }
+
+    /// @dev Consume a signer's nonce, rejecting replays of any intent signed with it
+    function _useNonce(address signer, uint256 nonce) internal {
+        if (usedNonces[signer][nonce]) revert NonceAlreadyUsed();
+        usedNonces[signer][nonce] = true;
+        emit NonceUsed(signer, nonce);
+    }
+
+    /// @dev Clear the reservation on `listingHash` once it is consumed by its holder or has expired.
+    /// Reverts while another buyer holds an active reservation.
+    function _settleReservation(bytes32 listingHash, address buyer) internal {
+        Reservation memory r = reservations[listingHash];
+        if (r.buyer == address(0)) return;
+
+        bool expired = r.expiresAt <= block.timestamp;
+        if (!expired && r.buyer != buyer) revert Forbidden();
+
+        delete reservations[listingHash];
+        address recipient = expired ? r.seller : r.buyer;
+        _transferOut(address(0), recipient, r.deposit);
+
+        emit ReservationReleased(listingHash, r.buyer, recipient);
+    }
+
+    /// @dev Satellite service registered in core for this module, zero if the feature is not deployed
+    function _service(string memory name) internal view returns (address) {
+        return core.getService(MODULE_ID, name);
+    }
+
+    /// @dev Send native currency or ERC-20 tokens held by the marketplace
+    function _transferOut(address token, address to, uint256 amount) internal {
+        if (amount == 0) return;
+        if (token == address(0)) {
+            (bool success, ) = payable(to).call{value: amount}('');
+            if (!success) revert TransferFailed();
+        } else {
+            IERC20(token).safeTransfer(to, amount);
+        }
+    }
+
     /// @notice Allows the contract to receive ETH (required for native currency payments)
     receive() external payable {}
 
@@ -0,0 +1,366 @@
+// SPDX-License-Identifier: MIT
+pragma solidity ^0.8.28;
+
+import '../../core/CoreSystem.sol';
+import '../../errors/Errors.sol';
+import '../../pay/interfaces/IPaymentGateway.sol';
+import './interfaces/IMarketplace.sol';
+import '@openzeppelin/contracts/token/ERC20/IERC20.sol';
+import '@openzeppelin/contracts/token/ERC20/utils/SafeERC20.sol';
+import '@openzeppelin/contracts/utils/ReentrancyGuard.sol';
+
+/// @title MarketplaceAuctions
+/// @notice English auctions and buyer offers on Marketplace seller SKUs
+/// @dev Registered in core as the `MarketplaceAuctions` service of the marketplace module. Winning bids and
+/// accepted offers are handed to the marketplace, which settles them through the gateway like any other sale.
+contract MarketplaceAuctions is ReentrancyGuard {
+    using SafeERC20 for IERC20;
+
+    CoreSystem public immutable core;
+    address public immutable marketplace;
+    bytes32 public immutable MODULE_ID;
+
+    // English auctions: bids are escrowed here, outbid bidders are refunded immediately
+    struct Auction {
+        address seller;
+        bytes32 sku;
+        address token;
+        uint256 reservePrice;
+        uint16 minIncrementBps;
+        uint64 endTime;
+        address highestBidder;
+        uint256 highestBid;
+        bool settled;
+    }
+
+    uint32 public constant MAX_AUCTION_DURATION = 30 days;
+    uint32 public constant SETTLEMENT_GRACE_PERIOD = 7 days; // after this the winner may reclaim an unsettled bid
+    uint256 public auctionCount;
+    mapping(uint256 => Auction) public auctions;
+    mapping(address => uint256) public auctionRefunds; // bidder => native refunds that could not be pushed
+    mapping(address => mapping(bytes32 => uint256)) public openAuctions; // seller => escrowed sku => auction
+
+    // Buyer offers on seller SKUs, escrowed until accepted, cancelled or expired
+    struct Offer {
+        address buyer;
+        address seller;
+        bytes32 sku;
+        address token;
+        uint256 amount;
+        uint64 expiresAt;
+        bool closed;
+    }
+
+    uint256 public offerCount;
+    mapping(uint256 => Offer) public offers;
+
+    event AuctionCreated(
+        uint256 indexed auctionId,
+        address indexed seller,
+        bytes32 indexed sku,
+        address token,
+        uint256 reservePrice,
+        uint16 minIncrementBps,
+        uint64 endTime
+    );
+    event AuctionBid(uint256 indexed auctionId, address indexed bidder, uint256 amount);
+    event AuctionSettled(uint256 indexed auctionId, address indexed winner, uint256 amount, uint256 netAmount);
+    event AuctionCancelled(uint256 indexed auctionId);
+    event AuctionRefundWithdrawn(address indexed bidder, uint256 amount);
+    event AuctionBidReclaimed(uint256 indexed auctionId, address indexed bidder, uint256 amount);
+    event OfferMade(
+        uint256 indexed offerId,
+        address indexed buyer,
+        address indexed seller,
+        bytes32 sku,
+        address token,
+        uint256 amount,
+        uint64 expiresAt
+    );
+    event OfferCancelled(uint256 indexed offerId);
+    event OfferAccepted(uint256 indexed offerId, uint256 netAmount);
+
+    modifier whenModuleActive() {
+        if (core.isModulePaused(MODULE_ID)) revert ModulePaused();
+        _;
+    }
+
+    constructor(address coreAddress, address marketplaceAddress, bytes32 moduleId) {
+        if (coreAddress == address(0) || marketplaceAddress == address(0)) revert ZeroAddress();
+        core = CoreSystem(coreAddress);
+        marketplace = marketplaceAddress;
+        MODULE_ID = moduleId;
+    }
+
+    /// @notice Start an English auction for one of the caller's SKUs
+    /// @dev The bid currency must pass the module's token filter. An SKU backed by an escrowed NFT can only be
+    /// in one open auction at a time.
+    /// @param sku Item SKU
+    /// @param token Bid currency (0 for native)
+    /// @param reservePrice Minimum first bid
+    /// @param minIncrementBps Minimum raise over the current highest bid in basis points
+    /// @param duration Auction length in seconds
+    /// @return auctionId Auction identifier
+    function createAuction(
+        bytes32 sku,
+        address token,
+        uint256 reservePrice,
+        uint16 minIncrementBps,
+        uint32 duration
+    ) external whenModuleActive returns (uint256 auctionId) {
+        if (reservePrice == 0) revert InvalidPrice();
+        if (minIncrementBps > 10000) revert InvalidArgument();
+        if (duration == 0 || duration > MAX_AUCTION_DURATION) revert InvalidArgument();
+
+        IMarketplace market = IMarketplace(marketplace);
+        if (!IPaymentGateway(market.paymentGateway()).isPairSupported(MODULE_ID, token, token)) {
+            revert UnsupportedPair();
+        }
+
+        auctionId = ++auctionCount;
+        (address collection, , bool delivered, ) = market.escrowedAssets(msg.sender, sku);
+        if (collection != address(0) && !delivered) {
+            if (openAuctions[msg.sender][sku] != 0) revert InvalidState();
+            openAuctions[msg.sender][sku] = auctionId;
+        }
+
+        uint64 endTime = uint64(block.timestamp) + duration;
+        auctions[auctionId] = Auction({
+            seller: msg.sender,
+            sku: sku,
+            token: token,
+            reservePrice: reservePrice,
+            minIncrementBps: minIncrementBps,
+            endTime: endTime,
+            highestBidder: address(0),
+            highestBid: 0,
+            settled: false
+        });
+
+        emit AuctionCreated(auctionId, msg.sender, sku, token, reservePrice, minIncrementBps, endTime);
+    }
+
+    /// @notice Bid on an auction, escrowing the bid and refunding the previous highest bidder
+    /// @param auctionId Auction identifier
+    /// @param amount Bid amount (must equal msg.value for native auctions)
+    function placeBid(uint256 auctionId, uint256 amount) external payable whenModuleActive nonReentrant {
+        Auction storage auction = auctions[auctionId];
+        if (auction.seller == address(0)) revert NotFound();
+        if (auction.settled || block.timestamp >= auction.endTime) revert Expired();
+        if (msg.sender == auction.seller) revert Forbidden();
+        if (!_withinPurchaseLimit(msg.sender, auction.seller, auction.sku)) revert LimitExceeded();
+
+        uint256 minBid = auction.reservePrice;
+        if (auction.highestBid > 0) {
+            uint256 increment = (auction.highestBid * auction.minIncrementBps) / 10000;
+            minBid = auction.highestBid + (increment == 0 ? 1 : increment);
+        }
+        if (amount < minBid) revert InvalidPrice();
+
+        if (auction.token == address(0)) {
+            if (msg.value != amount) revert InvalidAmount();
+        } else {
+            if (msg.value != 0) revert InvalidAmount();
+            IERC20(auction.token).safeTransferFrom(msg.sender, address(this), amount);
+        }
+
+        address previousBidder = auction.highestBidder;
+        uint256 previousBid = auction.highestBid;
+        auction.highestBidder = msg.sender;
+        auction.highestBid = amount;
+
+        if (previousBidder != address(0)) {
+            if (auction.token == address(0)) {
+                (bool success, ) = payable(previousBidder).call{value: previousBid}('');
+                if (!success) auctionRefunds[previousBidder] += previousBid;
+            } else {
+                IERC20(auction.token).safeTransfer(previousBidder, previousBid);
+            }
+        }
+
+        emit AuctionBid(auctionId, msg.sender, amount);
+    }
+
+    /// @notice Settle an ended auction, paying the seller through the marketplace like a fixed-price sale
+    /// @dev Allowed while the module is paused so escrowed bids never get stuck. A winner who reached the
+    /// seller's purchase limit since bidding is refunded instead. If the sale keeps reverting, the winner can
+    /// reclaim the bid once the settlement grace period is over.
+    /// @param auctionId Auction identifier
+    function settleAuction(uint256 auctionId) external nonReentrant {
+        Auction storage auction = auctions[auctionId];
+        if (auction.seller == address(0)) revert NotFound();
+        if (auction.settled) revert InvalidState();
+        if (block.timestamp < auction.endTime) revert NotDue();
+
+        _closeAuction(auctionId);
+        address winner = auction.highestBidder;
+        if (winner == address(0)) {
+            emit AuctionCancelled(auctionId);
+            return;
+        }
+
+        uint256 amount = auction.highestBid;
+        if (!_withinPurchaseLimit(winner, auction.seller, auction.sku)) {
+            _transferOut(auction.token, winner, amount);
+            emit AuctionCancelled(auctionId);
+            return;
+        }
+
+        bytes32 auctionHash = keccak256(abi.encodePacked('auction', auctionId));
+        uint256 netAmount = _settleSale(winner, auction.seller, auction.sku, auction.token, amount, auctionHash);
+
+        emit AuctionSettled(auctionId, winner, amount, netAmount);
+    }
+
+    /// @notice Cancel an auction that has not received any bids
+    /// @param auctionId Auction identifier
+    function cancelAuction(uint256 auctionId) external {
+        Auction storage auction = auctions[auctionId];
+        if (auction.seller != msg.sender) revert NotSeller();
+        if (auction.settled || auction.highestBidder != address(0)) revert InvalidState();
+
+        _closeAuction(auctionId);
+        emit AuctionCancelled(auctionId);
+    }
+
+    /// @notice Reclaim a winning bid whose auction was not settled within the grace period
+    /// @dev Covers sales the marketplace keeps rejecting, e.g. a reverting settlement hook or an NFT the
+    /// winner cannot receive
+    /// @param auctionId Auction identifier
+    function reclaimBid(uint256 auctionId) external nonReentrant {
+        Auction storage auction = auctions[auctionId];
+        if (auction.highestBidder != msg.sender) revert Unauthorized();
+        if (auction.settled) revert InvalidState();
+        if (block.timestamp < uint256(auction.endTime) + SETTLEMENT_GRACE_PERIOD) revert NotDue();
+
+        _closeAuction(auctionId);
+        _transferOut(auction.token, msg.sender, auction.highestBid);
+
+        emit AuctionBidReclaimed(auctionId, msg.sender, auction.highestBid);
+        emit AuctionCancelled(auctionId);
+    }
+
+    /// @notice Withdraw native auction refunds that could not be pushed when outbid
+    function withdrawAuctionRefund() external nonReentrant {
+        uint256 amount = auctionRefunds[msg.sender];
+        if (amount == 0) revert NothingToWithdraw();
+        auctionRefunds[msg.sender] = 0;
+        _transferOut(address(0), msg.sender, amount);
+
+        emit AuctionRefundWithdrawn(msg.sender, amount);
+    }
+
+    /// @notice Offer to buy a seller's SKU, escrowing the offered amount
+    /// @param seller Seller address
+    /// @param sku Item SKU
+    /// @param token Offer currency (0 for native)
+    /// @param amount Offered amount (must equal msg.value for native offers)
+    /// @param expiresAt Time after which the offer can no longer be accepted (0 = no expiry)
+    /// @return offerId Offer identifier
+    function makeOffer(
+        address seller,
+        bytes32 sku,
+        address token,
+        uint256 amount,
+        uint64 expiresAt
+    ) external payable whenModuleActive nonReentrant returns (uint256 offerId) {
+        if (seller == address(0)) revert ZeroAddress();
+        if (seller == msg.sender) revert Forbidden();
+        if (amount == 0) revert AmountZero();
+        if (expiresAt != 0 && expiresAt <= block.timestamp) revert Expired();
+        if (!_withinPurchaseLimit(msg.sender, seller, sku)) revert LimitExceeded();
+
+        if (token == address(0)) {
+            if (msg.value != amount) revert InvalidAmount();
+        } else {
+            if (msg.value != 0) revert InvalidAmount();
+            IERC20(token).safeTransferFrom(msg.sender, address(this), amount);
+        }
+
+        offerId = ++offerCount;
+        offers[offerId] = Offer({
+            buyer: msg.sender,
+            seller: seller,
+            sku: sku,
+            token: token,
+            amount: amount,
+            expiresAt: expiresAt,
+            closed: false
+        });
+
+        emit OfferMade(offerId, msg.sender, seller, sku, token, amount, expiresAt);
+    }
+
+    /// @notice Withdraw an open offer and reclaim the escrowed amount
+    /// @param offerId Offer identifier
+    function cancelOffer(uint256 offerId) external nonReentrant {
+        Offer storage offer = offers[offerId];
+        if (offer.buyer != msg.sender) revert Unauthorized();
+        if (offer.closed) revert InvalidState();
+
+        offer.closed = true;
+        _transferOut(offer.token, offer.buyer, offer.amount);
+
+        emit OfferCancelled(offerId);
+    }
+
+    /// @notice Accept an open offer, settling it like a regular sale
+    /// @dev Allowed while the module is paused, like auction settlement; buyers can always cancel instead
+    /// @param offerId Offer identifier
+    function acceptOffer(uint256 offerId) external nonReentrant {
+        Offer storage offer = offers[offerId];
+        if (offer.seller != msg.sender) revert NotSeller();
+        if (offer.closed) revert InvalidState();
+        if (offer.expiresAt != 0 && block.timestamp > offer.expiresAt) {
+            revert DeadlineExpired(offer.expiresAt, block.timestamp);
+        }
+
+        offer.closed = true;
+        bytes32 offerHash = keccak256(abi.encodePacked('offer', offerId));
+        uint256 netAmount = _settleSale(offer.buyer, offer.seller, offer.sku, offer.token, offer.amount, offerHash);
+
+        emit OfferAccepted(offerId, netAmount);
+    }
+
+    /// @dev Mark an auction as closed and free its escrowed SKU for a new auction
+    function _closeAuction(uint256 auctionId) internal {
+        Auction storage auction = auctions[auctionId];
+        auction.settled = true;
+        if (openAuctions[auction.seller][auction.sku] == auctionId) {
+            delete openAuctions[auction.seller][auction.sku];
+        }
+    }
+
+    /// @dev Hand an escrowed bid or offer to the marketplace, which settles it like a regular sale
+    function _settleSale(
+        address buyer,
+        address seller,
+        bytes32 sku,
+        address token,
+        uint256 amount,
+        bytes32 saleHash
+    ) internal returns (uint256) {
+        IMarketplace market = IMarketplace(marketplace);
+        if (token == address(0)) {
+            return market.settleEscrowedSale{value: amount}(buyer, seller, sku, token, amount, saleHash);
+        }
+        IERC20(token).safeTransfer(marketplace, amount);
+        return market.settleEscrowedSale(buyer, seller, sku, token, amount, saleHash);
+    }
+
+    /// @dev Whether `buyer` can still make one more purchase of the seller SKU under the marketplace limits
+    function _withinPurchaseLimit(address buyer, address seller, bytes32 sku) internal view returns (bool) {
+        return IMarketplace(marketplace).withinPurchaseLimit(buyer, seller, sku);
+    }
+
+    /// @dev Send native currency or ERC-20 tokens held by this contract
+    function _transferOut(address token, address to, uint256 amount) internal {
+        if (amount == 0) return;
+        if (token == address(0)) {
+            (bool success, ) = payable(to).call{value: amount}('');
+            if (!success) revert TransferFailed();
+        } else {
+            IERC20(token).safeTransfer(to, amount);
+        }
+    }
+}
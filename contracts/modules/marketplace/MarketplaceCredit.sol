@@ -0,0 +1,78 @@
+// SPDX-License-Identifier: MIT
+pragma solidity ^0.8.28;
+
+import '../../errors/Errors.sol';
+import './interfaces/IMarketplaceServices.sol';
+import '@openzeppelin/contracts/token/ERC20/IERC20.sol';
+import '@openzeppelin/contracts/token/ERC20/utils/SafeERC20.sol';
+import '@openzeppelin/contracts/utils/ReentrancyGuard.sol';
+
+/// @title MarketplaceCredit
+/// @notice Prepaid credit balances (gift cards, promotional credit) spent through the Marketplace
+/// @dev Registered in core as the `MarketplaceCredit` service of the marketplace module
+contract MarketplaceCredit is IMarketplaceCredit, ReentrancyGuard {
+    using SafeERC20 for IERC20;
+
+    address public immutable marketplace;
+
+    mapping(address => mapping(address => uint256)) public credits; // user => token => balance
+
+    event CreditDeposited(address indexed depositor, address indexed beneficiary, address token, uint256 amount);
+    event CreditSpent(address indexed user, address token, uint256 amount);
+
+    constructor(address marketplaceAddress) {
+        if (marketplaceAddress == address(0)) revert ZeroAddress();
+        marketplace = marketplaceAddress;
+    }
+
+    /// @notice Top up prepaid credit for a user
+    /// @param beneficiary Credit owner
+    /// @param token Credit token (0 for native currency)
+    /// @param amount Amount to deposit
+    function depositCredit(address beneficiary, address token, uint256 amount) external payable nonReentrant {
+        if (beneficiary == address(0)) revert ZeroAddress();
+        if (amount == 0) revert AmountZero();
+
+        if (token == 0xEeeeeEeeeEeEeeEeEeEeeEEEeeeeEeeeeeeeEEeE) token = address(0);
+
+        uint256 received = amount;
+        if (token == address(0)) {
+            if (msg.value != amount) revert InvalidAmount();
+        } else {
+            if (msg.value != 0) revert InvalidAmount();
+            uint256 balanceBefore = IERC20(token).balanceOf(address(this));
+            IERC20(token).safeTransferFrom(msg.sender, address(this), amount);
+            received = IERC20(token).balanceOf(address(this)) - balanceBefore;
+            if (received == 0) revert InvalidAmount();
+        }
+
+        credits[beneficiary][token] += received;
+
+        emit CreditDeposited(msg.sender, beneficiary, token, received);
+    }
+
+    /// @notice Debit up to `amount` of the user's credit and send it to the marketplace
+    /// @dev Only callable by the marketplace while it settles a purchase for `user`
+    /// @param user Credit owner
+    /// @param token Credit token (0 for native currency)
+    /// @param amount Maximum amount to draw
+    /// @return drawn Amount debited and transferred
+    function drawCredit(address user, address token, uint256 amount) external nonReentrant returns (uint256 drawn) {
+        if (msg.sender != marketplace) revert Unauthorized();
+
+        uint256 balance = credits[user][token];
+        drawn = balance < amount ? balance : amount;
+        if (drawn == 0) return 0;
+
+        credits[user][token] = balance - drawn;
+
+        if (token == address(0)) {
+            (bool success, ) = payable(marketplace).call{value: drawn}('');
+            if (!success) revert TransferFailed();
+        } else {
+            IERC20(token).safeTransfer(marketplace, drawn);
+        }
+
+        emit CreditSpent(user, token, drawn);
+    }
+}
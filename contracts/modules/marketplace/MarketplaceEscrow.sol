@@ -0,0 +1,476 @@
+// SPDX-License-Identifier: MIT
+pragma solidity ^0.8.28;
+
+import '../../core/CoreSystem.sol';
+import '../../core/CoreDefs.sol';
+import '../../errors/Errors.sol';
+import './interfaces/IMarketplace.sol';
+import './interfaces/IMarketplaceServices.sol';
+import '@openzeppelin/contracts/token/ERC20/IERC20.sol';
+import '@openzeppelin/contracts/token/ERC20/utils/SafeERC20.sol';
+import '@openzeppelin/contracts/utils/ReentrancyGuard.sol';
+
+/// @title MarketplaceEscrow
+/// @notice Milestone escrow for Marketplace service listings
+/// @dev Registered in core as the `MarketplaceEscrow` service of the marketplace module. Released funds go back
+/// through the marketplace so royalties, holdback and seller stats apply as for direct sales.
+/// Rulings come from holders of the core ARBITER_ROLE. A dispute the arbiter leaves unruled past its deadline
+/// settles by default: shipped orders are released to the seller, unshipped ones refunded to the buyer.
+contract MarketplaceEscrow is IMarketplaceEscrow, ReentrancyGuard {
+    using SafeERC20 for IERC20;
+
+    CoreSystem public immutable core;
+    address public immutable marketplace;
+    bytes32 public immutable MODULE_ID;
+
+    // Milestone escrow for service listings
+    struct MilestoneOrder {
+        address buyer;
+        address seller;
+        address token;
+        bytes32 listingHash;
+        uint256 escrowed;
+        uint256 released;
+        uint16[] milestoneBps;
+        uint8 approvedCount;
+        bool closed;
+        bool disputed;
+        uint64 shippedAt;
+        bytes32 sku;
+        uint32 acceptanceWindow; // snapshot of the global window when the order was opened
+        uint64 disputeDeadline; // end of the arbiter's ruling window while disputed
+    }
+
+    uint8 public constant MAX_MILESTONES = 10;
+    uint256 public milestoneOrderCount;
+    uint32 public acceptanceWindow = 14 days; // buyer review period after shipment before anyone may release
+    uint16 public constant MAX_CRANK_BOUNTY_BPS = 100;
+    uint16 public crankBountyBps; // share of an auto-released escrow paid to the caller
+    uint32 public disputeWindow = 30 days; // time the arbiter has to rule on a dispute
+    mapping(address => mapping(bytes32 => uint16[])) private milestoneSchedules; // seller => sku => bps
+    mapping(uint256 => MilestoneOrder) private milestoneOrders;
+    mapping(address => uint256[]) private sellerMilestoneOrders; // seller => order ids, in creation order
+    mapping(address => uint256[]) private buyerMilestoneOrders; // buyer => order ids, in creation order
+
+    event MilestoneScheduleUpdated(address indexed seller, bytes32 indexed sku, uint16[] milestoneBps);
+    event MilestoneOrderOpened(
+        uint256 indexed orderId,
+        address indexed buyer,
+        address indexed seller,
+        bytes32 listingHash,
+        address token,
+        uint256 escrowed
+    );
+    event MilestoneReleased(uint256 indexed orderId, uint8 indexed milestone, uint256 amount);
+    event MilestoneOrderResolved(uint256 indexed orderId, bool releasedToSeller, uint256 amount);
+    event MilestoneDisputeOpened(uint256 indexed orderId, address indexed openedBy);
+    event MilestoneEvidenceSubmitted(uint256 indexed orderId, address indexed submitter, bytes32 evidenceHash);
+    event MilestoneOrderSplit(uint256 indexed orderId, uint256 sellerAmount, uint256 buyerAmount);
+    event MilestoneOrderShipped(uint256 indexed orderId, uint64 shippedAt);
+    event MilestoneOrderAutoReleased(uint256 indexed orderId, address indexed caller, uint256 amount, uint256 bounty);
+    event AcceptanceWindowUpdated(uint32 window);
+    event CrankBountyUpdated(uint16 bps);
+    event DisputeWindowUpdated(uint32 window);
+    event MilestoneDisputeExpired(uint256 indexed orderId, bool releasedToSeller, uint256 amount);
+
+    modifier onlyOperator() {
+        if (!core.hasRole(CoreDefs.OPERATOR_ROLE, msg.sender)) revert NotOperator();
+        _;
+    }
+
+    modifier onlyArbiter() {
+        if (!core.hasRole(CoreDefs.ARBITER_ROLE, msg.sender)) revert NotArbiter();
+        _;
+    }
+
+    constructor(address coreAddress, address marketplaceAddress, bytes32 moduleId) {
+        if (coreAddress == address(0) || marketplaceAddress == address(0)) revert ZeroAddress();
+        core = CoreSystem(coreAddress);
+        marketplace = marketplaceAddress;
+        MODULE_ID = moduleId;
+    }
+
+    /// @notice Configure milestone escrow for one of the caller's SKUs
+    /// @dev Marketplace purchases of the SKU keep the net proceeds here until milestones are approved.
+    /// An empty schedule disables escrow for the SKU.
+    /// @param sku Item SKU
+    /// @param milestoneBps Share of each milestone in basis points, must sum to 10000
+    function setMilestoneSchedule(bytes32 sku, uint16[] calldata milestoneBps) external {
+        uint256 count = milestoneBps.length;
+        if (count > MAX_MILESTONES) revert BatchTooLarge();
+
+        if (count > 0) {
+            uint256 total;
+            for (uint256 i = 0; i < count; i++) {
+                if (milestoneBps[i] == 0) revert InvalidParameters();
+                total += milestoneBps[i];
+            }
+            if (total != 10000) revert InvalidParameters();
+        }
+
+        milestoneSchedules[msg.sender][sku] = milestoneBps;
+
+        emit MilestoneScheduleUpdated(msg.sender, sku, milestoneBps);
+    }
+
+    /// @notice Approve the next milestone of an escrowed order, releasing its share to the seller
+    /// @param orderId Milestone order identifier
+    function approveMilestone(uint256 orderId) external nonReentrant {
+        MilestoneOrder storage order = milestoneOrders[orderId];
+        if (order.buyer == address(0)) revert NotFound();
+        if (msg.sender != order.buyer) revert Unauthorized();
+        if (order.closed || order.disputed) revert InvalidState();
+
+        uint8 milestone = order.approvedCount;
+        uint256 amount;
+        if (milestone + 1 == order.milestoneBps.length) {
+            // Last milestone receives the rounding remainder
+            amount = order.escrowed - order.released;
+            order.closed = true;
+        } else {
+            amount = (order.escrowed * order.milestoneBps[milestone]) / 10000;
+        }
+
+        order.approvedCount = milestone + 1;
+        order.released += amount;
+
+        _release(order, amount);
+        if (order.closed) {
+            IMarketplace(marketplace).closeEscrowOrder(orderId, order.buyer, order.seller, order.sku, true, false);
+        }
+
+        emit MilestoneReleased(orderId, milestone, amount);
+    }
+
+    /// @notice Mark an escrowed order as shipped, starting the buyer's acceptance window
+    /// @param orderId Milestone order identifier
+    function markShipped(uint256 orderId) external {
+        MilestoneOrder storage order = milestoneOrders[orderId];
+        if (order.buyer == address(0)) revert NotFound();
+        if (msg.sender != order.seller) revert NotSeller();
+        if (order.closed || order.disputed || order.shippedAt != 0) revert InvalidState();
+
+        order.shippedAt = uint64(block.timestamp);
+        emit MilestoneOrderShipped(orderId, order.shippedAt);
+    }
+
+    /// @notice Release the remaining escrow to the seller once the acceptance window has passed
+    /// @dev Permissionless; the buyer can stop it by opening a dispute within the window.
+    /// The caller earns `crankBountyBps` of the released amount, taken from the seller's share.
+    /// @param orderId Milestone order identifier
+    function autoReleaseMilestoneOrder(uint256 orderId) external nonReentrant {
+        MilestoneOrder storage order = milestoneOrders[orderId];
+        if (order.buyer == address(0)) revert NotFound();
+        if (order.closed || order.shippedAt == 0 || order.disputed) revert InvalidState();
+        if (block.timestamp < order.shippedAt + order.acceptanceWindow) revert NotDue();
+
+        uint256 bounty = ((order.escrowed - order.released) * crankBountyBps) / 10000;
+        if (bounty > 0) {
+            order.released += bounty;
+            _transferOut(order.token, msg.sender, bounty);
+        }
+
+        (uint256 sellerAmount, ) = _resolveMilestoneOrder(orderId, 10000, false);
+        emit MilestoneOrderAutoReleased(orderId, msg.sender, sellerAmount, bounty);
+    }
+
+    /// @notice Set the bounty paid to whoever triggers an auto-release
+    /// @param bps Share of the released escrow in basis points
+    function setCrankBounty(uint16 bps) external onlyOperator {
+        if (bps > MAX_CRANK_BOUNTY_BPS) revert InvalidArgument();
+        crankBountyBps = bps;
+        emit CrankBountyUpdated(bps);
+    }
+
+    /// @notice Set how long buyers have to accept or dispute a shipped order
+    /// @dev Applies to orders opened afterwards; open orders keep the window they were opened with
+    /// @param window Acceptance window in seconds
+    function setAcceptanceWindow(uint32 window) external onlyOperator {
+        if (window == 0) revert InvalidArgument();
+        acceptanceWindow = window;
+        emit AcceptanceWindowUpdated(window);
+    }
+
+    /// @notice Set how long the arbiter has to rule once a dispute is opened
+    /// @dev Applies to disputes opened afterwards
+    /// @param window Ruling window in seconds
+    function setDisputeWindow(uint32 window) external onlyOperator {
+        if (window == 0) revert InvalidArgument();
+        disputeWindow = window;
+        emit DisputeWindowUpdated(window);
+    }
+
+    /// @notice Arbiter fallback: settle the remaining escrow of a milestone order
+    /// @param orderId Milestone order identifier
+    /// @param releaseToSeller Release remaining funds to the seller (true) or refund the buyer (false)
+    /// @param refundToCredit Book a buyer refund as prepaid credit instead of transferring it out
+    function resolveMilestoneOrder(
+        uint256 orderId,
+        bool releaseToSeller,
+        bool refundToCredit
+    ) external onlyArbiter nonReentrant {
+        _requireRulingOpen(orderId);
+        (uint256 sellerAmount, uint256 buyerAmount) = _resolveMilestoneOrder(
+            orderId,
+            releaseToSeller ? 10000 : 0,
+            refundToCredit
+        );
+        emit MilestoneOrderResolved(orderId, releaseToSeller, sellerAmount + buyerAmount);
+    }
+
+    /// @notice Arbiter ruling splitting the remaining escrow of a milestone order
+    /// @param orderId Milestone order identifier
+    /// @param sellerBps Share of the remaining escrow released to the seller in basis points
+    /// @param refundToCredit Book the buyer share as prepaid credit instead of transferring it out
+    function splitMilestoneOrder(
+        uint256 orderId,
+        uint16 sellerBps,
+        bool refundToCredit
+    ) external onlyArbiter nonReentrant {
+        _requireRulingOpen(orderId);
+        (uint256 sellerAmount, uint256 buyerAmount) = _resolveMilestoneOrder(orderId, sellerBps, refundToCredit);
+        emit MilestoneOrderSplit(orderId, sellerAmount, buyerAmount);
+    }
+
+    /// @notice Apply the default outcome to a dispute the arbiter did not rule on in time
+    /// @dev Permissionless. The seller gets the remaining escrow if the order shipped before the dispute,
+    /// otherwise the buyer is refunded
+    /// @param orderId Milestone order identifier
+    function settleExpiredDispute(uint256 orderId) external nonReentrant {
+        MilestoneOrder storage order = milestoneOrders[orderId];
+        if (order.buyer == address(0)) revert NotFound();
+        if (order.closed || !order.disputed) revert InvalidState();
+        if (block.timestamp < order.disputeDeadline) revert NotDue();
+
+        bool releaseToSeller = order.shippedAt != 0;
+        (uint256 sellerAmount, uint256 buyerAmount) = _resolveMilestoneOrder(
+            orderId,
+            releaseToSeller ? 10000 : 0,
+            false
+        );
+        emit MilestoneDisputeExpired(orderId, releaseToSeller, sellerAmount + buyerAmount);
+    }
+
+    /// @notice Open a dispute on a milestone order, freezing further milestone approvals
+    /// @dev The arbiter has `disputeWindow` to rule before the default outcome applies
+    /// @param orderId Milestone order identifier
+    function openMilestoneDispute(uint256 orderId) external {
+        MilestoneOrder storage order = milestoneOrders[orderId];
+        if (order.buyer == address(0)) revert NotFound();
+        if (msg.sender != order.buyer && msg.sender != order.seller) revert Unauthorized();
+        if (order.closed || order.disputed) revert InvalidState();
+
+        order.disputed = true;
+        order.disputeDeadline = uint64(block.timestamp + disputeWindow);
+        emit MilestoneDisputeOpened(orderId, msg.sender);
+    }
+
+    /// @notice Record the hash of off-chain evidence for a disputed milestone order
+    /// @param orderId Milestone order identifier
+    /// @param evidenceHash Hash of the evidence bundle
+    function submitMilestoneEvidence(uint256 orderId, bytes32 evidenceHash) external {
+        MilestoneOrder storage order = milestoneOrders[orderId];
+        if (order.buyer == address(0)) revert NotFound();
+        if (msg.sender != order.buyer && msg.sender != order.seller) revert Unauthorized();
+        if (!order.disputed || order.closed) revert InvalidState();
+        if (block.timestamp >= order.disputeDeadline) revert Expired();
+
+        emit MilestoneEvidenceSubmitted(orderId, msg.sender, evidenceHash);
+    }
+
+    /// @notice Whether purchases of a seller SKU are escrowed per milestone
+    /// @param seller Seller address
+    /// @param sku Item SKU
+    function hasMilestones(address seller, bytes32 sku) external view returns (bool) {
+        return milestoneSchedules[seller][sku].length > 0;
+    }
+
+    /// @notice Open a milestone order for a sale the marketplace just settled
+    /// @dev Only callable by the marketplace, which sends the net proceeds along (as value for native currency)
+    /// @param buyer Buyer address
+    /// @param seller Seller address
+    /// @param sku Item SKU
+    /// @param token Payout token (0 for native currency)
+    /// @param amount Net proceeds to escrow
+    /// @param listingHash Hash of the purchased listing
+    /// @return orderId Milestone order identifier
+    function openOrder(
+        address buyer,
+        address seller,
+        bytes32 sku,
+        address token,
+        uint256 amount,
+        bytes32 listingHash
+    ) external payable returns (uint256 orderId) {
+        if (msg.sender != marketplace) revert Unauthorized();
+        if (msg.value != (token == address(0) ? amount : 0)) revert InvalidAmount();
+
+        uint16[] storage schedule = milestoneSchedules[seller][sku];
+        if (schedule.length == 0) revert InvalidState();
+
+        orderId = ++milestoneOrderCount;
+        MilestoneOrder storage order = milestoneOrders[orderId];
+        order.buyer = buyer;
+        order.seller = seller;
+        order.token = token;
+        order.listingHash = listingHash;
+        order.escrowed = amount;
+        order.milestoneBps = schedule;
+        order.sku = sku;
+        order.acceptanceWindow = acceptanceWindow;
+        sellerMilestoneOrders[seller].push(orderId);
+        buyerMilestoneOrders[buyer].push(orderId);
+
+        emit MilestoneOrderOpened(orderId, buyer, seller, listingHash, token, amount);
+    }
+
+    /// @notice Get milestone schedule configured for a seller SKU
+    /// @param seller Seller address
+    /// @param sku Item SKU
+    /// @return milestoneBps Milestone shares in basis points
+    function getMilestoneSchedule(address seller, bytes32 sku) external view returns (uint16[] memory) {
+        return milestoneSchedules[seller][sku];
+    }
+
+    /// @notice Get an escrowed milestone order
+    /// @param orderId Milestone order identifier
+    /// @return order Order data
+    function getMilestoneOrder(uint256 orderId) external view returns (MilestoneOrder memory) {
+        MilestoneOrder memory order = milestoneOrders[orderId];
+        if (order.buyer == address(0)) revert NotFound();
+        return order;
+    }
+
+    /// @notice Number of milestone orders opened for a seller
+    /// @param seller Seller address
+    function getSellerMilestoneOrderCount(address seller) external view returns (uint256) {
+        return sellerMilestoneOrders[seller].length;
+    }
+
+    /// @notice Number of milestone orders opened by a buyer
+    /// @param buyer Buyer address
+    function getBuyerMilestoneOrderCount(address buyer) external view returns (uint256) {
+        return buyerMilestoneOrders[buyer].length;
+    }
+
+    /// @notice Page through a seller's milestone order ids in creation order
+    /// @param seller Seller address
+    /// @param offset Index of the first id to return
+    /// @param limit Maximum number of ids to return
+    /// @return orderIds Milestone order identifiers
+    function getSellerMilestoneOrders(
+        address seller,
+        uint256 offset,
+        uint256 limit
+    ) external view returns (uint256[] memory orderIds) {
+        return _slice(sellerMilestoneOrders[seller], offset, limit);
+    }
+
+    /// @notice Page through a buyer's milestone order ids in creation order
+    /// @param buyer Buyer address
+    /// @param offset Index of the first id to return
+    /// @param limit Maximum number of ids to return
+    /// @return orderIds Milestone order identifiers
+    function getBuyerMilestoneOrders(
+        address buyer,
+        uint256 offset,
+        uint256 limit
+    ) external view returns (uint256[] memory orderIds) {
+        return _slice(buyerMilestoneOrders[buyer], offset, limit);
+    }
+
+    function _slice(
+        uint256[] storage ids,
+        uint256 offset,
+        uint256 limit
+    ) internal view returns (uint256[] memory page) {
+        uint256 total = ids.length;
+        if (offset >= total) return new uint256[](0);
+        uint256 end = offset + limit > total ? total : offset + limit;
+        page = new uint256[](end - offset);
+        for (uint256 i = offset; i < end; i++) {
+            page[i - offset] = ids[i];
+        }
+    }
+
+    /// @dev Arbiter rulings on a dispute are only accepted until its deadline
+    function _requireRulingOpen(uint256 orderId) internal view {
+        MilestoneOrder storage order = milestoneOrders[orderId];
+        if (order.disputed && block.timestamp >= order.disputeDeadline) revert Expired();
+    }
+
+    /// @dev Close a milestone order, releasing `sellerBps` of the remaining escrow to the seller
+    /// @dev The buyer receives the rest, optionally as prepaid credit
+    function _resolveMilestoneOrder(
+        uint256 orderId,
+        uint16 sellerBps,
+        bool refundToCredit
+    ) internal returns (uint256 sellerAmount, uint256 buyerAmount) {
+        MilestoneOrder storage order = milestoneOrders[orderId];
+        if (order.buyer == address(0)) revert NotFound();
+        if (order.closed) revert InvalidState();
+        if (sellerBps > 10000 || (sellerBps == 10000 && refundToCredit)) revert InvalidArgument();
+
+        // a dispute ruled even partly for the buyer counts against the seller
+        bool disputeLost = order.disputed && sellerBps < 10000;
+
+        uint256 alreadyReleased = order.released;
+        uint256 remaining = order.escrowed - alreadyReleased;
+        order.closed = true;
+        order.disputed = false;
+        order.released = order.escrowed;
+
+        sellerAmount = (remaining * sellerBps) / 10000;
+        buyerAmount = remaining - sellerAmount;
+
+        // the order counts as a sale, and the held NFT goes to the buyer, unless the whole payment was refunded
+        bool sold = alreadyReleased + sellerAmount > 0;
+        IMarketplace(marketplace).closeEscrowOrder(orderId, order.buyer, order.seller, order.sku, sold, disputeLost);
+
+        if (sellerAmount > 0) {
+            _release(order, sellerAmount);
+        }
+        if (buyerAmount > 0) {
+            if (refundToCredit) {
+                _refundToCredit(order.buyer, order.token, buyerAmount);
+            } else {
+                _transferOut(order.token, order.buyer, buyerAmount);
+            }
+        }
+    }
+
+    /// @dev Hand released escrow to the marketplace, which pays it out to the seller
+    function _release(MilestoneOrder storage order, uint256 amount) internal {
+        IMarketplace market = IMarketplace(marketplace);
+        if (order.token == address(0)) {
+            market.releaseEscrow{value: amount}(order.seller, order.sku, address(0), amount);
+            return;
+        }
+        IERC20(order.token).safeTransfer(marketplace, amount);
+        market.releaseEscrow(order.seller, order.sku, order.token, amount);
+    }
+
+    /// @dev Book a buyer refund as prepaid credit with the marketplace credit service
+    function _refundToCredit(address buyer, address token, uint256 amount) internal {
+        address credit = core.getService(MODULE_ID, 'MarketplaceCredit');
+        if (credit == address(0)) revert ServiceNotFound();
+
+        if (token == address(0)) {
+            IMarketplaceCredit(credit).depositCredit{value: amount}(buyer, address(0), amount);
+        } else {
+            IERC20(token).forceApprove(credit, amount);
+            IMarketplaceCredit(credit).depositCredit(buyer, token, amount);
+        }
+    }
+
+    /// @dev Send native currency or ERC-20 tokens held by this contract
+    function _transferOut(address token, address to, uint256 amount) internal {
+        if (amount == 0) return;
+        if (token == address(0)) {
+            (bool success, ) = payable(to).call{value: amount}('');
+            if (!success) revert TransferFailed();
+        } else {
+            IERC20(token).safeTransfer(to, amount);
+        }
+    }
+}
@@ -5,8 +5,23 @@ import '../../core/BaseFactory.sol';
 import './Marketplace.sol';
 import '../../core/CoreDefs.sol';
 
+/// @notice Minimal view shared by the marketplace satellite services
+interface IMarketplaceSatellite {
+    function marketplace() external view returns (address);
+}
+
 contract MarketplaceFactory is BaseFactory {
     event MarketplaceCreated(address indexed creator, address marketplace);
+    event MarketplaceServicesRegistered(
+        address indexed marketplace,
+        address escrow,
+        address auctions,
+        address promotions,
+        address credit,
+        address insurance
+    );
+
+    mapping(address => bool) public createdMarketplaces;
 
     constructor(
         address registry,
@@ -30,8 +45,46 @@ contract MarketplaceFactory is BaseFactory {
 
         // Обновляем адрес созданного маркетплейса в реестре core
         core.upgradeFeature(instanceId, m);
+        createdMarketplaces[m] = true;
 
         // Эмитируем событие создания маркетплейса
         emit MarketplaceCreated(msg.sender, m);
     }
+
+    /// @notice Register the satellite services of a marketplace created by this factory
+    /// @dev The satellites do not fit into this factory next to the marketplace, so they are deployed separately
+    /// against the instance (its address and `MODULE_ID`) and attached here. A zero address leaves that
+    /// feature disabled for the instance.
+    /// @param m Marketplace created by this factory
+    /// @param escrow MarketplaceEscrow service
+    /// @param auctions MarketplaceAuctions service
+    /// @param promotions MarketplacePromotions service
+    /// @param credit MarketplaceCredit service
+    /// @param insurance MarketplaceInsurance fund
+    function registerMarketplaceServices(
+        address m,
+        address escrow,
+        address auctions,
+        address promotions,
+        address credit,
+        address insurance
+    ) external onlyFactoryAdmin {
+        if (!createdMarketplaces[m]) revert NotFound();
+        bytes32 instanceId = Marketplace(payable(m)).MODULE_ID();
+
+        _registerSatellite(instanceId, m, 'MarketplaceEscrow', escrow);
+        _registerSatellite(instanceId, m, 'MarketplaceAuctions', auctions);
+        _registerSatellite(instanceId, m, 'MarketplacePromotions', promotions);
+        _registerSatellite(instanceId, m, 'MarketplaceCredit', credit);
+        _registerSatellite(instanceId, m, 'MarketplaceInsurance', insurance);
+
+        emit MarketplaceServicesRegistered(m, escrow, auctions, promotions, credit, insurance);
+    }
+
+    /// @dev Register a satellite after checking it was deployed for the marketplace instance
+    function _registerSatellite(bytes32 instanceId, address m, string memory name, address service) internal {
+        if (service == address(0)) return;
+        if (IMarketplaceSatellite(service).marketplace() != m) revert InvalidAddress();
+        core.setService(instanceId, name, service);
+    }
 }
@@ -0,0 +1,167 @@
+// SPDX-License-Identifier: MIT
+pragma solidity ^0.8.28;
+
+import '../../core/CoreSystem.sol';
+import '../../core/CoreDefs.sol';
+import '../../errors/Errors.sol';
+import './interfaces/IMarketplace.sol';
+import '@openzeppelin/contracts/token/ERC20/IERC20.sol';
+import '@openzeppelin/contracts/token/ERC20/utils/SafeERC20.sol';
+import '@openzeppelin/contracts/utils/ReentrancyGuard.sol';
+
+/// @title MarketplaceInsurance
+/// @notice Buyer protection fund for Marketplace sales
+/// @dev The fund is this contract's balance. It is fed by listing this contract as a FeeProcessor fee split
+/// recipient, so a slice of every platform fee lands here, and may be topped up directly with `fundInsurance`.
+/// Claims cover any marketplace sale: the buyer proves the payment against the commitment the marketplace
+/// stores per sale and an operator approves or rejects the claim. Each sale supports a single claim, so the
+/// payout per sale never exceeds the claim cap; a rejected claim is final.
+contract MarketplaceInsurance is ReentrancyGuard {
+    using SafeERC20 for IERC20;
+
+    CoreSystem public immutable core;
+    address public immutable marketplace;
+
+    enum ClaimStatus {
+        None,
+        Filed,
+        Paid,
+        Rejected
+    }
+
+    struct InsuranceClaim {
+        address buyer;
+        address token;
+        uint256 amount; // amount requested by the buyer
+        ClaimStatus status;
+    }
+
+    uint16 public claimCapBps; // per-claim cap as a share of the sale payment
+    mapping(bytes32 => InsuranceClaim) public insuranceClaims; // saleHash => claim
+
+    event ClaimCapUpdated(uint16 capBps);
+    event InsuranceFunded(address indexed token, address indexed from, uint256 amount);
+    event InsuranceClaimFiled(bytes32 indexed saleHash, address indexed buyer, uint256 amount, bytes32 evidenceHash);
+    event InsuranceClaimPaid(bytes32 indexed saleHash, address indexed buyer, address token, uint256 amount);
+    event InsuranceClaimRejected(bytes32 indexed saleHash, address indexed buyer, bytes32 reasonHash);
+
+    modifier onlyOperator() {
+        if (!core.hasRole(CoreDefs.OPERATOR_ROLE, msg.sender)) revert NotOperator();
+        _;
+    }
+
+    modifier onlyGovernor() {
+        if (!core.hasRole(CoreDefs.GOVERNOR_ROLE, msg.sender)) revert NotGovernor();
+        _;
+    }
+
+    constructor(address coreAddress, address marketplaceAddress) {
+        if (coreAddress == address(0) || marketplaceAddress == address(0)) revert ZeroAddress();
+        core = CoreSystem(coreAddress);
+        marketplace = marketplaceAddress;
+    }
+
+    /// @notice Set the per-claim cap
+    /// @param capBps Maximum payout per claim as a share of the sale payment
+    function setClaimCap(uint16 capBps) external onlyGovernor {
+        if (capBps > 10000) revert InvalidArgument();
+        claimCapBps = capBps;
+        emit ClaimCapUpdated(capBps);
+    }
+
+    /// @notice Top up the insurance fund directly
+    /// @param token Fund token (zero address for native currency)
+    /// @param amount Amount to deposit
+    function fundInsurance(address token, uint256 amount) external payable nonReentrant {
+        if (amount == 0) revert InvalidAmount();
+
+        uint256 received = amount;
+        if (token == address(0)) {
+            if (msg.value != amount) revert InvalidAmount();
+        } else {
+            if (msg.value != 0) revert InvalidAmount();
+            uint256 balanceBefore = IERC20(token).balanceOf(address(this));
+            IERC20(token).safeTransferFrom(msg.sender, address(this), amount);
+            received = IERC20(token).balanceOf(address(this)) - balanceBefore;
+            if (received == 0) revert InvalidAmount();
+        }
+
+        emit InsuranceFunded(token, msg.sender, received);
+    }
+
+    /// @notice Balance of the fund in a token
+    /// @param token Fund token (zero address for native currency)
+    function insuranceFund(address token) public view returns (uint256) {
+        return token == address(0) ? address(this).balance : IERC20(token).balanceOf(address(this));
+    }
+
+    /// @notice File an insurance claim for a marketplace sale made by the caller
+    /// @dev Sellers cannot claim on purchases of their own SKUs
+    /// @param saleHash Listing, auction or offer hash the sale was recorded under
+    /// @param seller Seller of the sale
+    /// @param token Payment token of the sale (zero address for native currency)
+    /// @param paidAmount Amount the caller paid for the sale
+    /// @param amount Compensation requested, at most the per-claim cap
+    /// @param evidenceHash Hash of the loss evidence bundle
+    function fileInsuranceClaim(
+        bytes32 saleHash,
+        address seller,
+        address token,
+        uint256 paidAmount,
+        uint256 amount,
+        bytes32 evidenceHash
+    ) external {
+        IMarketplace market = IMarketplace(marketplace);
+        bytes32 payment = market.salePayments(saleHash);
+        if (payment == bytes32(0) || payment != market.hashSalePayment(msg.sender, seller, token, paidAmount)) {
+            revert NotFound();
+        }
+        if (seller == msg.sender) revert Forbidden();
+
+        InsuranceClaim storage claim = insuranceClaims[saleHash];
+        if (claim.status != ClaimStatus.None) revert InvalidState();
+        if (amount == 0 || amount > (paidAmount * claimCapBps) / 10000) revert InvalidAmount();
+
+        claim.buyer = msg.sender;
+        claim.token = token;
+        claim.amount = amount;
+        claim.status = ClaimStatus.Filed;
+        emit InsuranceClaimFiled(saleHash, msg.sender, amount, evidenceHash);
+    }
+
+    /// @notice Arbiter approval of a filed insurance claim, paid out of the fund in the sale token
+    /// @param saleHash Sale the claim was filed for
+    /// @param amount Approved compensation, at most the claimed amount
+    function approveInsuranceClaim(bytes32 saleHash, uint256 amount) external onlyOperator nonReentrant {
+        InsuranceClaim storage claim = insuranceClaims[saleHash];
+        if (claim.status != ClaimStatus.Filed) revert InvalidState();
+        if (amount == 0 || amount > claim.amount) revert InvalidAmount();
+
+        uint256 available = insuranceFund(claim.token);
+        if (amount > available) revert InsufficientBalance(amount, available);
+
+        claim.status = ClaimStatus.Paid;
+        if (claim.token == address(0)) {
+            (bool success, ) = payable(claim.buyer).call{value: amount}('');
+            if (!success) revert TransferFailed();
+        } else {
+            IERC20(claim.token).safeTransfer(claim.buyer, amount);
+        }
+
+        emit InsuranceClaimPaid(saleHash, claim.buyer, claim.token, amount);
+    }
+
+    /// @notice Arbiter rejection of a filed insurance claim
+    /// @param saleHash Sale the claim was filed for
+    /// @param reasonHash Hash of the rejection reasoning
+    function rejectInsuranceClaim(bytes32 saleHash, bytes32 reasonHash) external onlyOperator {
+        InsuranceClaim storage claim = insuranceClaims[saleHash];
+        if (claim.status != ClaimStatus.Filed) revert InvalidState();
+
+        claim.status = ClaimStatus.Rejected;
+        emit InsuranceClaimRejected(saleHash, claim.buyer, reasonHash);
+    }
+
+    /// @notice Receives the native fee split pushed by the payment gateway
+    receive() external payable {}
+}
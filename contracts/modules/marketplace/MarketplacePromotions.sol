@@ -0,0 +1,534 @@
+// SPDX-License-Identifier: MIT
+pragma solidity ^0.8.28;
+
+import '../../core/CoreSystem.sol';
+import '../../core/CoreDefs.sol';
+import '../../errors/Errors.sol';
+import './interfaces/IMarketplaceServices.sol';
+import '@openzeppelin/contracts/token/ERC20/IERC20.sol';
+import '@openzeppelin/contracts/token/ERC20/utils/SafeERC20.sol';
+import '@openzeppelin/contracts/utils/ReentrancyGuard.sol';
+
+/// @title MarketplacePromotions
+/// @notice Cashback promotions, cross-sell discounts and seller coupons applied to Marketplace purchases
+/// @dev Registered in core as the `MarketplacePromotions` service of the marketplace module
+contract MarketplacePromotions is IMarketplacePromotions, ReentrancyGuard {
+    using SafeERC20 for IERC20;
+
+    CoreSystem public immutable core;
+    address public immutable marketplace;
+
+    // Cashback promotions
+    struct Promotion {
+        address token;
+        uint16 cashbackBps;
+        uint64 startTime;
+        uint64 endTime;
+        uint256 budget;
+        address funder;
+        uint256 totalDeposited; // initial budget plus top-ups
+        uint256 refundable; // budget left at close, shared pro-rata among depositors
+    }
+
+    uint256 public promotionCount;
+    mapping(uint256 => Promotion) public promotions;
+    mapping(address => uint256) public activePromotionByToken;
+    mapping(uint256 => mapping(address => uint256)) public promotionDeposits; // promotionId => depositor => deposits
+    mapping(uint256 => uint256) public buyerCashbackCap; // promotionId => max cashback per buyer (0 = uncapped)
+    mapping(uint256 => mapping(address => uint256)) public cashbackReceived; // promotionId => buyer => paid or held
+
+    // Cashback on milestone orders is held until the order closes in the seller's favour, then claimed by the buyer
+    struct HeldCashback {
+        uint256 promotionId;
+        address buyer;
+        uint256 amount;
+        bool released;
+    }
+
+    mapping(uint256 => HeldCashback) public heldCashback; // escrow orderId => held cashback
+
+    // Cross-sell discounts: buying triggerSku unlocks a discount on targetSku
+    struct CrossSellRule {
+        bytes32 triggerSku;
+        uint16 discountBps;
+        uint32 window;
+    }
+
+    mapping(address => mapping(bytes32 => CrossSellRule)) public crossSellRules; // seller => targetSku => rule
+    mapping(address => mapping(address => mapping(bytes32 => uint256))) public lastPurchaseAt; // buyer => seller => sku
+
+    // Seller coupons: a code hash grants a one-time-per-buyer discount. Ids live in the issuing seller's
+    // namespace, so a code cannot be squatted by another seller.
+    enum CouponScope {
+        Global, // any listing of the seller
+        Sku, // listings of one SKU
+        Listing // one listing, by hash
+    }
+
+    struct Coupon {
+        CouponScope scope;
+        bytes32 target; // SKU or listing hash, 0 for global coupons
+        uint16 discountBps;
+        uint32 maxRedemptions;
+        uint32 redemptions;
+        uint64 expiresAt;
+    }
+
+    mapping(address => mapping(bytes32 => Coupon)) public coupons; // seller => couponId => coupon
+    mapping(address => mapping(bytes32 => mapping(address => bool))) public couponRedeemed; // seller => id => buyer
+
+    event PromotionCreated(
+        uint256 indexed promotionId,
+        address indexed token,
+        uint16 cashbackBps,
+        uint64 startTime,
+        uint64 endTime,
+        uint256 budget
+    );
+    event PromotionClosed(uint256 indexed promotionId, uint256 refundedBudget);
+    event PromotionRefundClaimed(uint256 indexed promotionId, address indexed depositor, uint256 amount);
+    event PromotionFunded(uint256 indexed promotionId, address indexed depositor, uint256 amount, bytes32 memoHash);
+    event CashbackPaid(uint256 indexed promotionId, address indexed buyer, address token, uint256 amount);
+    event CashbackHeld(uint256 indexed promotionId, uint256 indexed orderId, address indexed buyer, uint256 amount);
+    event CashbackReleased(uint256 indexed promotionId, uint256 indexed orderId, address indexed buyer, uint256 amount);
+    event CashbackForfeited(uint256 indexed promotionId, uint256 indexed orderId, uint256 amount);
+    event BuyerCashbackCapUpdated(uint256 indexed promotionId, uint256 cap);
+    event CrossSellRuleUpdated(
+        address indexed seller,
+        bytes32 indexed targetSku,
+        bytes32 triggerSku,
+        uint16 discountBps,
+        uint32 window
+    );
+    event CouponCreated(
+        address indexed seller,
+        bytes32 indexed couponId,
+        CouponScope scope,
+        bytes32 target,
+        uint16 discountBps,
+        uint32 maxRedemptions,
+        uint64 expiresAt
+    );
+    event CouponDisabled(address indexed seller, bytes32 indexed couponId);
+    event CouponRedeemed(
+        address indexed seller,
+        bytes32 indexed couponId,
+        address indexed buyer,
+        bytes32 sku,
+        uint256 discount
+    );
+    event CrossSellDiscountApplied(
+        address indexed buyer,
+        address indexed seller,
+        bytes32 indexed targetSku,
+        bytes32 triggerSku,
+        uint16 discountBps
+    );
+
+    modifier onlyOperator() {
+        if (!core.hasRole(CoreDefs.OPERATOR_ROLE, msg.sender)) revert NotOperator();
+        _;
+    }
+
+    constructor(address coreAddress, address marketplaceAddress) {
+        if (coreAddress == address(0) || marketplaceAddress == address(0)) revert ZeroAddress();
+        core = CoreSystem(coreAddress);
+        marketplace = marketplaceAddress;
+    }
+
+    /// @notice Configure a cross-sell discount for one of the caller's SKUs
+    /// @dev A discount of 0 removes the rule
+    /// @param triggerSku SKU that has to be purchased first
+    /// @param targetSku SKU that receives the discount
+    /// @param discountBps Discount in basis points
+    /// @param window Time window in seconds after the trigger purchase
+    function setCrossSellRule(bytes32 triggerSku, bytes32 targetSku, uint16 discountBps, uint32 window) external {
+        if (discountBps >= 10000) revert InvalidParameters();
+        if (discountBps > 0 && (window == 0 || triggerSku == targetSku)) revert InvalidParameters();
+
+        if (discountBps == 0) {
+            delete crossSellRules[msg.sender][targetSku];
+        } else {
+            crossSellRules[msg.sender][targetSku] = CrossSellRule({
+                triggerSku: triggerSku,
+                discountBps: discountBps,
+                window: window
+            });
+        }
+
+        emit CrossSellRuleUpdated(msg.sender, targetSku, triggerSku, discountBps, window);
+    }
+
+    /// @notice Cross-sell discount currently available to a buyer
+    /// @param buyer Buyer address
+    /// @param seller Seller address
+    /// @param sku Target SKU
+    /// @return discountBps Discount in basis points (0 if none)
+    function getCrossSellDiscount(address buyer, address seller, bytes32 sku) public view returns (uint16 discountBps) {
+        CrossSellRule memory rule = crossSellRules[seller][sku];
+        if (rule.discountBps == 0) return 0;
+
+        uint256 triggeredAt = lastPurchaseAt[buyer][seller][rule.triggerSku];
+        if (triggeredAt == 0 || block.timestamp > triggeredAt + rule.window) return 0;
+
+        return rule.discountBps;
+    }
+
+    /// @notice Issue a coupon for the caller's listings
+    /// @param couponId Coupon identifier, typically the hash of the promo code
+    /// @param scope Which of the caller's listings the coupon applies to
+    /// @param target SKU or listing hash for scoped coupons, 0 for global ones
+    /// @param discountBps Discount in basis points
+    /// @param maxRedemptions Total number of redemptions allowed
+    /// @param expiresAt Timestamp after which the coupon can no longer be redeemed
+    function createCoupon(
+        bytes32 couponId,
+        CouponScope scope,
+        bytes32 target,
+        uint16 discountBps,
+        uint32 maxRedemptions,
+        uint64 expiresAt
+    ) external {
+        if (couponId == bytes32(0) || coupons[msg.sender][couponId].expiresAt != 0) revert InvalidArgument();
+        if ((scope == CouponScope.Global) != (target == bytes32(0))) revert InvalidArgument();
+        if (discountBps == 0 || discountBps >= 10000 || maxRedemptions == 0) revert InvalidParameters();
+        if (expiresAt <= block.timestamp) revert DeadlineInPast();
+
+        coupons[msg.sender][couponId] = Coupon({
+            scope: scope,
+            target: target,
+            discountBps: discountBps,
+            maxRedemptions: maxRedemptions,
+            redemptions: 0,
+            expiresAt: expiresAt
+        });
+
+        emit CouponCreated(msg.sender, couponId, scope, target, discountBps, maxRedemptions, expiresAt);
+    }
+
+    /// @notice Stop further redemptions of one of the caller's coupons
+    /// @param couponId Coupon identifier
+    function disableCoupon(bytes32 couponId) external {
+        Coupon storage coupon = coupons[msg.sender][couponId];
+        if (coupon.expiresAt == 0) revert NotFound();
+        coupon.maxRedemptions = coupon.redemptions;
+        emit CouponDisabled(msg.sender, couponId);
+    }
+
+    /// @notice Create a cashback promotion funded with the provided budget
+    /// @param token Payment token the cashback applies to (0 for native currency)
+    /// @param cashbackBps Cashback share of the payment amount in basis points
+    /// @param startTime Promotion start timestamp
+    /// @param endTime Promotion end timestamp
+    /// @param budget Total cashback budget transferred from the operator
+    /// @return promotionId Identifier of the created promotion
+    function createPromotion(
+        address token,
+        uint16 cashbackBps,
+        uint64 startTime,
+        uint64 endTime,
+        uint256 budget
+    ) external payable onlyOperator nonReentrant returns (uint256 promotionId) {
+        if (cashbackBps == 0 || cashbackBps > 10000) revert InvalidParameters();
+        if (endTime <= startTime || endTime <= block.timestamp) revert InvalidParameters();
+        if (budget == 0) revert InvalidAmount();
+
+        if (token == 0xEeeeeEeeeEeEeeEeEeEeeEEEeeeeEeeeeeeeEEeE) token = address(0);
+
+        // Only one live promotion per token
+        uint256 currentId = activePromotionByToken[token];
+        if (currentId != 0) {
+            Promotion storage current = promotions[currentId];
+            if (current.budget > 0 && current.endTime >= block.timestamp) revert InvalidState();
+        }
+
+        uint256 received = budget;
+        if (token == address(0)) {
+            if (msg.value != budget) revert InvalidAmount();
+        } else {
+            if (msg.value != 0) revert InvalidAmount();
+            uint256 balanceBefore = IERC20(token).balanceOf(address(this));
+            IERC20(token).safeTransferFrom(msg.sender, address(this), budget);
+            received = IERC20(token).balanceOf(address(this)) - balanceBefore;
+            if (received == 0) revert InvalidAmount();
+        }
+
+        promotionId = ++promotionCount;
+        promotions[promotionId] = Promotion({
+            token: token,
+            cashbackBps: cashbackBps,
+            startTime: startTime,
+            endTime: endTime,
+            budget: received,
+            funder: msg.sender,
+            totalDeposited: received,
+            refundable: 0
+        });
+        activePromotionByToken[token] = promotionId;
+        promotionDeposits[promotionId][msg.sender] = received;
+
+        emit PromotionCreated(promotionId, token, cashbackBps, startTime, endTime, received);
+    }
+
+    /// @notice Top up the budget of a live promotion, recording the depositor
+    /// @dev Unspent budget is shared among all depositors pro-rata to their deposits on close
+    /// @param promotionId Promotion identifier
+    /// @param amount Amount to add, must equal `msg.value` for native promotions
+    /// @param memoHash Hash of an off-chain memo describing the deposit
+    function fundPromotion(uint256 promotionId, uint256 amount, bytes32 memoHash) external payable nonReentrant {
+        Promotion storage promo = promotions[promotionId];
+        if (promo.funder == address(0)) revert NotFound();
+        if (activePromotionByToken[promo.token] != promotionId || promo.endTime < block.timestamp) {
+            revert InvalidState();
+        }
+        if (amount == 0) revert InvalidAmount();
+
+        uint256 received = amount;
+        if (promo.token == address(0)) {
+            if (msg.value != amount) revert InvalidAmount();
+        } else {
+            if (msg.value != 0) revert InvalidAmount();
+            uint256 balanceBefore = IERC20(promo.token).balanceOf(address(this));
+            IERC20(promo.token).safeTransferFrom(msg.sender, address(this), amount);
+            received = IERC20(promo.token).balanceOf(address(this)) - balanceBefore;
+            if (received == 0) revert InvalidAmount();
+        }
+
+        promo.budget += received;
+        promo.totalDeposited += received;
+        promotionDeposits[promotionId][msg.sender] += received;
+
+        emit PromotionFunded(promotionId, msg.sender, received, memoHash);
+    }
+
+    /// @notice Limit the total cashback a single buyer can receive from a promotion
+    /// @param promotionId Promotion identifier
+    /// @param cap Maximum cashback per buyer (0 = uncapped)
+    function setBuyerCashbackCap(uint256 promotionId, uint256 cap) external onlyOperator {
+        if (promotions[promotionId].funder == address(0)) revert NotFound();
+        buyerCashbackCap[promotionId] = cap;
+        emit BuyerCashbackCapUpdated(promotionId, cap);
+    }
+
+    /// @notice Close a promotion, making its unspent budget claimable by the depositors
+    /// @param promotionId Promotion identifier
+    function closePromotion(uint256 promotionId) external onlyOperator nonReentrant {
+        Promotion storage promo = promotions[promotionId];
+        if (promo.funder == address(0)) revert NotFound();
+
+        uint256 remaining = promo.budget;
+        if (remaining == 0) revert NothingToWithdraw();
+
+        promo.budget = 0;
+        promo.refundable = remaining;
+        if (activePromotionByToken[promo.token] == promotionId) {
+            delete activePromotionByToken[promo.token];
+        }
+
+        emit PromotionClosed(promotionId, remaining);
+    }
+
+    /// @notice Claim the caller's pro-rata share of a closed promotion's unspent budget
+    /// @param promotionId Promotion identifier
+    function claimPromotionRefund(uint256 promotionId) external nonReentrant {
+        Promotion storage promo = promotions[promotionId];
+        if (promo.refundable == 0) revert InvalidState();
+
+        uint256 deposit = promotionDeposits[promotionId][msg.sender];
+        if (deposit == 0) revert NothingToWithdraw();
+        promotionDeposits[promotionId][msg.sender] = 0;
+
+        uint256 amount = (promo.refundable * deposit) / promo.totalDeposited;
+        _transferOut(promo.token, msg.sender, amount);
+
+        emit PromotionRefundClaimed(promotionId, msg.sender, amount);
+    }
+
+    /// @notice Apply the buyer's cross-sell discount and an optional seller coupon
+    /// @dev Only callable by the marketplace; the coupon applies after any cross-sell discount
+    /// @param buyer Buyer address
+    /// @param seller Seller address
+    /// @param sku Purchased SKU
+    /// @param listingHash Hash of the purchased listing
+    /// @param couponId Coupon identifier in the seller's namespace (0 for none)
+    /// @param price Price before discounts
+    /// @return Discounted price
+    function applyDiscounts(
+        address buyer,
+        address seller,
+        bytes32 sku,
+        bytes32 listingHash,
+        bytes32 couponId,
+        uint256 price
+    ) external returns (uint256) {
+        if (msg.sender != marketplace) revert Unauthorized();
+
+        price = _applyCrossSellDiscount(buyer, seller, sku, price);
+        if (couponId != bytes32(0)) {
+            price = _redeemCoupon(couponId, buyer, seller, sku, listingHash, price);
+        }
+        return price;
+    }
+
+    /// @notice Record a completed sale so it can trigger the seller's cross-sell rules
+    /// @dev Only callable by the marketplace, for every sale path (direct, auction, offer)
+    /// @param buyer Buyer address
+    /// @param seller Seller address
+    /// @param sku Purchased SKU
+    function recordPurchase(address buyer, address seller, bytes32 sku) external {
+        if (msg.sender != marketplace) revert Unauthorized();
+        lastPurchaseAt[buyer][seller][sku] = block.timestamp;
+    }
+
+    /// @notice Pay cashback from the active promotion for the payment token, if any
+    /// @dev Only callable by the marketplace after a purchase is paid. Sellers buying their own SKUs get nothing;
+    /// cashback on a milestone order is held until the order closes.
+    /// @param buyer Buyer address
+    /// @param seller Seller address
+    /// @param token Payment token (0 for native currency)
+    /// @param paymentAmount Amount the buyer paid
+    /// @param escrowOrderId Milestone order holding the proceeds (0 when the seller was paid directly)
+    function payCashback(
+        address buyer,
+        address seller,
+        address token,
+        uint256 paymentAmount,
+        uint256 escrowOrderId
+    ) external nonReentrant {
+        if (msg.sender != marketplace) revert Unauthorized();
+        if (buyer == seller) return;
+
+        uint256 promotionId = activePromotionByToken[token];
+        if (promotionId == 0) return;
+
+        Promotion storage promo = promotions[promotionId];
+        if (block.timestamp < promo.startTime || block.timestamp > promo.endTime) return;
+
+        uint256 cashback = (paymentAmount * promo.cashbackBps) / 10000;
+        if (cashback > promo.budget) cashback = promo.budget;
+        uint256 cap = buyerCashbackCap[promotionId];
+        if (cap > 0) {
+            uint256 received = cashbackReceived[promotionId][buyer];
+            if (cashback > cap - received) cashback = cap - received;
+        }
+        if (cashback == 0) return;
+
+        promo.budget -= cashback;
+        cashbackReceived[promotionId][buyer] += cashback;
+        // Promotion stops automatically once the budget is exhausted
+        if (promo.budget == 0) {
+            delete activePromotionByToken[token];
+        }
+
+        if (escrowOrderId != 0) {
+            heldCashback[escrowOrderId] = HeldCashback({
+                promotionId: promotionId,
+                buyer: buyer,
+                amount: cashback,
+                released: false
+            });
+            emit CashbackHeld(promotionId, escrowOrderId, buyer, cashback);
+            return;
+        }
+
+        _transferOut(token, buyer, cashback);
+
+        emit CashbackPaid(promotionId, buyer, token, cashback);
+    }
+
+    /// @notice Release or forfeit the cashback held for a milestone order once it closes
+    /// @dev Only callable by the marketplace. Released cashback is claimed by the buyer, so a buyer that cannot
+    /// receive it does not block the order. Forfeited cashback returns to the promotion budget, or to the
+    /// depositors' refund pool if the promotion was closed in the meantime.
+    /// @param escrowOrderId Milestone order identifier
+    /// @param paid Whether the order closed in the seller's favour
+    function settleCashback(uint256 escrowOrderId, bool paid) external {
+        if (msg.sender != marketplace) revert Unauthorized();
+
+        HeldCashback storage held = heldCashback[escrowOrderId];
+        if (held.amount == 0 || held.released) return;
+
+        if (paid) {
+            held.released = true;
+            emit CashbackReleased(held.promotionId, escrowOrderId, held.buyer, held.amount);
+            return;
+        }
+
+        Promotion storage promo = promotions[held.promotionId];
+        cashbackReceived[held.promotionId][held.buyer] -= held.amount;
+        if (promo.refundable > 0) {
+            promo.refundable += held.amount;
+        } else {
+            promo.budget += held.amount;
+        }
+        emit CashbackForfeited(held.promotionId, escrowOrderId, held.amount);
+        delete heldCashback[escrowOrderId];
+    }
+
+    /// @notice Claim cashback released when the caller's milestone order closed
+    /// @param escrowOrderId Milestone order identifier
+    function claimCashback(uint256 escrowOrderId) external nonReentrant {
+        HeldCashback memory held = heldCashback[escrowOrderId];
+        if (held.buyer != msg.sender) revert Unauthorized();
+        if (!held.released) revert InvalidState();
+        delete heldCashback[escrowOrderId];
+
+        address token = promotions[held.promotionId].token;
+        _transferOut(token, msg.sender, held.amount);
+
+        emit CashbackPaid(held.promotionId, msg.sender, token, held.amount);
+    }
+
+    /// @dev Apply a seller coupon, enforcing its scope, expiry, total cap and one use per buyer
+    function _redeemCoupon(
+        bytes32 couponId,
+        address buyer,
+        address seller,
+        bytes32 sku,
+        bytes32 listingHash,
+        uint256 price
+    ) internal returns (uint256) {
+        Coupon storage coupon = coupons[seller][couponId];
+        if (coupon.expiresAt == 0) revert NotFound();
+        if (coupon.scope == CouponScope.Sku && coupon.target != sku) revert NotFound();
+        if (coupon.scope == CouponScope.Listing && coupon.target != listingHash) revert NotFound();
+        if (coupon.expiresAt < block.timestamp) revert DeadlineExpired(coupon.expiresAt, block.timestamp);
+        if (coupon.redemptions >= coupon.maxRedemptions) revert LimitExceeded();
+        if (couponRedeemed[seller][couponId][buyer]) revert AlreadyPurchased();
+
+        coupon.redemptions += 1;
+        couponRedeemed[seller][couponId][buyer] = true;
+
+        uint256 discount = (price * coupon.discountBps) / 10000;
+        emit CouponRedeemed(seller, couponId, buyer, sku, discount);
+        return price - discount;
+    }
+
+    /// @dev Apply a cross-sell discount; each trigger purchase unlocks a single discounted purchase
+    function _applyCrossSellDiscount(
+        address buyer,
+        address seller,
+        bytes32 sku,
+        uint256 price
+    ) internal returns (uint256) {
+        uint16 discountBps = getCrossSellDiscount(buyer, seller, sku);
+        if (discountBps == 0) return price;
+
+        bytes32 triggerSku = crossSellRules[seller][sku].triggerSku;
+        delete lastPurchaseAt[buyer][seller][triggerSku];
+
+        emit CrossSellDiscountApplied(buyer, seller, sku, triggerSku, discountBps);
+        return price - (price * discountBps) / 10000;
+    }
+
+    /// @dev Send native currency or ERC-20 tokens held by this contract
+    function _transferOut(address token, address to, uint256 amount) internal {
+        if (amount == 0) return;
+        if (token == address(0)) {
+            (bool success, ) = payable(to).call{value: amount}('');
+            if (!success) revert TransferFailed();
+        } else {
+            IERC20(token).safeTransfer(to, amount);
+        }
+    }
+}
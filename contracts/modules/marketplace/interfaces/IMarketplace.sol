@@ -0,0 +1,43 @@
+// SPDX-License-Identifier: MIT
+pragma solidity ^0.8.28;
+
+/// @notice Marketplace entry points used by its satellite services
+interface IMarketplace {
+    function paymentGateway() external view returns (address);
+
+    function escrowedAssets(
+        address seller,
+        bytes32 sku
+    ) external view returns (address collection, uint256 tokenId, bool delivered, uint256 pendingOrder);
+
+    function withinPurchaseLimit(address buyer, address seller, bytes32 sku) external view returns (bool);
+
+    function salePayments(bytes32 saleHash) external view returns (bytes32);
+
+    function hashSalePayment(
+        address buyer,
+        address seller,
+        address token,
+        uint256 amount
+    ) external pure returns (bytes32);
+
+    function settleEscrowedSale(
+        address buyer,
+        address seller,
+        bytes32 sku,
+        address token,
+        uint256 amount,
+        bytes32 saleHash
+    ) external payable returns (uint256 netAmount);
+
+    function releaseEscrow(address seller, bytes32 sku, address token, uint256 amount) external payable;
+
+    function closeEscrowOrder(
+        uint256 orderId,
+        address buyer,
+        address seller,
+        bytes32 sku,
+        bool sold,
+        bool disputeLost
+    ) external;
+}
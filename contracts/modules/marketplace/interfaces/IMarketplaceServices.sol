@@ -0,0 +1,47 @@
+// SPDX-License-Identifier: MIT
+pragma solidity ^0.8.28;
+
+/// @notice Milestone escrow service: holds the net proceeds of SKUs sold with a milestone schedule
+interface IMarketplaceEscrow {
+    function hasMilestones(address seller, bytes32 sku) external view returns (bool);
+
+    function openOrder(
+        address buyer,
+        address seller,
+        bytes32 sku,
+        address token,
+        uint256 amount,
+        bytes32 listingHash
+    ) external payable returns (uint256 orderId);
+}
+
+/// @notice Promotions service: cross-sell discounts, seller coupons and cashback
+interface IMarketplacePromotions {
+    function applyDiscounts(
+        address buyer,
+        address seller,
+        bytes32 sku,
+        bytes32 listingHash,
+        bytes32 couponId,
+        uint256 price
+    ) external returns (uint256);
+
+    function recordPurchase(address buyer, address seller, bytes32 sku) external;
+
+    function payCashback(
+        address buyer,
+        address seller,
+        address token,
+        uint256 paymentAmount,
+        uint256 escrowOrderId
+    ) external;
+
+    function settleCashback(uint256 escrowOrderId, bool paid) external;
+}
+
+/// @notice Prepaid credit service
+interface IMarketplaceCredit {
+    function depositCredit(address beneficiary, address token, uint256 amount) external payable;
+
+    function drawCredit(address user, address token, uint256 amount) external returns (uint256 drawn);
+}
@@ -111,12 +111,14 @@ contract MonetaryCash is EIP712, ReentrancyGuard {
         bytes calldata signature
     ) external nonReentrant {
         if (recipient == address(0)) revert ZeroAddress();
-        if (deadline != 0 && block.timestamp > deadline) revert Expired();
+        if (deadline != 0 && block.timestamp > deadline) revert DeadlineExpired(deadline, block.timestamp);
 
         Cash storage entry = cashes[cashId];
         if (entry.status == CashStatus.None) revert NotFound();
         if (entry.status != CashStatus.Active) revert InvalidState();
-        if (entry.expiresAt != 0 && block.timestamp > entry.expiresAt) revert Expired();
+        if (entry.expiresAt != 0 && block.timestamp > entry.expiresAt) {
+            revert DeadlineExpired(entry.expiresAt, block.timestamp);
+        }
 
         bytes32 digest = _hashTypedDataV4(keccak256(abi.encode(ACTIVATE_TYPEHASH, cashId, recipient, deadline)));
         address signer = ECDSA.recover(digest, signature);
@@ -21,11 +21,32 @@ contract PlanManager is IPlanManager {
     bytes32 public immutable domainSeparator;
 
     uint8 public maxActivePlans;
+    uint8 public priceNoticePeriods = 2; // billing periods existing subscribers keep their price after an increase
+    uint8 public constant MAX_REVENUE_SHARES = 5;
+
+    struct PriceChange {
+        uint128 newPrice; // charged to new subscribers from scheduledAt
+        uint40 scheduledAt;
+        uint40 effectiveAt; // renewals locked in before scheduledAt pay an increase from here on
+    }
+
+    /// @notice Discount on the first charged period of one plan; ids are namespaced by plan
+    struct PlanCoupon {
+        uint16 discountBps;
+        uint32 maxRedemptions;
+        uint32 redemptions;
+        uint64 expiresAt;
+    }
 
     mapping(bytes32 => PlanData) private plans;
     mapping(address => bytes32[]) private merchantPlanHistory;
     mapping(address => bytes32[]) private activePlans;
     mapping(address => mapping(bytes32 => uint256)) private activePlanIndexes; // index + 1
+    mapping(bytes32 => PlanIntro) private planIntros;
+    mapping(bytes32 => PriceChange) public priceChanges; // latest price change per plan
+    mapping(bytes32 => RevenueShare[]) private revenueShares;
+    mapping(bytes32 => mapping(bytes32 => PlanCoupon)) public planCoupons; // planHash => couponId => coupon
+    mapping(bytes32 => mapping(bytes32 => mapping(address => bool))) public planCouponRedeemed; // plan => id => user
 
     event PlanCreated(
         address indexed merchant,
@@ -45,6 +66,31 @@ contract PlanManager is IPlanManager {
         address newMerchant
     );
     event MaxActivePlansUpdated(uint8 oldLimit, uint8 newLimit);
+    event PlanPriceChangeScheduled(
+        address indexed merchant,
+        bytes32 indexed planHash,
+        uint128 oldPrice,
+        uint128 newPrice,
+        uint40 effectiveAt
+    );
+    event PriceNoticePeriodsUpdated(uint8 oldPeriods, uint8 newPeriods);
+    event RevenueSharesUpdated(address indexed merchant, bytes32 indexed planHash, RevenueShare[] shares);
+    event PlanIntroUpdated(
+        address indexed merchant,
+        bytes32 indexed planHash,
+        uint32 trialSeconds,
+        uint128 firstPeriodPrice
+    );
+
+    event PlanCouponCreated(
+        bytes32 indexed planHash,
+        bytes32 indexed couponId,
+        uint16 discountBps,
+        uint32 maxRedemptions,
+        uint64 expiresAt
+    );
+    event PlanCouponDisabled(bytes32 indexed planHash, bytes32 indexed couponId);
+    event PlanCouponRedeemed(bytes32 indexed planHash, bytes32 indexed couponId, address indexed user);
 
     constructor(address coreAddress, address subscriptionManagerAddress, bytes32 moduleId, uint8 initialMaxActive) {
         if (coreAddress == address(0) || subscriptionManagerAddress == address(0)) revert ZeroAddress();
@@ -162,6 +208,134 @@ contract PlanManager is IPlanManager {
         emit PlanUriUpdated(plan.merchant, planHash, uri);
     }
 
+    /// @notice Configure a free trial or discounted first period for new subscribers of a plan
+    /// @dev Each user gets introductory terms at most once per merchant, tracked by SubscriptionManager
+    function setPlanIntro(bytes32 planHash, uint32 trialSeconds, uint128 firstPeriodPrice) external {
+        PlanData storage plan = _requirePlan(planHash);
+        _requireMerchantOrOperator(plan.merchant);
+        if (firstPeriodPrice != 0 && firstPeriodPrice >= _listPrice(planHash, plan.price)) revert InvalidPrice();
+
+        planIntros[planHash] = PlanIntro({trialSeconds: trialSeconds, firstPeriodPrice: firstPeriodPrice});
+        plan.updatedAt = uint48(block.timestamp);
+
+        emit PlanIntroUpdated(plan.merchant, planHash, trialSeconds, firstPeriodPrice);
+    }
+
+    /// @notice Change the price of a plan
+    /// @dev New subscribers pay the new price immediately and decreases reach renewals at once. Existing
+    /// subscribers keep their locked price through a notice window of `priceNoticePeriods` billing periods
+    /// on an increase; a new schedule replaces a pending one. The signed plan price stays unchanged so the
+    /// plan keeps verifying; charges use getEffectivePrice and getRenewalPrice
+    /// @param planHash Plan hash
+    /// @param newPrice New list price
+    function schedulePriceChange(bytes32 planHash, uint128 newPrice) external {
+        PlanData storage plan = _requirePlan(planHash);
+        if (msg.sender != plan.merchant) revert UnauthorizedMerchant();
+        if (newPrice == 0) revert InvalidPrice();
+
+        uint128 oldPrice = _listPrice(planHash, plan.price);
+        if (newPrice == oldPrice || planIntros[planHash].firstPeriodPrice >= newPrice) revert InvalidPrice();
+
+        uint40 effectiveAt = newPrice > oldPrice
+            ? uint40(block.timestamp + uint256(plan.period) * priceNoticePeriods)
+            : uint40(block.timestamp);
+        priceChanges[planHash] = PriceChange({
+            newPrice: newPrice,
+            scheduledAt: uint40(block.timestamp),
+            effectiveAt: effectiveAt
+        });
+        plan.updatedAt = uint48(block.timestamp);
+
+        emit PlanPriceChangeScheduled(plan.merchant, planHash, oldPrice, newPrice, effectiveAt);
+    }
+
+    /// @notice Issue a coupon discounting the first charged period of a plan
+    /// @param planHash Plan hash
+    /// @param couponId Coupon identifier, typically the hash of the promo code
+    /// @param discountBps Discount in basis points
+    /// @param maxRedemptions Total number of redemptions allowed
+    /// @param expiresAt Timestamp after which the coupon can no longer be redeemed
+    function createPlanCoupon(
+        bytes32 planHash,
+        bytes32 couponId,
+        uint16 discountBps,
+        uint32 maxRedemptions,
+        uint64 expiresAt
+    ) external {
+        PlanData storage plan = _requirePlan(planHash);
+        _requireMerchantOrOperator(plan.merchant);
+        if (couponId == bytes32(0) || planCoupons[planHash][couponId].expiresAt != 0) revert InvalidArgument();
+        if (discountBps == 0 || discountBps >= 10000 || maxRedemptions == 0) revert InvalidParameters();
+        if (expiresAt <= block.timestamp) revert DeadlineInPast();
+
+        planCoupons[planHash][couponId] = PlanCoupon({
+            discountBps: discountBps,
+            maxRedemptions: maxRedemptions,
+            redemptions: 0,
+            expiresAt: expiresAt
+        });
+
+        emit PlanCouponCreated(planHash, couponId, discountBps, maxRedemptions, expiresAt);
+    }
+
+    /// @notice Stop further redemptions of a plan coupon
+    function disablePlanCoupon(bytes32 planHash, bytes32 couponId) external {
+        PlanData storage plan = _requirePlan(planHash);
+        _requireMerchantOrOperator(plan.merchant);
+        PlanCoupon storage coupon = planCoupons[planHash][couponId];
+        if (coupon.expiresAt == 0) revert NotFound();
+        coupon.maxRedemptions = coupon.redemptions;
+        emit PlanCouponDisabled(planHash, couponId);
+    }
+
+    /// @notice Consume one redemption of a plan coupon for `user`
+    /// @dev Only SubscriptionManager may redeem, once per user and coupon
+    /// @return discountBps Discount to apply to the first charged period
+    function redeemCoupon(bytes32 planHash, bytes32 couponId, address user) external override returns (uint16) {
+        if (msg.sender != subscriptionManager) revert Unauthorized();
+        PlanCoupon storage coupon = planCoupons[planHash][couponId];
+        if (coupon.expiresAt == 0) revert NotFound();
+        if (coupon.expiresAt < block.timestamp) revert DeadlineExpired(coupon.expiresAt, block.timestamp);
+        if (coupon.redemptions >= coupon.maxRedemptions) revert LimitExceeded();
+        if (planCouponRedeemed[planHash][couponId][user]) revert AlreadyPurchased();
+
+        coupon.redemptions += 1;
+        planCouponRedeemed[planHash][couponId][user] = true;
+
+        emit PlanCouponRedeemed(planHash, couponId, user);
+        return coupon.discountBps;
+    }
+
+    /// @notice Split a plan's net revenue with collaborators; the merchant receives the remainder
+    /// @param planHash Plan hash
+    /// @param shares Recipients and their shares in basis points (empty to clear)
+    function setRevenueShares(bytes32 planHash, RevenueShare[] calldata shares) external {
+        PlanData storage plan = _requirePlan(planHash);
+        if (msg.sender != plan.merchant) revert UnauthorizedMerchant();
+        if (shares.length > MAX_REVENUE_SHARES) revert LimitExceeded();
+
+        delete revenueShares[planHash];
+        uint256 total;
+        for (uint256 i = 0; i < shares.length; i++) {
+            if (shares[i].recipient == address(0)) revert ZeroAddress();
+            if (shares[i].bps == 0) revert InvalidParameters();
+            total += shares[i].bps;
+            revenueShares[planHash].push(shares[i]);
+        }
+        if (total > 10_000) revert InvalidParameters();
+        plan.updatedAt = uint48(block.timestamp);
+
+        emit RevenueSharesUpdated(plan.merchant, planHash, shares);
+    }
+
+    function setPriceNoticePeriods(uint8 periods) external {
+        _requireGovernor();
+        if (periods == 0) revert InvalidParameters();
+        uint8 oldPeriods = priceNoticePeriods;
+        priceNoticePeriods = periods;
+        emit PriceNoticePeriodsUpdated(oldPeriods, periods);
+    }
+
     function transferPlanOwnership(bytes32 planHash, address newMerchant) external {
         if (newMerchant == address(0)) revert ZeroAddress();
         PlanData storage plan = _requirePlan(planHash);
@@ -198,6 +372,36 @@ contract PlanManager is IPlanManager {
         return plan;
     }
 
+    function getPlanIntro(bytes32 planHash) external view override returns (PlanIntro memory) {
+        return planIntros[planHash];
+    }
+
+    function getEffectivePrice(bytes32 planHash) external view override returns (uint128) {
+        PlanData memory plan = plans[planHash];
+        if (plan.merchant == address(0)) revert PlanNotFound();
+        return _listPrice(planHash, plan.price);
+    }
+
+    function getRenewalPrice(
+        bytes32 planHash,
+        uint128 lockedPrice,
+        uint40 lockedAt
+    ) external view override returns (uint128) {
+        PlanData memory plan = plans[planHash];
+        if (plan.merchant == address(0)) revert PlanNotFound();
+        uint128 price = _listPrice(planHash, plan.price);
+        if (lockedPrice == 0 || price <= lockedPrice) return price;
+
+        // an increase reaches a subscriber locked in before it was announced only after the notice window
+        PriceChange memory change = priceChanges[planHash];
+        if (lockedAt >= change.scheduledAt || block.timestamp >= change.effectiveAt) return price;
+        return lockedPrice;
+    }
+
+    function getRevenueShares(bytes32 planHash) external view override returns (RevenueShare[] memory) {
+        return revenueShares[planHash];
+    }
+
     function isPlanActive(bytes32 planHash) external view override returns (bool) {
         PlanData memory plan = plans[planHash];
         return plan.status == PlanStatus.Active;
@@ -227,6 +431,11 @@ contract PlanManager is IPlanManager {
         return plan;
     }
 
+    function _listPrice(bytes32 planHash, uint128 signedPrice) internal view returns (uint128) {
+        PriceChange memory change = priceChanges[planHash];
+        return change.scheduledAt == 0 ? signedPrice : change.newPrice;
+    }
+
     function _addActivePlan(address merchant, bytes32 planHash) internal {
         if (activePlanIndexes[merchant][planHash] != 0) return;
         activePlans[merchant].push(planHash);
@@ -31,7 +31,8 @@ contract SubscriptionManager is ReentrancyGuard {
     enum SubscriptionStatus {
         None,
         Active,
-        Inactive
+        Inactive,
+        Paused
     }
 
     enum CancelReason {
@@ -42,6 +43,13 @@ contract SubscriptionManager is ReentrancyGuard {
         Switch
     }
 
+    enum Standing {
+        None,
+        Current,
+        PastDue,
+        Paused
+    }
+
     enum ActivationMode {
         ImmediateCharge,
         StartNextPeriod
@@ -56,6 +64,12 @@ contract SubscriptionManager is ReentrancyGuard {
         SubscriptionStatus status;
         CancelReason cancelReason;
         uint40 createdAt;
+        uint40 pausedAt;
+        uint40 pausedUntil;
+        uint40 trialEndsAt; // unpaid trial time before this point earns no tier-change credit
+        uint128 periodPrice; // amount charged for each period currently covered
+        uint128 lockedPrice; // list price renewals are locked to; 0 follows the list price
+        uint40 priceLockedAt;
     }
 
     mapping(address => mapping(bytes32 => SubscriptionState)) private subscriptionStates;
@@ -63,15 +77,35 @@ contract SubscriptionManager is ReentrancyGuard {
     mapping(address => bytes32[]) private userPlans;
     mapping(address => mapping(bytes32 => uint256)) private userPlanIndex; // index + 1
     mapping(address => uint256) private nativeDeposits;
+    mapping(address => uint32) public maxPauseDuration; // merchant => max pause in seconds (0 = pausing disabled)
+    mapping(address => mapping(address => bool)) public introUsed; // user => merchant => trial/discount consumed
+    mapping(address => mapping(address => uint256)) public merchantRevenue; // merchant => token => net revenue
+
+    // Streaming prepayments: escrowed upfront and accrued to the merchant per second
+    struct Stream {
+        bytes32 planHash; // plan whose revenue shares apply to payouts
+        address token;
+        uint40 startsAt;
+        uint40 endsAt;
+        uint256 deposited;
+        uint256 claimed;
+    }
+
+    mapping(address => mapping(address => Stream)) public streams; // user => merchant => stream
+    mapping(address => mapping(address => uint256)) public pendingPayouts; // recipient => token => unsent payouts
 
     uint16 public batchLimit;
+    uint40 public retryDelay = 24 hours; // grace period before a failed charge is retried
 
-    uint40 public constant RETRY_DELAY = 24 hours;
+    uint40 public constant MIN_RETRY_DELAY = 1 hours;
+    uint40 public constant MAX_RETRY_DELAY = 30 days;
+    uint32 public constant MAX_PREPAID_PERIODS = 24;
 
     uint8 private constant SKIP_REASON_NO_PLAN = 1;
     uint8 private constant SKIP_REASON_NOT_DUE = 2;
     uint8 private constant SKIP_REASON_INSUFFICIENT_NATIVE_DEPOSIT = 3;
     uint8 private constant SKIP_REASON_PLAN_INACTIVE = 4;
+    uint8 private constant SKIP_REASON_PAUSED = 5;
 
     event SubscriptionActivated(
         address indexed user,
@@ -93,6 +127,32 @@ contract SubscriptionManager is ReentrancyGuard {
     event NativeDepositIncreased(address indexed user, uint256 amount, uint256 newBalance);
     event NativeDepositWithdrawn(address indexed user, uint256 amount, uint256 newBalance);
     event ChargeSkipped(address indexed user, bytes32 indexed planHash, uint8 reason);
+    event SubscriptionPaused(address indexed user, bytes32 indexed planHash, uint40 pausedUntil);
+    event SubscriptionResumed(address indexed user, bytes32 indexed planHash, uint40 nextChargeAt);
+    event MaxPauseDurationUpdated(address indexed merchant, uint32 duration);
+    event BatchLimitUpdated(uint16 newLimit);
+    event RetryDelayUpdated(uint40 newDelay);
+    event SubscriptionTierChanged(
+        address indexed user,
+        bytes32 indexed fromPlan,
+        bytes32 indexed toPlan,
+        uint256 charged,
+        uint256 credit
+    );
+    event SubscriptionTrialStarted(address indexed user, bytes32 indexed planHash, uint40 trialEndsAt);
+    event StreamStarted(
+        address indexed user,
+        address indexed merchant,
+        address token,
+        uint256 amount,
+        uint40 startsAt,
+        uint40 endsAt
+    );
+    event StreamClaimed(address indexed user, address indexed merchant, uint256 amount);
+    event StreamClosed(address indexed user, address indexed merchant, uint256 merchantAmount, uint256 refund);
+    event RevenueSharePaid(bytes32 indexed planHash, address indexed recipient, address token, uint256 amount);
+    event PayoutDeferred(address indexed recipient, address token, uint256 amount);
+    event PayoutWithdrawn(address indexed recipient, address token, uint256 amount);
 
     modifier onlyAdmin() {
         if (!core.hasRole(0x00, msg.sender)) revert NotAdmin();
@@ -114,6 +174,11 @@ contract SubscriptionManager is ReentrancyGuard {
         _;
     }
 
+    modifier whenModuleActive() {
+        if (core.isModulePaused(MODULE_ID)) revert ModulePaused();
+        _;
+    }
+
     modifier onlyRole(bytes32 role) {
         if (!core.hasRole(role, msg.sender)) revert Forbidden();
         _;
@@ -149,8 +214,8 @@ contract SubscriptionManager is ReentrancyGuard {
         SignatureLib.Plan calldata plan,
         bytes calldata sigMerchant,
         bytes calldata permitSig
-    ) external payable nonReentrant {
-        _subscribe(plan, sigMerchant, permitSig, plan.token, plan.price, '');
+    ) external payable whenModuleActive nonReentrant {
+        _subscribe(plan, sigMerchant, permitSig, plan.token, plan.price, '', bytes32(0));
     }
 
     function subscribe(
@@ -158,8 +223,20 @@ contract SubscriptionManager is ReentrancyGuard {
         bytes calldata sigMerchant,
         bytes calldata permitSig,
         string calldata planUri
-    ) external payable nonReentrant {
-        _subscribe(plan, sigMerchant, permitSig, plan.token, plan.price, planUri);
+    ) external payable whenModuleActive nonReentrant {
+        _subscribe(plan, sigMerchant, permitSig, plan.token, plan.price, planUri, bytes32(0));
+    }
+
+    /// @notice Subscribe with a plan coupon discounting the first charged period
+    /// @param couponId Coupon identifier issued for this plan in PlanManager
+    function subscribeWithCoupon(
+        SignatureLib.Plan calldata plan,
+        bytes calldata sigMerchant,
+        bytes calldata permitSig,
+        bytes32 couponId
+    ) external payable whenModuleActive nonReentrant {
+        if (couponId == bytes32(0)) revert InvalidArgument();
+        _subscribe(plan, sigMerchant, permitSig, plan.token, plan.price, '', couponId);
     }
 
     function subscribeWithToken(
@@ -168,7 +245,7 @@ contract SubscriptionManager is ReentrancyGuard {
         bytes calldata permitSig,
         address paymentToken,
         uint256 maxPaymentAmount
-    ) external nonReentrant {
+    ) external whenModuleActive nonReentrant {
         _subscribeWithToken(plan, sigMerchant, permitSig, paymentToken, maxPaymentAmount, '');
     }
 
@@ -179,7 +256,7 @@ contract SubscriptionManager is ReentrancyGuard {
         address paymentToken,
         uint256 maxPaymentAmount,
         string calldata planUri
-    ) external nonReentrant {
+    ) external whenModuleActive nonReentrant {
         _subscribeWithToken(plan, sigMerchant, permitSig, paymentToken, maxPaymentAmount, planUri);
     }
 
@@ -197,6 +274,209 @@ contract SubscriptionManager is ReentrancyGuard {
         }
     }
 
+    /// @notice Pay several upcoming periods of the caller's subscription to a merchant in advance
+    /// @dev Native plans are paid from msg.value; the next charge moves forward by `periods` periods
+    /// @param merchant Merchant address
+    /// @param periods Number of periods to prepay
+    function prepayPeriods(address merchant, uint32 periods) external payable whenModuleActive nonReentrant {
+        if (periods == 0 || periods > MAX_PREPAID_PERIODS) revert InvalidParameters();
+
+        bytes32 planHash = activePlanByMerchant[msg.sender][merchant];
+        if (planHash == bytes32(0)) revert NoPlan();
+
+        SubscriptionState storage state = subscriptionStates[msg.sender][planHash];
+        if (state.status != SubscriptionStatus.Active) revert InvalidState();
+
+        IPlanManager.PlanData memory plan = _getRenewalPlan(msg.sender, planHash);
+        if (plan.status != IPlanManager.PlanStatus.Active) revert PlanInactive();
+
+        uint256 amount = uint256(plan.price) * periods;
+        IPaymentGateway gateway = IPaymentGateway(_getPaymentGateway());
+
+        uint256 netAmount;
+        if (plan.token == address(0)) {
+            if (msg.value != amount) revert InvalidAmount();
+            netAmount = gateway.processPayment{value: amount}(MODULE_ID, plan.token, msg.sender, amount, '');
+        } else {
+            if (msg.value != 0) revert InvalidAmount();
+            netAmount = gateway.processPayment(MODULE_ID, plan.token, msg.sender, amount, '');
+        }
+        _payMerchant(planHash, plan.merchant, plan.token, netAmount);
+
+        state.lastChargedAt = uint40(block.timestamp);
+        state.nextChargeAt += uint40(uint256(plan.period) * periods);
+        state.periodPrice = plan.price;
+        _lockPrice(state, plan.price);
+        state.retryAt = 0;
+        state.retryCount = 0;
+
+        emit SubscriptionCharged(msg.sender, planHash, amount, state.nextChargeAt);
+    }
+
+    /// @notice Escrow several upcoming periods that accrue to the merchant per second
+    /// @dev The stream starts when the current period ends; the unstreamed remainder is refunded on cancellation.
+    /// An existing stream is settled first and its unstreamed remainder is folded into the new one
+    /// @param merchant Merchant address
+    /// @param periods Number of periods to stream
+    function streamPeriods(address merchant, uint32 periods) external payable whenModuleActive nonReentrant {
+        if (periods == 0 || periods > MAX_PREPAID_PERIODS) revert InvalidParameters();
+
+        bytes32 planHash = activePlanByMerchant[msg.sender][merchant];
+        if (planHash == bytes32(0)) revert NoPlan();
+
+        SubscriptionState storage state = subscriptionStates[msg.sender][planHash];
+        if (state.status != SubscriptionStatus.Active) revert InvalidState();
+
+        IPlanManager.PlanData memory plan = _getRenewalPlan(msg.sender, planHash);
+        if (plan.status != IPlanManager.PlanStatus.Active) revert PlanInactive();
+
+        uint256 amount = uint256(plan.price) * periods;
+        IPaymentGateway gateway = IPaymentGateway(_getPaymentGateway());
+
+        uint256 netAmount;
+        if (plan.token == address(0)) {
+            if (msg.value != amount) revert InvalidAmount();
+            netAmount = gateway.processPayment{value: amount}(MODULE_ID, plan.token, msg.sender, amount, '');
+        } else {
+            if (msg.value != 0) revert InvalidAmount();
+            netAmount = gateway.processPayment(MODULE_ID, plan.token, msg.sender, amount, '');
+        }
+        if (netAmount == 0) revert InvalidAmount();
+
+        uint40 startsAt = state.nextChargeAt > block.timestamp ? state.nextChargeAt : uint40(block.timestamp);
+        uint40 endsAt = startsAt + uint40(uint256(plan.period) * periods);
+        uint256 carried = _settleStream(msg.sender, merchant);
+        if (carried > 0) {
+            // the running stream ends at nextChargeAt, so the merged one keeps its start
+            uint40 previousStart = streams[msg.sender][merchant].startsAt;
+            startsAt = previousStart > block.timestamp ? previousStart : uint40(block.timestamp);
+        }
+        streams[msg.sender][merchant] = Stream({
+            planHash: planHash,
+            token: plan.token,
+            startsAt: startsAt,
+            endsAt: endsAt,
+            deposited: netAmount + carried,
+            claimed: 0
+        });
+        state.nextChargeAt = endsAt;
+        state.retryAt = 0;
+        state.retryCount = 0;
+
+        emit StreamStarted(msg.sender, merchant, plan.token, netAmount + carried, startsAt, endsAt);
+    }
+
+    /// @notice Withdraw the amount streamed to the caller by a subscriber so far
+    /// @param user Subscriber address
+    function claimStreamed(address user) external nonReentrant {
+        Stream storage stream = streams[user][msg.sender];
+        if (stream.deposited == 0) revert NotFound();
+
+        if (streamedAmount(user, msg.sender) == stream.claimed) revert NothingToWithdraw();
+
+        _settleStream(user, msg.sender);
+    }
+
+    /// @notice Total amount of a stream accrued to the merchant, claimed or not
+    /// @param user Subscriber address
+    /// @param merchant Merchant address
+    /// @return Accrued amount
+    function streamedAmount(address user, address merchant) public view returns (uint256) {
+        Stream memory stream = streams[user][merchant];
+        if (stream.deposited == 0 || block.timestamp <= stream.startsAt) return 0;
+        if (block.timestamp >= stream.endsAt) return stream.deposited;
+        return (stream.deposited * (block.timestamp - stream.startsAt)) / (stream.endsAt - stream.startsAt);
+    }
+
+    /// @notice Move the caller's subscription to another registered plan of the same merchant with proration
+    /// @dev Unused time on the current plan is credited against the new price; surplus credit extends the new period
+    /// @param newPlanHash Hash of the target plan
+    function changeTier(bytes32 newPlanHash) external payable whenModuleActive nonReentrant {
+        IPlanManager.PlanData memory newPlan = _getBillingPlan(newPlanHash);
+        if (newPlan.status != IPlanManager.PlanStatus.Active) revert PlanInactive();
+
+        bytes32 currentHash = activePlanByMerchant[msg.sender][newPlan.merchant];
+        if (currentHash == bytes32(0)) revert NoPlan();
+        if (currentHash == newPlanHash) revert InvalidParameters();
+        if (_settleStream(msg.sender, newPlan.merchant) != 0) revert InvalidState();
+
+        SubscriptionState storage current = subscriptionStates[msg.sender][currentHash];
+        if (current.status != SubscriptionStatus.Active) revert InvalidState();
+
+        IPlanManager.PlanData memory currentPlan = _getBillingPlan(currentHash);
+        if (currentPlan.token != newPlan.token) revert InvalidParameters();
+
+        // only paid time earns credit: an unpaid trial gets none, and an intro discount is not credited at full price
+        uint256 paidFrom = current.trialEndsAt > block.timestamp ? current.trialEndsAt : block.timestamp;
+        uint256 remaining = current.nextChargeAt > paidFrom ? current.nextChargeAt - paidFrom : 0;
+        uint256 credit = (uint256(current.periodPrice) * remaining) / currentPlan.period;
+        uint256 charged = newPlan.price > credit ? newPlan.price - credit : 0;
+
+        if (newPlan.token == address(0)) {
+            if (msg.value != charged) revert InvalidAmount();
+        } else if (msg.value != 0) {
+            revert InvalidAmount();
+        }
+
+        if (charged > 0) {
+            IPaymentGateway gateway = IPaymentGateway(_getPaymentGateway());
+            uint256 netAmount;
+            if (newPlan.token == address(0)) {
+                netAmount = gateway.processPayment{value: charged}(MODULE_ID, address(0), msg.sender, charged, '');
+            } else {
+                netAmount = gateway.processPayment(MODULE_ID, newPlan.token, msg.sender, charged, '');
+            }
+            _payMerchant(newPlanHash, newPlan.merchant, newPlan.token, netAmount);
+        }
+
+        _activateSubscription(msg.sender, newPlanHash, newPlan);
+
+        if (credit > newPlan.price) {
+            SubscriptionState storage next = subscriptionStates[msg.sender][newPlanHash];
+            next.nextChargeAt += uint40(((credit - newPlan.price) * newPlan.period) / newPlan.price);
+        }
+
+        emit SubscriptionTierChanged(msg.sender, currentHash, newPlanHash, charged, credit);
+    }
+
+    /// @notice Freeze the renewal schedule of the caller's subscription to a merchant
+    /// @dev The pause ends automatically after the merchant's max pause duration
+    function pauseSubscription(address merchant) external {
+        bytes32 planHash = activePlanByMerchant[msg.sender][merchant];
+        if (planHash == bytes32(0)) revert NoPlan();
+
+        SubscriptionState storage state = subscriptionStates[msg.sender][planHash];
+        if (state.status != SubscriptionStatus.Active || state.retryAt != 0) revert InvalidState();
+        if (streams[msg.sender][merchant].deposited != 0) revert InvalidState();
+
+        uint32 maxPause = maxPauseDuration[merchant];
+        if (maxPause == 0) revert Forbidden();
+
+        state.status = SubscriptionStatus.Paused;
+        state.pausedAt = uint40(block.timestamp);
+        state.pausedUntil = uint40(block.timestamp + maxPause);
+
+        emit SubscriptionPaused(msg.sender, planHash, state.pausedUntil);
+    }
+
+    /// @notice Resume a paused subscription, shifting the next charge by the time spent paused
+    function resumeSubscription(address merchant) external {
+        bytes32 planHash = activePlanByMerchant[msg.sender][merchant];
+        if (planHash == bytes32(0)) revert NoPlan();
+
+        SubscriptionState storage state = subscriptionStates[msg.sender][planHash];
+        if (state.status != SubscriptionStatus.Paused) revert InvalidState();
+
+        _resume(msg.sender, planHash, state);
+    }
+
+    /// @notice Set the longest pause subscribers may take on the caller's plans
+    /// @param duration Max pause in seconds (0 disables pausing)
+    function setMaxPauseDuration(uint32 duration) external {
+        maxPauseDuration[msg.sender] = duration;
+        emit MaxPauseDurationUpdated(msg.sender, duration);
+    }
+
     function forceCancel(address user, address merchant, uint8 reason) external onlyOperator nonReentrant {
         bytes32 planHash = activePlanByMerchant[user][merchant];
         if (planHash == bytes32(0)) revert NoPlan();
@@ -230,6 +510,9 @@ contract SubscriptionManager is ReentrancyGuard {
         state.nextChargeAt = mode == ActivationMode.ImmediateCharge
             ? uint40(block.timestamp)
             : uint40(block.timestamp + plan.period);
+        state.trialEndsAt = 0;
+        state.periodPrice = 0;
+        _lockPrice(state, 0);
 
         activePlanByMerchant[user][plan.merchant] = planHash;
         _ensureUserPlanListed(user, planHash);
@@ -242,16 +525,19 @@ contract SubscriptionManager is ReentrancyGuard {
     // Автосписание
     // ---------------------------------------------------------------------
 
-    function charge(address user, bytes32 planHash) public onlyAutomation nonReentrant {
+    function charge(address user, bytes32 planHash) public onlyAutomation whenModuleActive nonReentrant {
         _charge(user, planHash, true);
     }
 
-    function charge(address user) public onlyAutomation nonReentrant {
+    function charge(address user) public onlyAutomation whenModuleActive nonReentrant {
         bytes32 planHash = _singleActivePlan(user);
         _charge(user, planHash, true);
     }
 
-    function chargeBatch(address[] calldata users, bytes32[] calldata plans) external onlyAutomation nonReentrant {
+    function chargeBatch(
+        address[] calldata users,
+        bytes32[] calldata plans
+    ) external onlyAutomation whenModuleActive nonReentrant {
         if (users.length != plans.length) revert LengthMismatch();
         uint256 limit = users.length;
         if (batchLimit > 0 && limit > batchLimit) {
@@ -268,6 +554,15 @@ contract SubscriptionManager is ReentrancyGuard {
 
     function setBatchLimit(uint16 newLimit) external onlyRole(CoreDefs.GOVERNOR_ROLE) {
         batchLimit = newLimit;
+        emit BatchLimitUpdated(newLimit);
+    }
+
+    /// @notice Set the grace period between a failed charge and its retry
+    /// @param newDelay Delay in seconds, between MIN_RETRY_DELAY and MAX_RETRY_DELAY
+    function setRetryDelay(uint40 newDelay) external onlyRole(CoreDefs.GOVERNOR_ROLE) {
+        if (newDelay < MIN_RETRY_DELAY || newDelay > MAX_RETRY_DELAY) revert InvalidArgument();
+        retryDelay = newDelay;
+        emit RetryDelayUpdated(newDelay);
     }
 
     // ---------------------------------------------------------------------
@@ -280,6 +575,31 @@ contract SubscriptionManager is ReentrancyGuard {
         return subscriptionStates[user][planHash];
     }
 
+    /// @notice Payment standing of a user's subscription to a merchant, for gating content
+    /// @dev A subscription is past due once a charge failed or the retry delay after its due date has passed;
+    /// a successful charge makes it current again
+    /// @param user Subscriber address
+    /// @param merchant Merchant address
+    /// @return standing Current payment standing
+    /// @return missedPeriods Number of unpaid periods while past due
+    function getStanding(
+        address user,
+        address merchant
+    ) external view returns (Standing standing, uint32 missedPeriods) {
+        bytes32 planHash = activePlanByMerchant[user][merchant];
+        if (planHash == bytes32(0)) return (Standing.None, 0);
+
+        SubscriptionState memory state = subscriptionStates[user][planHash];
+        if (state.status == SubscriptionStatus.Paused) return (Standing.Paused, 0);
+        if (state.retryCount == 0 && block.timestamp < uint256(state.nextChargeAt) + retryDelay) {
+            return (Standing.Current, 0);
+        }
+
+        uint256 period = _getPlan(planHash).period;
+        uint256 overdue = block.timestamp > state.nextChargeAt ? block.timestamp - state.nextChargeAt : 0;
+        return (Standing.PastDue, uint32(overdue / period + 1));
+    }
+
     function getSubscriptionByPlan(address user, bytes32 planHash) external view returns (SubscriptionState memory) {
         return subscriptionStates[user][planHash];
     }
@@ -309,13 +629,28 @@ contract SubscriptionManager is ReentrancyGuard {
     function withdrawNativeFunds(uint256 amount) external nonReentrant {
         if (amount == 0) revert InvalidAmount();
         uint256 balance = nativeDeposits[msg.sender];
-        if (amount > balance) revert InsufficientBalance();
+        if (amount > balance) revert InsufficientBalance(amount, balance);
         nativeDeposits[msg.sender] = balance - amount;
         (bool success, ) = payable(msg.sender).call{value: amount}('');
         if (!success) revert TransferFailed();
         emit NativeDepositWithdrawn(msg.sender, amount, nativeDeposits[msg.sender]);
     }
 
+    /// @notice Withdraw payouts and stream refunds that could not be sent to the caller when they fell due
+    /// @param token Payout token (0 for native)
+    function withdrawPayout(address token) external nonReentrant {
+        uint256 amount = pendingPayouts[msg.sender][token];
+        if (amount == 0) revert NothingToWithdraw();
+        pendingPayouts[msg.sender][token] = 0;
+        if (token == address(0)) {
+            (bool success, ) = payable(msg.sender).call{value: amount}('');
+            if (!success) revert TransferFailed();
+        } else {
+            IERC20(token).safeTransfer(msg.sender, amount);
+        }
+        emit PayoutWithdrawn(msg.sender, token, amount);
+    }
+
     // ---------------------------------------------------------------------
     // Внутренние функции
     // ---------------------------------------------------------------------
@@ -333,7 +668,7 @@ contract SubscriptionManager is ReentrancyGuard {
         if (plan.merchant == address(0)) revert ZeroAddress();
 
         if (paymentToken == plan.token) {
-            _subscribe(plan, sigMerchant, permitSig, plan.token, plan.price, planUri);
+            _subscribe(plan, sigMerchant, permitSig, plan.token, plan.price, planUri, bytes32(0));
             return;
         }
 
@@ -345,9 +680,11 @@ contract SubscriptionManager is ReentrancyGuard {
         uint256 paymentAmount = gateway.convertAmount(MODULE_ID, plan.token, paymentToken, plan.price);
         if (paymentAmount == 0) revert InvalidPrice();
 
-        if (maxPaymentAmount > 0 && paymentAmount > maxPaymentAmount) revert PriceExceedsMaximum();
+        if (maxPaymentAmount > 0 && paymentAmount > maxPaymentAmount) {
+            revert PriceExceedsMaximum(maxPaymentAmount, paymentAmount);
+        }
 
-        _subscribe(plan, sigMerchant, permitSig, paymentToken, paymentAmount, planUri);
+        _subscribe(plan, sigMerchant, permitSig, paymentToken, paymentAmount, planUri, bytes32(0));
     }
 
     function _subscribe(
@@ -356,20 +693,17 @@ contract SubscriptionManager is ReentrancyGuard {
         bytes calldata permitSig,
         address paymentToken,
         uint256 paymentAmount,
-        string memory planUri
+        string memory planUri,
+        bytes32 couponId
     ) internal {
         if (paymentAmount == 0) revert InvalidAmount();
         if (plan.merchant == address(0)) revert ZeroAddress();
         if (plan.period == 0) revert InvalidParameters();
 
         bool isNativePayment = paymentToken == address(0);
-        if (isNativePayment) {
-            if (msg.value < paymentAmount) revert InsufficientBalance();
-        } else if (msg.value != 0) {
-            revert InvalidAmount();
-        }
+        if (!isNativePayment && msg.value != 0) revert InvalidAmount();
 
-        if (!(plan.expiry == 0 || plan.expiry >= block.timestamp)) revert Expired();
+        if (!(plan.expiry == 0 || plan.expiry >= block.timestamp)) revert DeadlineExpired(plan.expiry, block.timestamp);
 
         bool chainAllowed = false;
         uint256 chainIdsLen = plan.chainIds.length;
@@ -396,6 +730,24 @@ contract SubscriptionManager is ReentrancyGuard {
 
         if (sigMerchant.length > 0 && ECDSA.recover(planHash, sigMerchant) != plan.merchant) revert InvalidSignature();
 
+        IPlanManager.PlanIntro memory intro = _consumeIntro(msg.sender, planHash, storedPlan.merchant);
+        uint128 listPrice = IPlanManager(_getPlanManagerAddress()).getEffectivePrice(planHash);
+        uint256 chargedPrice = listPrice;
+        if (intro.trialSeconds > 0) {
+            chargedPrice = 0;
+        } else if (intro.firstPeriodPrice > 0) {
+            chargedPrice = intro.firstPeriodPrice;
+        }
+        if (couponId != bytes32(0)) {
+            // coupons discount a charged first period; stacking one on a free trial would burn a redemption
+            if (chargedPrice == 0) revert InvalidParameters();
+            uint16 discountBps = IPlanManager(_getPlanManagerAddress()).redeemCoupon(planHash, couponId, msg.sender);
+            chargedPrice -= (chargedPrice * discountBps) / 10000;
+        }
+        // paymentAmount is quoted for the full plan price, possibly in a converted token
+        uint256 dueAmount = chargedPrice == plan.price ? paymentAmount : (paymentAmount * chargedPrice) / plan.price;
+        if (isNativePayment && msg.value < dueAmount) revert InsufficientBalance(dueAmount, msg.value);
+
         address gatewayAddress = _getPaymentGateway();
         IPaymentGateway gateway = IPaymentGateway(gatewayAddress);
 
@@ -403,29 +755,50 @@ contract SubscriptionManager is ReentrancyGuard {
 
         uint256 netAmount;
         if (isNativePayment) {
-            uint256 depositAdded = msg.value - paymentAmount;
-            netAmount = gateway.processPayment{value: paymentAmount}(
-                MODULE_ID,
-                paymentToken,
-                msg.sender,
-                paymentAmount,
-                ''
-            );
-            if (netAmount > 0) {
-                (bool success, ) = payable(plan.merchant).call{value: netAmount}('');
-                if (!success) revert TransferFailed();
+            uint256 depositAdded = msg.value - dueAmount;
+            if (dueAmount > 0) {
+                netAmount = gateway.processPayment{value: dueAmount}(
+                    MODULE_ID,
+                    paymentToken,
+                    msg.sender,
+                    dueAmount,
+                    ''
+                );
             }
             if (depositAdded > 0) {
                 nativeDeposits[msg.sender] += depositAdded;
                 emit NativeDepositIncreased(msg.sender, depositAdded, nativeDeposits[msg.sender]);
             }
-        } else {
-            netAmount = gateway.processPayment(MODULE_ID, paymentToken, msg.sender, paymentAmount, '');
-            IERC20(paymentToken).safeTransfer(plan.merchant, netAmount);
+        } else if (dueAmount > 0) {
+            netAmount = gateway.processPayment(MODULE_ID, paymentToken, msg.sender, dueAmount, '');
         }
+        _payMerchant(planHash, plan.merchant, paymentToken, netAmount);
 
         _activateSubscription(msg.sender, planHash, storedPlan);
-        emit SubscriptionCharged(msg.sender, planHash, plan.price, uint40(block.timestamp + storedPlan.period));
+        SubscriptionState storage state = subscriptionStates[msg.sender][planHash];
+        state.periodPrice = uint128(chargedPrice);
+        // new subscribers lock in the list price, even when an intro discounts their first period
+        _lockPrice(state, listPrice);
+        if (intro.trialSeconds > 0) {
+            uint40 trialEndsAt = uint40(block.timestamp + intro.trialSeconds);
+            state.nextChargeAt = trialEndsAt;
+            state.trialEndsAt = trialEndsAt;
+            emit SubscriptionTrialStarted(msg.sender, planHash, trialEndsAt);
+        } else {
+            emit SubscriptionCharged(msg.sender, planHash, chargedPrice, uint40(block.timestamp + storedPlan.period));
+        }
+    }
+
+    /// @dev Returns the plan's introductory terms if the user has never received them from this merchant
+    function _consumeIntro(
+        address user,
+        bytes32 planHash,
+        address merchant
+    ) internal returns (IPlanManager.PlanIntro memory intro) {
+        if (introUsed[user][merchant]) return intro;
+        intro = IPlanManager(_getPlanManagerAddress()).getPlanIntro(planHash);
+        // any first subscription with the merchant uses up eligibility, so switching plans cannot restart a trial
+        introUsed[user][merchant] = true;
     }
 
     function _activateSubscription(address user, bytes32 planHash, IPlanManager.PlanData memory plan) internal {
@@ -446,6 +819,9 @@ contract SubscriptionManager is ReentrancyGuard {
         state.retryAt = 0;
         state.lastChargedAt = uint40(block.timestamp);
         state.nextChargeAt = uint40(block.timestamp + plan.period);
+        state.trialEndsAt = 0;
+        state.periodPrice = plan.price;
+        _lockPrice(state, plan.price);
 
         activePlanByMerchant[user][plan.merchant] = planHash;
         _ensureUserPlanListed(user, planHash);
@@ -455,13 +831,22 @@ contract SubscriptionManager is ReentrancyGuard {
 
     function _charge(address user, bytes32 planHash, bool strict) internal returns (bool processed) {
         SubscriptionState storage state = subscriptionStates[user][planHash];
+        if (state.status == SubscriptionStatus.Paused) {
+            if (block.timestamp < state.pausedUntil) {
+                if (strict) revert InvalidState();
+                emit ChargeSkipped(user, planHash, SKIP_REASON_PAUSED);
+                return false;
+            }
+            _resume(user, planHash, state);
+        }
+
         if (state.status != SubscriptionStatus.Active) {
             if (strict) revert NoPlan();
             emit ChargeSkipped(user, planHash, SKIP_REASON_NO_PLAN);
             return false;
         }
 
-        IPlanManager.PlanData memory plan = _getPlan(planHash);
+        IPlanManager.PlanData memory plan = _getRenewalPlan(user, planHash);
         if (plan.status != IPlanManager.PlanStatus.Active) {
             if (strict) revert PlanInactive();
             emit ChargeSkipped(user, planHash, SKIP_REASON_PLAN_INACTIVE);
@@ -482,7 +867,7 @@ contract SubscriptionManager is ReentrancyGuard {
         if (isNativePlan) {
             uint256 balance = nativeDeposits[user];
             if (balance < plan.price) {
-                if (strict) revert InsufficientBalance();
+                if (strict) revert InsufficientBalance(plan.price, balance);
                 emit ChargeSkipped(user, planHash, SKIP_REASON_INSUFFICIENT_NATIVE_DEPOSIT);
                 return false;
             }
@@ -492,17 +877,15 @@ contract SubscriptionManager is ReentrancyGuard {
         uint256 netAmount;
         if (isNativePlan) {
             netAmount = gateway.processPayment{value: plan.price}(MODULE_ID, plan.token, user, plan.price, '');
-            if (netAmount > 0) {
-                (bool success, ) = payable(plan.merchant).call{value: netAmount}('');
-                if (!success) revert TransferFailed();
-            }
         } else {
             netAmount = gateway.processPayment(MODULE_ID, plan.token, user, plan.price, '');
-            IERC20(plan.token).safeTransfer(plan.merchant, netAmount);
         }
+        _payMerchant(planHash, plan.merchant, plan.token, netAmount);
 
         state.lastChargedAt = uint40(block.timestamp);
         state.nextChargeAt = uint40(block.timestamp + plan.period);
+        state.periodPrice = plan.price;
+        _lockPrice(state, plan.price);
         state.retryAt = 0;
         state.retryCount = 0;
 
@@ -516,28 +899,112 @@ contract SubscriptionManager is ReentrancyGuard {
 
         if (state.retryCount == 0) {
             state.retryCount = 1;
-            state.retryAt = uint40(block.timestamp + RETRY_DELAY);
+            state.retryAt = uint40(block.timestamp + retryDelay);
             emit SubscriptionRetryScheduled(user, planHash, state.retryAt, state.retryCount);
         } else {
-            state.status = SubscriptionStatus.Inactive;
-            state.retryAt = 0;
-            state.cancelReason = CancelReason.RetryFailed;
-            activePlanByMerchant[user][state.merchant] = bytes32(0);
             emit SubscriptionFailedFinal(user, planHash, uint8(CancelReason.RetryFailed));
+            _deactivatePlan(user, planHash, CancelReason.RetryFailed);
         }
     }
 
+    function _resume(address user, bytes32 planHash, SubscriptionState storage state) internal {
+        uint40 resumedAt = uint40(block.timestamp) < state.pausedUntil ? uint40(block.timestamp) : state.pausedUntil;
+
+        state.status = SubscriptionStatus.Active;
+        state.nextChargeAt += resumedAt - state.pausedAt;
+        state.pausedAt = 0;
+        state.pausedUntil = 0;
+
+        emit SubscriptionResumed(user, planHash, state.nextChargeAt);
+    }
+
     function _deactivatePlan(address user, bytes32 planHash, CancelReason reason) internal {
         SubscriptionState storage state = subscriptionStates[user][planHash];
-        if (state.status != SubscriptionStatus.Active) return;
+        if (state.status != SubscriptionStatus.Active && state.status != SubscriptionStatus.Paused) return;
 
         state.status = SubscriptionStatus.Inactive;
         state.cancelReason = reason;
         state.retryAt = 0;
         state.retryCount = 0;
+        state.pausedAt = 0;
+        state.pausedUntil = 0;
         activePlanByMerchant[user][state.merchant] = bytes32(0);
 
         emit SubscriptionCancelled(user, planHash, uint8(reason));
+
+        _closeStream(user, state.merchant);
+    }
+
+    /// @dev Pay the merchant what a stream has accrued since the last claim; a fully streamed one is removed
+    /// @return remaining Unstreamed part of the deposit still owed to the user
+    function _settleStream(address user, address merchant) internal returns (uint256 remaining) {
+        Stream storage stream = streams[user][merchant];
+        if (stream.deposited == 0) return 0;
+
+        uint256 accrued = streamedAmount(user, merchant);
+        uint256 amount = accrued - stream.claimed;
+        bytes32 planHash = stream.planHash;
+        address token = stream.token;
+        remaining = stream.deposited - accrued;
+        if (remaining == 0) {
+            delete streams[user][merchant];
+        } else {
+            stream.claimed = accrued;
+        }
+
+        if (amount > 0) {
+            _payMerchant(planHash, merchant, token, amount);
+            emit StreamClaimed(user, merchant, amount);
+        }
+    }
+
+    /// @dev Settle a stream: the merchant receives the accrued remainder, the user the unstreamed part
+    function _closeStream(address user, address merchant) internal {
+        Stream memory stream = streams[user][merchant];
+        if (stream.deposited == 0) return;
+
+        uint256 accrued = streamedAmount(user, merchant);
+        uint256 merchantAmount = accrued - stream.claimed;
+        uint256 refund = stream.deposited - accrued;
+        delete streams[user][merchant];
+
+        _payMerchant(stream.planHash, merchant, stream.token, merchantAmount);
+        if (refund > 0) _sendFunds(stream.token, user, refund);
+
+        emit StreamClosed(user, merchant, merchantAmount, refund);
+    }
+
+    /// @dev Pay a plan's net revenue out to its revenue share recipients; the merchant receives the remainder,
+    /// including rounding dust
+    function _payMerchant(bytes32 planHash, address merchant, address token, uint256 amount) internal {
+        merchantRevenue[merchant][token] += amount;
+        if (amount == 0) return;
+
+        IPlanManager.RevenueShare[] memory shares = IPlanManager(_getPlanManagerAddress()).getRevenueShares(planHash);
+        uint256 remaining = amount;
+        for (uint256 i = 0; i < shares.length; i++) {
+            uint256 share = (amount * shares[i].bps) / 10_000;
+            if (share == 0) continue;
+            remaining -= share;
+            _sendFunds(token, shares[i].recipient, share);
+            emit RevenueSharePaid(planHash, shares[i].recipient, token, share);
+        }
+        if (remaining > 0) _sendFunds(token, merchant, remaining);
+    }
+
+    /// @dev Push funds to a recipient; a transfer the recipient rejects is credited to its pending payouts, so
+    /// a merchant or share recipient cannot block charges, cancellations or the user's stream refund
+    function _sendFunds(address token, address to, uint256 amount) internal {
+        bool success;
+        if (token == address(0)) {
+            (success, ) = payable(to).call{value: amount}('');
+        } else {
+            success = IERC20(token).trySafeTransfer(to, amount);
+        }
+        if (!success) {
+            pendingPayouts[to][token] += amount;
+            emit PayoutDeferred(to, token, amount);
+        }
     }
 
     function _ensureUserPlanListed(address user, bytes32 planHash) internal {
@@ -603,6 +1070,33 @@ contract SubscriptionManager is ReentrancyGuard {
         return IPlanManager(service).getPlan(planHash);
     }
 
+    /// @dev Plan data with the price that applies to charges made now
+    function _getBillingPlan(bytes32 planHash) internal view returns (IPlanManager.PlanData memory plan) {
+        plan = _getPlan(planHash);
+        plan.price = IPlanManager(_getPlanManagerAddress()).getEffectivePrice(planHash);
+    }
+
+    /// @dev Plan data with the price `user` renews at, honouring their price lock
+    function _getRenewalPlan(
+        address user,
+        bytes32 planHash
+    ) internal view returns (IPlanManager.PlanData memory plan) {
+        SubscriptionState storage state = subscriptionStates[user][planHash];
+        plan = _getPlan(planHash);
+        plan.price = IPlanManager(_getPlanManagerAddress()).getRenewalPrice(
+            planHash,
+            state.lockedPrice,
+            state.priceLockedAt
+        );
+    }
+
+    function _lockPrice(SubscriptionState storage state, uint128 price) internal {
+        // a renewal held at the locked price keeps the lock time, so a pending increase still lands on schedule
+        if (state.lockedPrice == price) return;
+        state.lockedPrice = price;
+        state.priceLockedAt = uint40(block.timestamp);
+    }
+
     function _getPaymentGateway() internal view returns (address) {
         address gatewayAddress = core.getService(MODULE_ID, 'PaymentGateway');
         if (gatewayAddress == address(0)) revert PaymentGatewayNotRegistered();
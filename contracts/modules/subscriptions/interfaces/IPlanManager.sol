@@ -20,11 +20,35 @@ interface IPlanManager {
         string uri;
     }
 
+    /// @notice Introductory terms applied to a user's first subscription with the plan's merchant
+    struct PlanIntro {
+        uint32 trialSeconds; // free period before the first charge (takes precedence over the discount)
+        uint128 firstPeriodPrice; // discounted price of the first period, in plan token units (0 = no discount)
+    }
+
+    /// @notice Collaborator share of a plan's net revenue; the merchant keeps the remainder
+    struct RevenueShare {
+        address recipient;
+        uint16 bps;
+    }
+
     function getPlan(bytes32 planHash) external view returns (PlanData memory);
 
+    function getPlanIntro(bytes32 planHash) external view returns (PlanIntro memory);
+
+    /// @notice Current list price, charged to new subscribers
+    function getEffectivePrice(bytes32 planHash) external view returns (uint128);
+
+    /// @notice Price of a renewal for a subscriber locked to `lockedPrice` since `lockedAt`
+    function getRenewalPrice(bytes32 planHash, uint128 lockedPrice, uint40 lockedAt) external view returns (uint128);
+
+    function getRevenueShares(bytes32 planHash) external view returns (RevenueShare[] memory);
+
     function isPlanActive(bytes32 planHash) external view returns (bool);
 
     function planStatus(bytes32 planHash) external view returns (PlanStatus);
 
     function listActivePlans(address merchant) external view returns (bytes32[] memory);
+
+    function redeemCoupon(bytes32 planHash, bytes32 couponId, address user) external returns (uint16);
 }
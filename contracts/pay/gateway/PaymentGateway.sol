@@ -30,6 +30,8 @@ contract PaymentGateway is IPaymentGateway, AccessControl, ReentrancyGuard, Paus
 
     mapping(bytes32 => mapping(address => bool)) private moduleAuthorizations;
     mapping(bytes32 => uint8) private paymentStatuses;
+    // Cumulative processor fees paid out per module and token (fees are pushed, never held)
+    mapping(bytes32 => mapping(address => uint256)) public feesCollected;
 
     event ModuleAuthorizationUpdated(bytes32 indexed moduleId, address indexed module, bool authorized);
 
@@ -85,7 +87,7 @@ contract PaymentGateway is IPaymentGateway, AccessControl, ReentrancyGuard, Paus
         uint256 actualAmount = amount;
 
         if (isNative) {
-            if (msg.value < amount) revert InsufficientBalance();
+            if (msg.value < amount) revert InsufficientBalance(amount, msg.value);
         } else {
             uint256 beforeBal = IERC20(token).balanceOf(address(this));
             IERC20(token).safeTransferFrom(payer, address(this), amount);
@@ -142,6 +144,10 @@ contract PaymentGateway is IPaymentGateway, AccessControl, ReentrancyGuard, Paus
             }
         }
 
+        if (distributedFees > 0) {
+            feesCollected[moduleId][token] += distributedFees;
+        }
+
         uint256 remainingBudget = feesBudget - distributedFees;
         if (remainingBudget > 0) {
             if (isNative) {
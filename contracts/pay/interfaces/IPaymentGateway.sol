@@ -9,7 +9,7 @@ interface IPaymentGateway {
     /// @param token Адрес токена (address(0) для нативной валюты)
     /// @param payer Адрес плательщика
     /// @param amount Сумма платежа
-    /// @param signature Подпись, если требуется
+    /// @param signature Подпись или метаданные для процессоров (например, ABI-адрес реферера)
     /// @return netAmount Чистая сумма после вычета комиссий
     function processPayment(
         bytes32 moduleId,
@@ -30,8 +30,10 @@ contract PaymentOrchestrator is AccessControl, ReentrancyGuard, Pausable {
     mapping(bytes32 => address[]) private moduleProcessors;
     mapping(bytes32 => mapping(string => bool)) private moduleProcessorConfig;
     uint256 private paymentNonce;
+    mapping(bytes32 => bool) public strictConversion; // module => oracle failures revert instead of converting 1:1
 
     event ProcessorConfigured(bytes32 indexed moduleId, string processorName, bool enabled);
+    event StrictConversionUpdated(bytes32 indexed moduleId, bool strict);
 
     constructor(address _processorRegistry) {
         require(_processorRegistry != address(0), 'Orchestrator: zero registry address');
@@ -46,7 +48,7 @@ contract PaymentOrchestrator is AccessControl, ReentrancyGuard, Pausable {
         address token,
         address payer,
         uint256 amount,
-        bytes calldata /* signature */
+        bytes calldata metadata
     )
         external
         nonReentrant
@@ -62,7 +64,7 @@ contract PaymentOrchestrator is AccessControl, ReentrancyGuard, Pausable {
             token,
             amount,
             currentNonce,
-            ''
+            metadata
         );
 
         bytes memory contextBytes = abi.encode(context);
@@ -122,6 +124,9 @@ contract PaymentOrchestrator is AccessControl, ReentrancyGuard, Pausable {
             if (processor == address(0)) continue;
             try IPaymentProcessor(processor).getName() returns (string memory name) {
                 if (keccak256(abi.encodePacked(name)) == keccak256(abi.encodePacked('PriceOracle'))) {
+                    if (strictConversion[moduleId]) {
+                        return IOracleProcessor(processor).convertAmount(moduleId, fromToken, toToken, amount);
+                    }
                     try IOracleProcessor(processor).convertAmount(moduleId, fromToken, toToken, amount) returns (
                         uint256 result
                     ) {
@@ -182,6 +187,15 @@ contract PaymentOrchestrator is AccessControl, ReentrancyGuard, Pausable {
         return true;
     }
 
+    /// @notice Make oracle failures (stale or missing feeds) revert conversions for a module
+    /// @dev Off by default, where a failing oracle falls back to converting 1:1
+    /// @param moduleId Module identifier
+    /// @param strict Whether conversions for the module revert on oracle failures
+    function setStrictConversion(bytes32 moduleId, bool strict) external onlyRole(PROCESSOR_MANAGER_ROLE) {
+        strictConversion[moduleId] = strict;
+        emit StrictConversionUpdated(moduleId, strict);
+    }
+
     function pause() external onlyRole(PAUSER_ROLE) {
         _pause();
     }
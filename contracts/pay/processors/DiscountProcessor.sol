@@ -15,6 +15,8 @@ contract DiscountProcessor is IPaymentProcessor, AccessControl {
 
     uint16 public discountPercent; // скидка в базисных пунктах (например, 100 = 1%)
 
+    event DiscountPercentUpdated(uint16 previousPercent, uint16 newPercent);
+
     constructor(uint16 initialDiscountPercent) {
         require(initialDiscountPercent <= 10000, 'DiscountProcessor: discount percent too high');
         discountPercent = initialDiscountPercent;
@@ -62,6 +64,7 @@ contract DiscountProcessor is IPaymentProcessor, AccessControl {
         require(configData.length == 2, 'DiscountProcessor: invalid config length');
         uint16 newDiscountPercent = (uint16(uint8(configData[0])) << 8) | uint16(uint8(configData[1]));
         require(newDiscountPercent <= 10000, 'DiscountProcessor: discount percent too high');
+        emit DiscountPercentUpdated(discountPercent, newDiscountPercent);
         discountPercent = newDiscountPercent;
     }
 }
@@ -13,10 +13,40 @@ contract FeeProcessor is IPaymentProcessor, AccessControl {
     string private constant PROCESSOR_NAME = 'FeeProcessor';
     string private constant PROCESSOR_VERSION = '1.0.0';
 
+    enum RoundingMode {
+        Floor,
+        Ceil,
+        HalfUp
+    }
+
     uint16 public feePercent; // комиссия в базисных пунктах (например, 100 = 1%)
     address public feeRecipient;
+    RoundingMode public roundingMode; // режим округления комиссии
+    uint16 public referralBps; // доля комиссии для реферера из метаданных платежа
+
+    struct TokenFee {
+        bool enabled;
+        uint16 feePercent;
+    }
+
+    mapping(address => TokenFee) public tokenFees; // переопределение комиссии для отдельных токенов
+    mapping(address => uint256) public minFees; // минимальная комиссия в единицах токена (ограничена суммой платежа)
+
+    struct FeeSplit {
+        address recipient;
+        uint16 bps;
+    }
+
+    uint8 public constant MAX_FEE_SPLITS = 8;
+    FeeSplit[] private feeSplits; // распределение платформенной комиссии (пусто = всё feeRecipient)
 
     event FeeRecipientUpdated(address indexed previousRecipient, address indexed newRecipient);
+    event FeePercentUpdated(uint16 previousPercent, uint16 newPercent);
+    event RoundingModeUpdated(RoundingMode mode);
+    event MinFeeUpdated(address indexed token, uint256 minFee);
+    event ReferralShareUpdated(uint16 previousBps, uint16 newBps);
+    event TokenFeeUpdated(address indexed token, bool enabled, uint16 feePercent);
+    event FeeSplitsUpdated(address[] recipients, uint16[] bps);
 
     constructor(uint16 initialFeePercent) {
         require(initialFeePercent <= 10000, 'FeeProcessor: fee percent too high');
@@ -36,7 +66,7 @@ contract FeeProcessor is IPaymentProcessor, AccessControl {
     ) external view override returns (IPaymentProcessor.ProcessResult result, bytes memory updatedContextBytes) {
         PaymentContext.Context memory context = abi.decode(contextBytes, (PaymentContext.Context));
 
-        uint256 feeAmount = (uint256(context.processedAmount) * feePercent) / 10000;
+        uint256 feeAmount = computeFeeForToken(context.token, context.processedAmount);
 
         if (feeAmount > context.processedAmount) {
             context = PaymentContext.setError(context, 'FeeProcessor: fee exceeds amount');
@@ -51,13 +81,146 @@ contract FeeProcessor is IPaymentProcessor, AccessControl {
 
             uint256 newAmount = uint256(context.processedAmount) - feeAmount;
             context = PaymentContext.updateProcessedAmount(context, newAmount);
-            context = PaymentContext.addFee(context, feeRecipient, feeAmount);
+
+            address referrer = _referrer(context.metadata);
+            uint256 referralAmount = referrer == address(0) ? 0 : (feeAmount * referralBps) / 10000;
+            if (referralAmount > 0) {
+                context = PaymentContext.addFee(context, referrer, referralAmount);
+            }
+            if (feeAmount > referralAmount) {
+                context = _addPlatformFee(context, feeAmount - referralAmount);
+            }
         }
 
         updatedContextBytes = abi.encode(context);
         return (IPaymentProcessor.ProcessResult.SUCCESS, updatedContextBytes);
     }
 
+    /// @notice Calculate the default percentage fee for an amount using the configured rounding mode
+    /// @dev Minimum fees are set per token, so they only apply in `computeFeeForToken`
+    /// @param amount Payment amount
+    /// @return feeAmount Fee amount, never greater than `amount`
+    function computeFee(uint256 amount) public view returns (uint256 feeAmount) {
+        return _computeFee(amount, feePercent, 0);
+    }
+
+    /// @notice Calculate the fee for an amount of a token, honouring a per-token fee override and minimum
+    /// @param token Payment token
+    /// @param amount Payment amount
+    /// @return feeAmount Fee amount, never greater than `amount`
+    function computeFeeForToken(address token, uint256 amount) public view returns (uint256 feeAmount) {
+        TokenFee memory tokenFee = tokenFees[token];
+        return _computeFee(amount, tokenFee.enabled ? tokenFee.feePercent : feePercent, minFees[token]);
+    }
+
+    /// @notice Override the fee percent for a single token
+    /// @param token Payment token
+    /// @param enabled Whether the override applies
+    /// @param percent Fee in basis points used for the token
+    function setTokenFee(address token, bool enabled, uint16 percent) external onlyRole(PROCESSOR_ADMIN_ROLE) {
+        require(percent <= 10000, 'FeeProcessor: fee percent too high');
+        tokenFees[token] = TokenFee({enabled: enabled, feePercent: percent});
+        emit TokenFeeUpdated(token, enabled, percent);
+    }
+
+    /// @dev A zero percent fee stays zero; the minimum only lifts non-zero percentage fees
+    function _computeFee(uint256 amount, uint16 percent, uint256 minimum) internal view returns (uint256 feeAmount) {
+        if (percent == 0) return 0;
+
+        uint256 product = amount * percent;
+        if (roundingMode == RoundingMode.Ceil) {
+            feeAmount = (product + 9999) / 10000;
+        } else if (roundingMode == RoundingMode.HalfUp) {
+            feeAmount = (product + 5000) / 10000;
+        } else {
+            feeAmount = product / 10000;
+        }
+
+        if (feeAmount < minimum) {
+            feeAmount = minimum > amount ? amount : minimum;
+        }
+    }
+
+    /// @notice Set the rounding mode applied to percentage fees
+    /// @param mode Rounding mode
+    function setRoundingMode(RoundingMode mode) external onlyRole(PROCESSOR_ADMIN_ROLE) {
+        roundingMode = mode;
+        emit RoundingModeUpdated(mode);
+    }
+
+    /// @notice Set the minimum fee charged per payment in a token
+    /// @dev Expressed in the token's own units; not applied when the token's fee percent is 0
+    /// @param token Payment token
+    /// @param newMinFee Minimum fee (0 disables)
+    function setMinFee(address token, uint256 newMinFee) external onlyRole(PROCESSOR_ADMIN_ROLE) {
+        minFees[token] = newMinFee;
+        emit MinFeeUpdated(token, newMinFee);
+    }
+
+    /// @notice Set the share of each fee paid to the referrer named in the payment metadata
+    /// @param newBps Share in basis points of the fee
+    function setReferralShare(uint16 newBps) external onlyRole(PROCESSOR_ADMIN_ROLE) {
+        require(newBps <= 10000, 'FeeProcessor: referral share too high');
+        emit ReferralShareUpdated(referralBps, newBps);
+        referralBps = newBps;
+    }
+
+    /// @notice Divide the platform fee among several recipients
+    /// @dev Pass empty arrays to send the whole platform fee to `feeRecipient` again
+    /// @param recipients Fee recipients, at most MAX_FEE_SPLITS
+    /// @param bps Shares in basis points, summing to 10000
+    function setFeeSplits(
+        address[] calldata recipients,
+        uint16[] calldata bps
+    ) external onlyRole(PROCESSOR_ADMIN_ROLE) {
+        require(recipients.length == bps.length, 'FeeProcessor: length mismatch');
+        require(recipients.length <= MAX_FEE_SPLITS, 'FeeProcessor: too many splits');
+
+        delete feeSplits;
+        uint256 total;
+        for (uint256 i = 0; i < recipients.length; i++) {
+            require(recipients[i] != address(0), 'FeeProcessor: zero recipient');
+            require(bps[i] > 0, 'FeeProcessor: zero share');
+            total += bps[i];
+            feeSplits.push(FeeSplit({recipient: recipients[i], bps: bps[i]}));
+        }
+        require(recipients.length == 0 || total == 10000, 'FeeProcessor: shares must total 10000');
+
+        emit FeeSplitsUpdated(recipients, bps);
+    }
+
+    /// @notice Get the configured platform fee split
+    /// @return splits Recipients and their shares in basis points
+    function getFeeSplits() external view returns (FeeSplit[] memory) {
+        return feeSplits;
+    }
+
+    /// @dev Add the platform fee to the context, split per `feeSplits`; rounding dust goes to the last recipient
+    function _addPlatformFee(
+        PaymentContext.Context memory context,
+        uint256 amount
+    ) internal view returns (PaymentContext.Context memory) {
+        uint256 count = feeSplits.length;
+        if (count == 0) return PaymentContext.addFee(context, feeRecipient, amount);
+
+        uint256 distributed;
+        for (uint256 i = 0; i < count; i++) {
+            FeeSplit memory split = feeSplits[i];
+            uint256 share = i == count - 1 ? amount - distributed : (amount * split.bps) / 10000;
+            distributed += share;
+            if (share > 0) context = PaymentContext.addFee(context, split.recipient, share);
+        }
+        return context;
+    }
+
+    /// @dev Payment metadata carries an ABI-encoded referrer address, if any; anything else is ignored
+    function _referrer(bytes memory metadata) internal pure returns (address) {
+        if (metadata.length != 32) return address(0);
+        uint256 word = abi.decode(metadata, (uint256));
+        if (word >> 160 != 0) return address(0);
+        return address(uint160(word));
+    }
+
     function getName() external pure override returns (string memory) {
         return PROCESSOR_NAME;
     }
@@ -71,6 +234,7 @@ contract FeeProcessor is IPaymentProcessor, AccessControl {
 
         uint16 newFeePercent = (uint16(uint8(configData[0])) << 8) | uint16(uint8(configData[1]));
         require(newFeePercent <= 10000, 'FeeProcessor: fee percent too high');
+        emit FeePercentUpdated(feePercent, newFeePercent);
         feePercent = newFeePercent;
 
         if (configData.length == 22) {
@@ -3,15 +3,34 @@ pragma solidity ^0.8.28;
 
 import '../interfaces/IPaymentProcessor.sol';
 import '../PaymentContext.sol';
+import '../../errors/Errors.sol';
+import '../../external/AggregatorV3Interface.sol';
+import '../../lib/Native.sol';
 import '@openzeppelin/contracts/access/AccessControl.sol';
+import '@openzeppelin/contracts/utils/math/Math.sol';
 
 /// @title OracleProcessor
-/// @notice Sample payment processor stub that simply echoes the requested amount
+/// @notice Converts amounts between tokens using Chainlink USD price feeds
+/// @dev Prices quoted in USD use the Chainlink denomination address with 8 decimals
 contract OracleProcessor is IPaymentProcessor, AccessControl {
     bytes32 public constant PROCESSOR_ADMIN_ROLE = keccak256('PROCESSOR_ADMIN_ROLE');
 
+    /// @notice Chainlink `Denominations.USD`, usable as a listing or plan token for USD pricing
+    address public constant USD = 0x0000000000000000000000000000000000000348;
+    uint8 public constant USD_DECIMALS = 8;
+
     string private constant PROCESSOR_NAME = 'PriceOracle';
-    string private constant PROCESSOR_VERSION = '1.0.0';
+    string private constant PROCESSOR_VERSION = '1.1.0';
+
+    struct FeedConfig {
+        AggregatorV3Interface feed; // token/USD feed
+        uint8 tokenDecimals;
+        uint32 heartbeat; // maximum age of an answer in seconds
+    }
+
+    mapping(address => FeedConfig) public feeds;
+
+    event PriceFeedUpdated(address indexed token, address feed, uint8 tokenDecimals, uint32 heartbeat);
 
     constructor() {
         _grantRole(DEFAULT_ADMIN_ROLE, msg.sender);
@@ -42,7 +61,81 @@ contract OracleProcessor is IPaymentProcessor, AccessControl {
         // no-op for demo implementation
     }
 
-    function convertAmount(bytes32, address, address, uint256 amount) external pure returns (uint256) {
-        return amount;
+    /// @notice Register or remove the USD price feed of a token
+    /// @param token Token address (address(0) and the native sentinel both mean the native currency)
+    /// @param feed Token/USD feed, or address(0) to remove
+    /// @param tokenDecimals Decimals of the token amounts, at most 36
+    /// @param heartbeat Maximum accepted age of the feed answer in seconds
+    function setPriceFeed(
+        address token,
+        address feed,
+        uint8 tokenDecimals,
+        uint32 heartbeat
+    ) external onlyRole(PROCESSOR_ADMIN_ROLE) {
+        if (token == USD) revert InvalidAddress();
+        token = _normalize(token);
+        if (feed == address(0)) {
+            delete feeds[token];
+        } else {
+            if (heartbeat == 0) revert InvalidParameters();
+            if (tokenDecimals > 36) revert InvalidDecimals();
+            if (AggregatorV3Interface(feed).decimals() > 18) revert InvalidDecimals();
+            feeds[token] = FeedConfig({
+                feed: AggregatorV3Interface(feed),
+                tokenDecimals: tokenDecimals,
+                heartbeat: heartbeat
+            });
+        }
+        emit PriceFeedUpdated(token, feed, tokenDecimals, heartbeat);
+    }
+
+    /// @notice Convert an amount of `fromToken` into the equivalent amount of `toToken`
+    /// @dev Reverts on missing, non-positive or stale answers so callers never settle at a wrong rate
+    function convertAmount(
+        bytes32,
+        address fromToken,
+        address toToken,
+        uint256 amount
+    ) external view returns (uint256) {
+        fromToken = _normalize(fromToken);
+        toToken = _normalize(toToken);
+        if (fromToken == toToken || amount == 0) return amount;
+
+        (uint256 fromPrice, uint8 fromDecimals) = _usdPrice(fromToken);
+        (uint256 toPrice, uint8 toDecimals) = _usdPrice(toToken);
+
+        return Math.mulDiv(amount, fromPrice * 10 ** toDecimals, toPrice * 10 ** fromDecimals);
+    }
+
+    /// @notice Whether both tokens can be priced
+    function isPairSupported(bytes32, address fromToken, address toToken) external view returns (bool) {
+        fromToken = _normalize(fromToken);
+        toToken = _normalize(toToken);
+        if (fromToken == toToken) return true;
+        return _hasPrice(fromToken) && _hasPrice(toToken);
+    }
+
+    /// @dev USD price of one whole token scaled to 18 decimals, and the token's decimals
+    function _usdPrice(address token) internal view returns (uint256 price, uint8 tokenDecimals) {
+        if (token == USD) return (1e18, USD_DECIMALS);
+
+        FeedConfig memory cfg = feeds[token];
+        if (address(cfg.feed) == address(0)) revert PriceFeedNotFound();
+
+        (uint80 roundId, int256 answer, , uint256 updatedAt, uint80 answeredInRound) = cfg.feed.latestRoundData();
+        if (answer <= 0) revert InvalidPrice();
+        if (updatedAt == 0 || answeredInRound < roundId) revert StalePrice();
+        if (block.timestamp > updatedAt + cfg.heartbeat) revert StalePrice();
+
+        price = uint256(answer) * 10 ** (18 - cfg.feed.decimals());
+        tokenDecimals = cfg.tokenDecimals;
+    }
+
+    function _hasPrice(address token) internal view returns (bool) {
+        return token == USD || address(feeds[token].feed) != address(0);
+    }
+
+    function _normalize(address token) internal pure returns (address) {
+        return Native.isNative(token) ? Native.ETH_SENTINEL : token;
     }
 }
@@ -15,6 +15,12 @@ contract TokenFilterProcessor is IPaymentProcessor, AccessControl {
 
     mapping(bytes32 => mapping(address => bool)) private allowedTokens;
     mapping(bytes32 => address[]) private tokenLists;
+    mapping(bytes32 => mapping(address => uint256)) private tokenIndexes; // index + 1
+    mapping(bytes32 => mapping(address => uint256)) public minAmounts; // минимальная сумма платежа в токене
+
+    event AllowedTokensUpdated(bytes32 indexed moduleId, address[] tokens);
+    event TokenAllowanceUpdated(bytes32 indexed moduleId, address indexed token, bool allowed);
+    event MinAmountUpdated(bytes32 indexed moduleId, address indexed token, uint256 minAmount);
 
     constructor() {
         _grantRole(DEFAULT_ADMIN_ROLE, msg.sender);
@@ -35,6 +41,11 @@ contract TokenFilterProcessor is IPaymentProcessor, AccessControl {
             return (IPaymentProcessor.ProcessResult.FAILED, abi.encode(context));
         }
 
+        if (context.originalAmount < minAmounts[context.moduleId][context.token]) {
+            context = PaymentContext.setError(context, 'TokenFilter: amount below minimum');
+            return (IPaymentProcessor.ProcessResult.FAILED, abi.encode(context));
+        }
+
         updatedContextBytes = abi.encode(context);
         return (IPaymentProcessor.ProcessResult.SUCCESS, updatedContextBytes);
     }
@@ -54,6 +65,7 @@ contract TokenFilterProcessor is IPaymentProcessor, AccessControl {
         address[] storage prev = tokenLists[moduleId];
         for (uint256 i = 0; i < prev.length; i++) {
             allowedTokens[moduleId][prev[i]] = false;
+            tokenIndexes[moduleId][prev[i]] = 0;
         }
         // Очищаем предыдущий список токенов
         delete tokenLists[moduleId];
@@ -69,17 +81,68 @@ contract TokenFilterProcessor is IPaymentProcessor, AccessControl {
             }
             require(token != address(0), 'TokenFilter: zero token');
             if (!allowedTokens[moduleId][token]) {
-                allowedTokens[moduleId][token] = true;
-                tokenLists[moduleId].push(token);
+                _addToken(moduleId, token);
             }
         }
+
+        emit AllowedTokensUpdated(moduleId, tokenLists[moduleId]);
+    }
+
+    /// @notice Allow or disallow a single token without rewriting the module's whole list
+    /// @param moduleId Module identifier
+    /// @param token Token address
+    /// @param allowed Whether the token is accepted
+    function setTokenAllowed(bytes32 moduleId, address token, bool allowed) external onlyRole(PROCESSOR_ADMIN_ROLE) {
+        require(token != address(0), 'TokenFilter: zero token');
+        if (allowedTokens[moduleId][token] == allowed) return;
+
+        if (allowed) {
+            _addToken(moduleId, token);
+        } else {
+            _removeToken(moduleId, token);
+        }
+
+        emit TokenAllowanceUpdated(moduleId, token, allowed);
     }
 
     function isPairSupported(bytes32 moduleId, address fromToken, address toToken) external view returns (bool) {
         return allowedTokens[moduleId][fromToken] && allowedTokens[moduleId][toToken];
     }
 
+    /// @notice Set the smallest payment accepted in a token, rejecting dust payments
+    /// @param moduleId Module identifier
+    /// @param token Token address
+    /// @param minAmount Minimum payment amount in token units (0 disables)
+    function setMinAmount(bytes32 moduleId, address token, uint256 minAmount) external onlyRole(PROCESSOR_ADMIN_ROLE) {
+        minAmounts[moduleId][token] = minAmount;
+        emit MinAmountUpdated(moduleId, token, minAmount);
+    }
+
     function getAllowedTokens(bytes32 moduleId) external view returns (address[] memory tokens) {
         return tokenLists[moduleId];
     }
+
+    function isTokenAllowed(bytes32 moduleId, address token) external view returns (bool) {
+        return allowedTokens[moduleId][token];
+    }
+
+    function _addToken(bytes32 moduleId, address token) internal {
+        allowedTokens[moduleId][token] = true;
+        tokenLists[moduleId].push(token);
+        tokenIndexes[moduleId][token] = tokenLists[moduleId].length;
+    }
+
+    function _removeToken(bytes32 moduleId, address token) internal {
+        address[] storage list = tokenLists[moduleId];
+        uint256 index = tokenIndexes[moduleId][token];
+        uint256 lastIndex = list.length;
+        if (index != lastIndex) {
+            address last = list[lastIndex - 1];
+            list[index - 1] = last;
+            tokenIndexes[moduleId][last] = index;
+        }
+        list.pop();
+        tokenIndexes[moduleId][token] = 0;
+        allowedTokens[moduleId][token] = false;
+    }
 }
@@ -16,6 +16,7 @@ const PrizeType = {
 } as const;
 
 const FEATURE_OWNER_ROLE = ethers.id('FEATURE_OWNER_ROLE');
+const OPERATOR_ROLE = ethers.id('OPERATOR_ROLE');
 
 function toInstanceId(contestId: bigint): string {
   return ethers.zeroPadValue(ethers.toBeHex(contestId), 32);
@@ -193,4 +194,429 @@ describe('ContestFactory & ContestEscrow', function () {
     expect(await core.getService(instanceId, 'Validator')).to.equal(await validator.getAddress());
     expect(await core.getService(instanceId, 'NFTManager')).to.equal(await nftManager.getAddress());
   });
+
+  it('records sponsors and refunds them when the contest is cancelled', async function () {
+    const amount = ethers.parseEther('40');
+    const sponsored = ethers.parseEther('10');
+    const attribution = ethers.id('brand://sponsor-logo');
+    const prizes: ContestFactory.PrizeInfoStruct[] = [
+      {
+        prizeType: PrizeType.MONETARY,
+        token: await tokenA.getAddress(),
+        amount,
+        distribution: 0,
+        uri: '',
+      },
+    ];
+
+    await tokenA.connect(creator).approve(await factory.getAddress(), amount);
+    const { escrow } = await createContest(prizes);
+
+    await tokenA.mint(other.address, sponsored);
+    await tokenA.connect(other).approve(await escrow.getAddress(), sponsored);
+
+    // dust sponsorships below 1% of the slot are rejected so they cannot fill MAX_SPONSORSHIPS
+    expect(await escrow.minSponsorshipFor(0)).to.equal(ethers.parseEther('0.4'));
+    await expect(escrow.connect(other).sponsor(0, 1n, attribution)).to.be.revertedWithCustomError(
+      escrow,
+      'InvalidAmount',
+    );
+    await expect(escrow.connect(other).setMinSponsorship(0, sponsored)).to.be.revertedWithCustomError(
+      escrow,
+      'NotCreator',
+    );
+    await expect(escrow.connect(creator).setMinSponsorship(0, sponsored))
+      .to.emit(escrow, 'MinSponsorshipUpdated')
+      .withArgs(0n, sponsored);
+
+    await expect(escrow.connect(other).sponsor(0, sponsored, attribution))
+      .to.emit(escrow, 'ContestSponsored')
+      .withArgs(other.address, 0n, await tokenA.getAddress(), sponsored, attribution);
+
+    const [, , prizeAmount] = await escrow.prizes(0);
+    expect(prizeAmount).to.equal(amount + sponsored);
+    expect(await escrow.sponsorshipsLength()).to.equal(1n);
+
+    await expect(escrow.connect(other).claimSponsorRefund(0)).to.be.revertedWithCustomError(escrow, 'InvalidState');
+
+    const creatorBefore = await tokenA.balanceOf(creator.address);
+    await expect(escrow.connect(creator).cancel()).to.not.emit(escrow, 'SponsorRefunded');
+    expect((await tokenA.balanceOf(creator.address)) - creatorBefore).to.equal(amount);
+
+    // sponsors pull their refunds, so one that cannot receive does not block cancellation
+    await expect(escrow.connect(creator).claimSponsorRefund(0)).to.be.revertedWithCustomError(escrow, 'Unauthorized');
+    await expect(escrow.connect(other).claimSponsorRefund(0))
+      .to.emit(escrow, 'SponsorRefunded')
+      .withArgs(other.address, 0n, sponsored);
+    expect(await tokenA.balanceOf(other.address)).to.equal(sponsored);
+    await expect(escrow.connect(other).claimSponsorRefund(0)).to.be.revertedWithCustomError(
+      escrow,
+      'NothingToWithdraw',
+    );
+  });
+
+  it('cannot be cancelled once winners started being paid', async function () {
+    const amount = ethers.parseEther('5');
+    const prizes: ContestFactory.PrizeInfoStruct[] = [
+      {
+        prizeType: PrizeType.MONETARY,
+        token: await tokenA.getAddress(),
+        amount,
+        distribution: 0,
+        uri: '',
+      },
+    ];
+    // one more prize than a single finalize call processes
+    for (let i = 0; i < 20; i++) {
+      prizes.push({
+        prizeType: PrizeType.PROMO,
+        token: ethers.ZeroAddress,
+        amount: 0n,
+        distribution: 0,
+        uri: 'ipfs://badge',
+      });
+    }
+
+    await tokenA.connect(creator).approve(await factory.getAddress(), amount);
+    const { escrow } = await createContest(prizes);
+
+    await escrow.connect(creator).finalize(prizes.map(() => other.address), 0);
+    expect(await escrow.processedWinners()).to.equal(20n);
+    expect(await escrow.finalized()).to.equal(false);
+
+    await expect(escrow.connect(creator).cancel()).to.be.revertedWithCustomError(escrow, 'ContestAlreadyFinalized');
+    await expect(escrow.connect(creator).emergencyWithdraw()).to.be.revertedWithCustomError(
+      escrow,
+      'ContestAlreadyFinalized',
+    );
+  });
+
+  it('grows the prize from entry fees and routes the platform share to the treasury', async function () {
+    const amount = ethers.parseEther('20');
+    const fee = ethers.parseEther('2');
+    const prizes: ContestFactory.PrizeInfoStruct[] = [
+      {
+        prizeType: PrizeType.MONETARY,
+        token: await tokenA.getAddress(),
+        amount,
+        distribution: 0,
+        uri: '',
+      },
+    ];
+
+    await tokenA.connect(creator).approve(await factory.getAddress(), amount);
+    const { escrow } = await createContest(prizes);
+    await nftManager.connect(admin).transferOwnership(await escrow.getAddress());
+    await core.connect(admin).setService(await factory.MODULE_ID(), 'Treasury', admin.address);
+
+    await expect(escrow.connect(other).setEntryFee(fee, 1000)).to.be.revertedWithCustomError(escrow, 'NotCreator');
+    await expect(escrow.connect(creator).setEntryFee(fee, 1000))
+      .to.emit(escrow, 'EntryFeeUpdated')
+      .withArgs(fee, 1000);
+
+    await tokenA.mint(other.address, fee * 2n);
+    await tokenA.connect(other).approve(await escrow.getAddress(), fee * 2n);
+    await expect(escrow.connect(other).enter()).to.emit(escrow, 'ContestEntered').withArgs(other.address, 1n, fee);
+    await escrow.connect(other).enter();
+
+    expect(await escrow.entriesOf(other.address)).to.equal(2n);
+    const [, , prizeAmount] = await escrow.prizes(0);
+    expect(prizeAmount).to.equal(amount + fee * 2n);
+    await expect(escrow.connect(creator).setEntryFee(0, 0)).to.be.revertedWithCustomError(escrow, 'Forbidden');
+
+    const cut = (fee * 2n * 1000n) / 10_000n;
+    const treasuryBefore = await tokenA.balanceOf(admin.address);
+    await expect(escrow.connect(creator).finalize([creator.address], 0))
+      .to.emit(escrow, 'PlatformFeeCollected')
+      .withArgs(admin.address, cut);
+
+    expect((await tokenA.balanceOf(admin.address)) - treasuryBefore).to.equal(cut);
+    expect(await tokenA.balanceOf(await escrow.getAddress())).to.equal(0n);
+  });
+
+  it('lets contestants reclaim entry fees after cancellation', async function () {
+    const amount = ethers.parseEther('20');
+    const fee = ethers.parseEther('3');
+    const prizes: ContestFactory.PrizeInfoStruct[] = [
+      {
+        prizeType: PrizeType.MONETARY,
+        token: await tokenA.getAddress(),
+        amount,
+        distribution: 0,
+        uri: '',
+      },
+    ];
+
+    await tokenA.connect(creator).approve(await factory.getAddress(), amount);
+    const { escrow } = await createContest(prizes);
+    await escrow.connect(creator).setEntryFee(fee, 0);
+
+    await tokenA.mint(other.address, fee);
+    await tokenA.connect(other).approve(await escrow.getAddress(), fee);
+    await escrow.connect(other).enter();
+
+    await expect(escrow.connect(other).claimEntryRefund()).to.be.revertedWithCustomError(escrow, 'InvalidState');
+
+    const creatorBefore = await tokenA.balanceOf(creator.address);
+    await escrow.connect(creator).cancel();
+    expect((await tokenA.balanceOf(creator.address)) - creatorBefore).to.equal(amount);
+
+    await expect(escrow.connect(other).claimEntryRefund())
+      .to.emit(escrow, 'EntryFeeRefunded')
+      .withArgs(other.address, fee);
+    expect(await tokenA.balanceOf(other.address)).to.equal(fee);
+    await expect(escrow.connect(other).claimEntryRefund()).to.be.revertedWithCustomError(escrow, 'NothingToWithdraw');
+  });
+
+  it('lets an operator cancel an abandoned contest after the deadline', async function () {
+    const amount = ethers.parseEther('15');
+    const prizes: ContestFactory.PrizeInfoStruct[] = [
+      {
+        prizeType: PrizeType.MONETARY,
+        token: await tokenA.getAddress(),
+        amount,
+        distribution: 0,
+        uri: '',
+      },
+    ];
+
+    await tokenA.connect(creator).approve(await factory.getAddress(), amount);
+    const { escrow } = await createContest(prizes);
+    await expect(escrow.connect(other).cancel()).to.be.revertedWithCustomError(escrow, 'NotCreator');
+
+    await core.connect(admin).grantRole(OPERATOR_ROLE, other.address);
+    await expect(escrow.connect(other).cancel()).to.be.revertedWithCustomError(escrow, 'Forbidden');
+
+    // jump past the deadline in a snapshot so later suites keep a realistic clock
+    const snapshot = await ethers.provider.send('evm_snapshot', []);
+    try {
+      const deadline = await escrow.deadline();
+      await ethers.provider.send('evm_setNextBlockTimestamp', [Number(deadline) + 1]);
+
+      const creatorBefore = await tokenA.balanceOf(creator.address);
+      await expect(escrow.connect(other).cancel()).to.emit(escrow, 'ContestCancelled');
+      expect((await tokenA.balanceOf(creator.address)) - creatorBefore).to.equal(amount);
+    } finally {
+      await ethers.provider.send('evm_revert', [snapshot]);
+    }
+  });
+
+  it('pays the highest-scored contestant after committee judging', async function () {
+    const [, , , judgeA, judgeB, contestant] = await ethers.getSigners();
+    const amount = ethers.parseEther('30');
+    const prizes: ContestFactory.PrizeInfoStruct[] = [
+      {
+        prizeType: PrizeType.MONETARY,
+        token: await tokenA.getAddress(),
+        amount,
+        distribution: 0,
+        uri: '',
+      },
+    ];
+
+    await tokenA.connect(creator).approve(await factory.getAddress(), amount);
+    const { escrow } = await createContest(prizes);
+    await nftManager.connect(admin).transferOwnership(await escrow.getAddress());
+
+    const latest = await ethers.provider.getBlock('latest');
+    const votingEnd = BigInt(latest!.timestamp) + 86_400n;
+
+    // an uncapped judged contest could grow too large to tally
+    await expect(
+      escrow.connect(creator).setJudges([judgeA.address, judgeB.address], 2, votingEnd),
+    ).to.be.revertedWithCustomError(escrow, 'LimitExceeded');
+    await escrow.connect(creator).setEntryLimits(10, 0);
+
+    await expect(escrow.connect(creator).setJudges([judgeA.address, judgeB.address], 2, votingEnd))
+      .to.emit(escrow, 'JudgesConfigured')
+      .withArgs([judgeA.address, judgeB.address], 2, votingEnd);
+    await expect(escrow.connect(creator).setEntryLimits(0, 0)).to.be.revertedWithCustomError(
+      escrow,
+      'InvalidParameters',
+    );
+
+    await escrow.connect(other).enter();
+    await escrow.connect(contestant).enter();
+    expect(await escrow.contestantsLength()).to.equal(2n);
+
+    await expect(escrow.connect(creator).finalize([contestant.address], 0)).to.be.revertedWithCustomError(
+      escrow,
+      'Forbidden',
+    );
+    await expect(escrow.connect(other).voteEntry(other.address, 10)).to.be.revertedWithCustomError(
+      escrow,
+      'Unauthorized',
+    );
+
+    await escrow.connect(judgeA).voteEntry(other.address, 5);
+    await escrow.connect(judgeA).voteEntry(contestant.address, 7);
+    await expect(escrow.tally()).to.be.revertedWithCustomError(escrow, 'DeadlineNotReached');
+
+    await expect(escrow.connect(judgeB).voteEntry(other.address, 6))
+      .to.emit(escrow, 'EntryScored')
+      .withArgs(judgeB.address, other.address, 6);
+    expect(await escrow.totalScores(other.address)).to.equal(11n);
+
+    await expect(escrow.connect(judgeB).tally()).to.emit(escrow, 'ContestFinalized');
+    expect(await escrow.winners(0)).to.equal(other.address);
+    expect(await tokenA.balanceOf(other.address)).to.equal(amount);
+  });
+
+  it('enforces total and per-contestant entry limits', async function () {
+    const [, , , contestant] = await ethers.getSigners();
+    const prizes: ContestFactory.PrizeInfoStruct[] = [
+      {
+        prizeType: PrizeType.PROMO,
+        token: ethers.ZeroAddress,
+        amount: 0n,
+        distribution: 0,
+        uri: 'ipfs://badge',
+      },
+    ];
+    const { escrow } = await createContest(prizes);
+
+    await expect(escrow.connect(creator).setEntryLimits(2, 1))
+      .to.emit(escrow, 'EntryLimitsUpdated')
+      .withArgs(2, 1);
+
+    await escrow.connect(other).enter();
+    await expect(escrow.connect(other).enter()).to.be.revertedWithCustomError(escrow, 'LimitExceeded');
+    await escrow.connect(contestant).enter();
+    await expect(escrow.connect(admin).enter()).to.be.revertedWithCustomError(escrow, 'LimitExceeded');
+
+    await expect(escrow.connect(creator).setEntryLimits(1, 0)).to.be.revertedWithCustomError(
+      escrow,
+      'InvalidParameters',
+    );
+    expect(await escrow.entryCount()).to.equal(2n);
+  });
+
+  it('refunds part of the entry fee when a contestant withdraws', async function () {
+    const amount = ethers.parseEther('10');
+    const fee = ethers.parseEther('4');
+    const prizes: ContestFactory.PrizeInfoStruct[] = [
+      {
+        prizeType: PrizeType.MONETARY,
+        token: await tokenA.getAddress(),
+        amount,
+        distribution: 0,
+        uri: '',
+      },
+    ];
+
+    await tokenA.connect(creator).approve(await factory.getAddress(), amount);
+    const { escrow } = await createContest(prizes);
+    await escrow.connect(creator).setEntryFee(fee, 0);
+    await escrow.connect(creator).setWithdrawalRefund(7500);
+
+    await tokenA.mint(other.address, fee);
+    await tokenA.connect(other).approve(await escrow.getAddress(), fee);
+    await escrow.connect(other).enter();
+
+    const refund = (fee * 7500n) / 10_000n;
+    await expect(escrow.connect(other).withdrawEntry())
+      .to.emit(escrow, 'EntryWithdrawn')
+      .withArgs(other.address, 1n, refund, fee - refund);
+
+    expect(await tokenA.balanceOf(other.address)).to.equal(refund);
+    expect(await escrow.entryCount()).to.equal(0n);
+    expect(await escrow.contestantsLength()).to.equal(0n);
+    const [, , prizeAmount] = await escrow.prizes(0);
+    expect(prizeAmount).to.equal(amount + fee - refund);
+    await expect(escrow.connect(other).withdrawEntry()).to.be.revertedWithCustomError(escrow, 'NotFound');
+  });
+
+  it('returns entry stakes after finalization and slashes flagged entries', async function () {
+    const amount = ethers.parseEther('10');
+    const stake = ethers.parseEther('2');
+    const prizes: ContestFactory.PrizeInfoStruct[] = [
+      {
+        prizeType: PrizeType.MONETARY,
+        token: await tokenA.getAddress(),
+        amount,
+        distribution: 0,
+        uri: '',
+      },
+    ];
+
+    await tokenA.connect(creator).approve(await factory.getAddress(), amount);
+    const { escrow } = await createContest(prizes);
+    await nftManager.connect(admin).transferOwnership(await escrow.getAddress());
+    await expect(escrow.connect(creator).setEntryStake(stake)).to.emit(escrow, 'EntryStakeUpdated').withArgs(stake);
+
+    for (const contestant of [other, admin]) {
+      await tokenA.mint(contestant.address, stake);
+      await tokenA.connect(contestant).approve(await escrow.getAddress(), stake);
+      await escrow.connect(contestant).enter();
+    }
+    expect(await escrow.stakesHeld()).to.equal(stake * 2n);
+
+    await expect(escrow.connect(other).flagEntry(admin.address)).to.be.revertedWithCustomError(escrow, 'NotCreator');
+    await expect(escrow.connect(creator).flagEntry(other.address))
+      .to.emit(escrow, 'EntryFlagged')
+      .withArgs(other.address, stake, await escrow.getAddress());
+    expect(await escrow.contestantsLength()).to.equal(1n);
+    const [, , prizeAmount] = await escrow.prizes(0);
+    expect(prizeAmount).to.equal(amount + stake);
+    await expect(escrow.connect(other).enter()).to.be.revertedWithCustomError(escrow, 'Forbidden');
+
+    await expect(escrow.connect(admin).claimStake()).to.be.revertedWithCustomError(escrow, 'InvalidState');
+    await escrow.connect(creator).finalize([admin.address], 0);
+
+    await expect(escrow.connect(admin).claimStake()).to.emit(escrow, 'StakeReturned').withArgs(admin.address, stake);
+    expect(await tokenA.balanceOf(admin.address)).to.equal(amount + stake * 2n);
+    await expect(escrow.connect(other).claimStake()).to.be.revertedWithCustomError(escrow, 'NothingToWithdraw');
+    expect(await tokenA.balanceOf(await escrow.getAddress())).to.equal(0n);
+  });
+
+  it('vests monetary prizes linearly after a cliff', async function () {
+    const amount = ethers.parseEther('100');
+    const day = 24 * 60 * 60;
+    const prizes: ContestFactory.PrizeInfoStruct[] = [
+      {
+        prizeType: PrizeType.MONETARY,
+        token: await tokenA.getAddress(),
+        amount,
+        distribution: 0,
+        uri: '',
+      },
+    ];
+
+    await tokenA.connect(creator).approve(await factory.getAddress(), amount);
+    const { escrow } = await createContest(prizes);
+    await nftManager.connect(admin).transferOwnership(await escrow.getAddress());
+    await expect(escrow.connect(creator).setPrizeVesting(20 * day, 10 * day)).to.be.revertedWithCustomError(
+      escrow,
+      'InvalidParameters',
+    );
+    await expect(escrow.connect(creator).setPrizeVesting(10 * day, 100 * day))
+      .to.emit(escrow, 'PrizeVestingUpdated')
+      .withArgs(10 * day, 100 * day);
+
+    await expect(escrow.connect(creator).finalize([other.address], 0)).to.not.emit(escrow, 'MonetaryPrizePaid');
+    expect(await tokenA.balanceOf(other.address)).to.equal(0n);
+    expect(await escrow.vestedPrizeAmount(0)).to.equal(amount);
+
+    const snapshot = await ethers.provider.send('evm_snapshot', []);
+    try {
+      await expect(escrow.connect(other).claimVestedPrize(0)).to.be.revertedWithCustomError(
+        escrow,
+        'NothingToWithdraw',
+      );
+      await expect(escrow.connect(creator).claimVestedPrize(0)).to.be.revertedWithCustomError(escrow, 'Unauthorized');
+
+      const start = await escrow.vestingStart();
+      await ethers.provider.send('evm_setNextBlockTimestamp', [Number(start) + 25 * day]);
+      await expect(escrow.connect(other).claimVestedPrize(0))
+        .to.emit(escrow, 'VestedPrizeClaimed')
+        .withArgs(0, other.address, amount / 4n);
+
+      await ethers.provider.send('evm_setNextBlockTimestamp', [Number(start) + 120 * day]);
+      await escrow.connect(other).claimVestedPrize(0);
+      expect(await tokenA.balanceOf(other.address)).to.equal(amount);
+      expect(await escrow.claimableVestedPrize(0)).to.equal(0n);
+    } finally {
+      await ethers.provider.send('evm_revert', [snapshot]);
+    }
+  });
 });
@@ -1,11 +1,30 @@
 import { expect } from 'chai';
 import { ethers } from '../../hardhat-connection';
 import { anyValue } from '@nomicfoundation/hardhat-ethers-chai-matchers/withArgs';
-import type { CoreSystem, Marketplace, PaymentGateway, TestToken } from '../../typechain-types';
+import type {
+  CoreSystem,
+  FeeProcessor,
+  Marketplace,
+  MarketplaceAuctions,
+  MarketplaceCredit,
+  MarketplaceEscrow,
+  MarketplaceFactory,
+  MarketplaceInsurance,
+  MarketplacePromotions,
+  NFTManager,
+  PaymentGateway,
+  PaymentOrchestrator,
+  ProcessorRegistry,
+  SettlementHookMock,
+  TestToken,
+  TokenFilterProcessor,
+} from '../../typechain-types';
 import { deployGatewayStack, deployTestToken } from '../shared/paymentStack';
 
 const MODULE_ID = ethers.id('Marketplace');
 const FEATURE_OWNER_ROLE = ethers.id('FEATURE_OWNER_ROLE');
+const OPERATOR_ROLE = ethers.id('OPERATOR_ROLE');
+const ARBITER_ROLE = ethers.id('ARBITER_ROLE');
 
 interface ListingInput {
   chainIds: bigint[];
@@ -24,8 +43,15 @@ describe('Marketplace', function () {
   let buyer: Awaited<ReturnType<typeof ethers.getSigners>>[number];
   let other: Awaited<ReturnType<typeof ethers.getSigners>>[number];
   let core: CoreSystem;
+  let registry: ProcessorRegistry;
+  let orchestrator: PaymentOrchestrator;
   let gateway: PaymentGateway;
   let marketplace: Marketplace;
+  let escrow: MarketplaceEscrow;
+  let auctions: MarketplaceAuctions;
+  let promotions: MarketplacePromotions;
+  let credit: MarketplaceCredit;
+  let insurance: MarketplaceInsurance;
   let paymentToken: TestToken;
 
   beforeEach(async function () {
@@ -34,8 +60,7 @@ describe('Marketplace', function () {
     const Core = await ethers.getContractFactory('CoreSystem', admin);
     core = (await Core.deploy(admin.address)) as CoreSystem;
 
-    const stack = await deployGatewayStack(admin);
-    gateway = stack.gateway;
+    ({ registry, orchestrator, gateway } = await deployGatewayStack(admin));
 
     const MarketplaceFactory = await ethers.getContractFactory('Marketplace', admin);
     marketplace = (await MarketplaceFactory.deploy(
@@ -52,6 +77,29 @@ describe('Marketplace', function () {
     await core.connect(admin).setService(MODULE_ID, 'PaymentGateway', await gateway.getAddress());
     await gateway.connect(admin).setModuleAuthorization(MODULE_ID, await marketplace.getAddress(), true);
 
+    const coreAddress = await core.getAddress();
+    const marketplaceAddress = await marketplace.getAddress();
+    escrow = (await (
+      await ethers.getContractFactory('MarketplaceEscrow', admin)
+    ).deploy(coreAddress, marketplaceAddress, MODULE_ID)) as MarketplaceEscrow;
+    auctions = (await (
+      await ethers.getContractFactory('MarketplaceAuctions', admin)
+    ).deploy(coreAddress, marketplaceAddress, MODULE_ID)) as MarketplaceAuctions;
+    promotions = (await (
+      await ethers.getContractFactory('MarketplacePromotions', admin)
+    ).deploy(coreAddress, marketplaceAddress)) as MarketplacePromotions;
+    credit = (await (
+      await ethers.getContractFactory('MarketplaceCredit', admin)
+    ).deploy(marketplaceAddress)) as MarketplaceCredit;
+    insurance = (await (
+      await ethers.getContractFactory('MarketplaceInsurance', admin)
+    ).deploy(coreAddress, marketplaceAddress)) as MarketplaceInsurance;
+
+    await core.connect(admin).setService(MODULE_ID, 'MarketplaceEscrow', await escrow.getAddress());
+    await core.connect(admin).setService(MODULE_ID, 'MarketplaceAuctions', await auctions.getAddress());
+    await core.connect(admin).setService(MODULE_ID, 'MarketplacePromotions', await promotions.getAddress());
+    await core.connect(admin).setService(MODULE_ID, 'MarketplaceCredit', await credit.getAddress());
+
     await core.connect(admin).revokeRole(FEATURE_OWNER_ROLE, await admin.getAddress());
 
     paymentToken = await deployTestToken(admin, 'PayToken', 'PAY', 18, 0);
@@ -172,7 +220,9 @@ describe('Marketplace', function () {
 
     await expect(
       marketplace.connect(buyer).buy(listing, signature, listing.token, ethers.parseEther('4')),
-    ).to.be.revertedWithCustomError(marketplace, 'PriceExceedsMaximum');
+    )
+      .to.be.revertedWithCustomError(marketplace, 'PriceExceedsMaximum')
+      .withArgs(ethers.parseEther('4'), listing.price);
   });
 
   it('processes purchase with alternative payment token conversion', async function () {
@@ -286,4 +336,1832 @@ describe('Marketplace', function () {
 
     await expect(marketplace.connect(seller).revokeListing(listing, signature)).to.emit(marketplace, 'ListingRevoked');
   });
+
+  it('calls approved settlement hooks and skips them once revoked', async function () {
+    const chainIds = [BigInt((await ethers.provider.getNetwork()).chainId)];
+    const sellerAddress = await seller.getAddress();
+    const listingFor = (salt: bigint) =>
+      signListing({
+        chainIds,
+        token: ethers.ZeroAddress,
+        price: ethers.parseEther('1'),
+        sku: 'SKU-HOOKED',
+        seller: sellerAddress,
+        salt,
+        expiry: futureTimestamp(),
+      });
+    const Hook = await ethers.getContractFactory('SettlementHookMock', admin);
+    const hook = (await Hook.deploy()) as SettlementHookMock;
+    const hookAddress = await hook.getAddress();
+    const sku = ethers.id('SKU-HOOKED');
+
+    await expect(marketplace.connect(seller).setSettlementHook(sku, hookAddress)).to.be.revertedWithCustomError(
+      marketplace,
+      'InvalidModule',
+    );
+    await expect(marketplace.connect(admin).setHookApproval(hookAddress, true)).to.be.revertedWithCustomError(
+      marketplace,
+      'NotGovernor',
+    );
+
+    await core.connect(admin).grantRole(ethers.id('GOVERNOR_ROLE'), await admin.getAddress());
+    await expect(marketplace.connect(admin).setHookApproval(hookAddress, true))
+      .to.emit(marketplace, 'HookApprovalUpdated')
+      .withArgs(hookAddress, true);
+    await expect(marketplace.connect(seller).setSettlementHook(sku, hookAddress))
+      .to.emit(marketplace, 'SettlementHookUpdated')
+      .withArgs(sellerAddress, sku, hookAddress);
+
+    const first = await listingFor(1n);
+    const firstHash = await marketplace.hashListing(first.listing);
+    await expect(
+      marketplace
+        .connect(buyer)
+        .buy(first.listing, first.signature, ethers.ZeroAddress, 0, { value: first.listing.price }),
+    )
+      .to.emit(hook, 'Settled')
+      .withArgs(firstHash, await buyer.getAddress(), sellerAddress, first.listing.price);
+    expect(await hook.calls()).to.equal(1n);
+
+    // a failing hook reverts the whole sale
+    await hook.setShouldRevert(true);
+    const second = await listingFor(2n);
+    await expect(
+      marketplace
+        .connect(buyer)
+        .buy(second.listing, second.signature, ethers.ZeroAddress, 0, { value: second.listing.price }),
+    ).to.be.revertedWith('SettlementHookMock: rejected');
+
+    // once revoked, the hook is skipped and the SKU keeps selling
+    await expect(marketplace.connect(admin).setHookApproval(hookAddress, false))
+      .to.emit(marketplace, 'HookApprovalUpdated')
+      .withArgs(hookAddress, false);
+    await expect(
+      marketplace
+        .connect(buyer)
+        .buy(second.listing, second.signature, ethers.ZeroAddress, 0, { value: second.listing.price }),
+    ).to.emit(marketplace, 'MarketplaceSale');
+    expect(await hook.calls()).to.equal(1n);
+  });
+
+  it('vests each holdback tranche on its own schedule, including milestone releases', async function () {
+    const chainIds = [BigInt((await ethers.provider.getNetwork()).chainId)];
+    const sellerAddress = await seller.getAddress();
+    const token = await paymentToken.getAddress();
+    const listingFor = (sku: string, salt: bigint) =>
+      signListing({
+        chainIds,
+        token,
+        price: ethers.parseEther('100'),
+        sku,
+        seller: sellerAddress,
+        salt,
+        expiry: 0n,
+      });
+
+    await core.connect(admin).grantRole(OPERATOR_ROLE, await admin.getAddress());
+    await expect(marketplace.connect(admin).setSellerHoldback(sellerAddress, 2000, 1000))
+      .to.emit(marketplace, 'HoldbackConfigured')
+      .withArgs(sellerAddress, 2000, 1000);
+
+    const first = await listingFor('SKU-HELD', 1n);
+    const second = await listingFor('SKU-HELD', 2n);
+    const service = await listingFor('SKU-HELD-SERVICE', 3n);
+    await escrow.connect(seller).setMilestoneSchedule(service.listing.sku, [10000]);
+
+    const snapshot = await ethers.provider.send('evm_snapshot', []);
+    try {
+      const start = (await ethers.provider.getBlock('latest'))!.timestamp + 10;
+
+      await ethers.provider.send('evm_setNextBlockTimestamp', [start]);
+      await expect(marketplace.connect(buyer).buy(first.listing, first.signature, token, 0))
+        .to.emit(marketplace, 'HoldbackLocked')
+        .withArgs(sellerAddress, token, ethers.parseEther('20'), start + 1000);
+      expect(await paymentToken.balanceOf(sellerAddress)).to.equal(ethers.parseEther('80'));
+
+      // a second sale halfway through must not push back vesting of the first tranche
+      await ethers.provider.send('evm_setNextBlockTimestamp', [start + 500]);
+      await marketplace.connect(buyer).buy(second.listing, second.signature, token, 0);
+
+      await ethers.provider.send('evm_setNextBlockTimestamp', [start + 1000]);
+      await expect(marketplace.connect(seller).claimHoldback(token))
+        .to.emit(marketplace, 'HoldbackClaimed')
+        .withArgs(sellerAddress, token, ethers.parseEther('30'));
+      expect(await marketplace.lockedHoldback(sellerAddress, token)).to.equal(ethers.parseEther('10'));
+      expect(await marketplace.holdbackCursor(sellerAddress, token)).to.equal(1n);
+
+      await ethers.provider.send('evm_setNextBlockTimestamp', [start + 1500]);
+      await marketplace.connect(seller).claimHoldback(token);
+      expect(await paymentToken.balanceOf(sellerAddress)).to.equal(ethers.parseEther('200'));
+      expect(await marketplace.lockedHoldback(sellerAddress, token)).to.equal(0n);
+      await expect(marketplace.connect(seller).claimHoldback(token)).to.be.revertedWithCustomError(
+        marketplace,
+        'NothingToWithdraw',
+      );
+
+      await marketplace.connect(buyer).buy(service.listing, service.signature, token, 0);
+      await expect(escrow.connect(buyer).approveMilestone(1n)).to.emit(marketplace, 'HoldbackLocked');
+      expect(await paymentToken.balanceOf(sellerAddress)).to.equal(ethers.parseEther('280'));
+      expect(await marketplace.lockedHoldback(sellerAddress, token)).to.equal(ethers.parseEther('20'));
+    } finally {
+      await ethers.provider.send('evm_revert', [snapshot]);
+    }
+  });
+
+  it('claims at most fifty holdback tranches per call and resumes from the cursor', async function () {
+    const chainIds = [BigInt((await ethers.provider.getNetwork()).chainId)];
+    const sellerAddress = await seller.getAddress();
+    const token = await paymentToken.getAddress();
+    const maxTranches = await marketplace.MAX_HOLDBACK_TRANCHES_PER_CLAIM();
+    const sales = maxTranches + 2n;
+
+    await core.connect(admin).grantRole(OPERATOR_ROLE, await admin.getAddress());
+    await marketplace.connect(admin).setSellerHoldback(sellerAddress, 10000, 10);
+
+    for (let salt = 1n; salt <= sales; salt++) {
+      const { listing, signature } = await signListing({
+        chainIds,
+        token,
+        price: ethers.parseEther('1'),
+        sku: 'SKU-TRANCHES',
+        seller: sellerAddress,
+        salt,
+        expiry: 0n,
+      });
+      await marketplace.connect(buyer).buy(listing, signature, token, 0);
+    }
+    expect(await paymentToken.balanceOf(sellerAddress)).to.equal(0n);
+
+    const snapshot = await ethers.provider.send('evm_snapshot', []);
+    try {
+      await ethers.provider.send('evm_increaseTime', [100]);
+      await ethers.provider.send('evm_mine', []);
+      expect(await marketplace.claimableHoldback(sellerAddress, token)).to.equal(ethers.parseEther('50'));
+
+      await expect(marketplace.connect(seller).claimHoldback(token))
+        .to.emit(marketplace, 'HoldbackClaimed')
+        .withArgs(sellerAddress, token, ethers.parseEther('50'));
+      expect(await marketplace.holdbackCursor(sellerAddress, token)).to.equal(maxTranches);
+      expect(await marketplace.claimableHoldback(sellerAddress, token)).to.equal(ethers.parseEther('2'));
+
+      await expect(marketplace.connect(seller).claimHoldback(token))
+        .to.emit(marketplace, 'HoldbackClaimed')
+        .withArgs(sellerAddress, token, ethers.parseEther('2'));
+      expect(await marketplace.holdbackCursor(sellerAddress, token)).to.equal(sales);
+      expect(await paymentToken.balanceOf(sellerAddress)).to.equal(ethers.parseEther('52'));
+    } finally {
+      await ethers.provider.send('evm_revert', [snapshot]);
+    }
+  });
+
+  it('pays cashback from an active promotion until the budget is exhausted', async function () {
+    const budget = ethers.parseEther('15');
+    await core.connect(admin).grantRole(OPERATOR_ROLE, await admin.getAddress());
+    await paymentToken.mint(await admin.getAddress(), budget);
+    await paymentToken.connect(admin).approve(await promotions.getAddress(), budget);
+
+    await expect(
+      promotions.connect(admin).createPromotion(await paymentToken.getAddress(), 1000, 0, futureTimestamp(), budget),
+    ).to.emit(promotions, 'PromotionCreated');
+
+    const chainIds = [BigInt((await ethers.provider.getNetwork()).chainId)];
+    const first = await signListing({
+      chainIds,
+      token: await paymentToken.getAddress(),
+      price: ethers.parseEther('100'),
+      sku: 'SKU-PROMO-1',
+      seller: await seller.getAddress(),
+      salt: 1n,
+      expiry: futureTimestamp(),
+    });
+    const second = await signListing({
+      chainIds,
+      token: await paymentToken.getAddress(),
+      price: ethers.parseEther('100'),
+      sku: 'SKU-PROMO-2',
+      seller: await seller.getAddress(),
+      salt: 1n,
+      expiry: futureTimestamp(),
+    });
+
+    await expect(marketplace.connect(buyer).buy(first.listing, first.signature, first.listing.token, 0))
+      .to.emit(promotions, 'CashbackPaid')
+      .withArgs(1n, await buyer.getAddress(), await paymentToken.getAddress(), ethers.parseEther('10'));
+
+    await expect(marketplace.connect(buyer).buy(second.listing, second.signature, second.listing.token, 0))
+      .to.emit(promotions, 'CashbackPaid')
+      .withArgs(1n, await buyer.getAddress(), await paymentToken.getAddress(), ethers.parseEther('5'));
+
+    expect(await promotions.activePromotionByToken(await paymentToken.getAddress())).to.equal(0n);
+    await expect(promotions.connect(admin).closePromotion(1n)).to.be.revertedWithCustomError(
+      promotions,
+      'NothingToWithdraw',
+    );
+  });
+
+  it('caps cashback per buyer, skips self-purchases and holds cashback on milestone orders', async function () {
+    const budget = ethers.parseEther('100');
+    const token = await paymentToken.getAddress();
+    const sellerAddress = await seller.getAddress();
+    const otherAddress = await other.getAddress();
+    await core.connect(admin).grantRole(OPERATOR_ROLE, await admin.getAddress());
+    await paymentToken.mint(await admin.getAddress(), budget);
+    await paymentToken.connect(admin).approve(await promotions.getAddress(), budget);
+    await promotions.connect(admin).createPromotion(token, 1000, 0, futureTimestamp(), budget);
+    await expect(promotions.connect(admin).setBuyerCashbackCap(1n, ethers.parseEther('12')))
+      .to.emit(promotions, 'BuyerCashbackCapUpdated')
+      .withArgs(1n, ethers.parseEther('12'));
+
+    const chainId = BigInt((await ethers.provider.getNetwork()).chainId);
+    const listingFor = (sku: string, price: bigint) =>
+      signListing({
+        chainIds: [chainId],
+        token,
+        price,
+        sku,
+        seller: sellerAddress,
+        salt: 1n,
+        expiry: 0n,
+      });
+
+    const first = await listingFor('SKU-CAP-1', ethers.parseEther('100'));
+    await expect(marketplace.connect(buyer).buy(first.listing, first.signature, token, 0))
+      .to.emit(promotions, 'CashbackPaid')
+      .withArgs(1n, await buyer.getAddress(), token, ethers.parseEther('10'));
+    const second = await listingFor('SKU-CAP-2', ethers.parseEther('100'));
+    await expect(marketplace.connect(buyer).buy(second.listing, second.signature, token, 0))
+      .to.emit(promotions, 'CashbackPaid')
+      .withArgs(1n, await buyer.getAddress(), token, ethers.parseEther('2'));
+
+    // sellers buying their own SKUs get no cashback
+    const own = await listingFor('SKU-CAP-OWN', ethers.parseEther('10'));
+    await paymentToken.mint(sellerAddress, ethers.parseEther('10'));
+    await paymentToken.connect(seller).approve(await gateway.getAddress(), ethers.MaxUint256);
+    await expect(marketplace.connect(seller).buy(own.listing, own.signature, token, 0)).to.not.emit(
+      promotions,
+      'CashbackPaid',
+    );
+
+    // milestone orders hold the cashback until the order is released to the seller
+    const milestone = await listingFor('SKU-CAP-MILESTONE', ethers.parseEther('50'));
+    await escrow.connect(seller).setMilestoneSchedule(milestone.listing.sku, [10000]);
+    await paymentToken.mint(otherAddress, ethers.parseEther('50'));
+    await paymentToken.connect(other).approve(await gateway.getAddress(), ethers.MaxUint256);
+    await expect(marketplace.connect(other).buy(milestone.listing, milestone.signature, token, 0))
+      .to.emit(promotions, 'CashbackHeld')
+      .withArgs(1n, 1n, otherAddress, ethers.parseEther('5'))
+      .and.not.to.emit(promotions, 'CashbackPaid');
+    await expect(promotions.connect(other).claimCashback(1n)).to.be.revertedWithCustomError(
+      promotions,
+      'InvalidState',
+    );
+
+    await expect(escrow.connect(other).approveMilestone(1n))
+      .to.emit(promotions, 'CashbackReleased')
+      .withArgs(1n, 1n, otherAddress, ethers.parseEther('5'));
+    await expect(promotions.connect(other).claimCashback(1n))
+      .to.emit(promotions, 'CashbackPaid')
+      .withArgs(1n, otherAddress, token, ethers.parseEther('5'));
+    expect(await paymentToken.balanceOf(otherAddress)).to.equal(ethers.parseEther('5'));
+  });
+
+  it('records attributed top-ups of a live promotion budget', async function () {
+    const budget = ethers.parseEther('10');
+    const topUp = ethers.parseEther('4');
+    const memoHash = ethers.id('Q3 marketing budget');
+    await core.connect(admin).grantRole(OPERATOR_ROLE, await admin.getAddress());
+    await paymentToken.mint(await admin.getAddress(), budget);
+    await paymentToken.connect(admin).approve(await promotions.getAddress(), budget);
+    await promotions
+      .connect(admin)
+      .createPromotion(await paymentToken.getAddress(), 1000, 0, futureTimestamp(), budget);
+
+    await paymentToken.mint(await other.getAddress(), topUp);
+    await paymentToken.connect(other).approve(await promotions.getAddress(), topUp);
+    await expect(promotions.connect(other).fundPromotion(1n, topUp, memoHash))
+      .to.emit(promotions, 'PromotionFunded')
+      .withArgs(1n, await other.getAddress(), topUp, memoHash);
+
+    expect((await promotions.promotions(1n)).budget).to.equal(budget + topUp);
+    expect(await promotions.promotionDeposits(1n, await other.getAddress())).to.equal(topUp);
+    await expect(promotions.connect(other).fundPromotion(2n, topUp, memoHash)).to.be.revertedWithCustomError(
+      promotions,
+      'NotFound',
+    );
+
+    // spend 1 token of cashback, then split the unspent 13 pro-rata between both depositors
+    const { listing, signature } = await signListing({
+      chainIds: [BigInt((await ethers.provider.getNetwork()).chainId)],
+      token: await paymentToken.getAddress(),
+      price: ethers.parseEther('10'),
+      sku: 'SKU-PROMO-SPEND',
+      seller: await seller.getAddress(),
+      salt: 1n,
+      expiry: 0n,
+    });
+    await marketplace.connect(buyer).buy(listing, signature, listing.token, 0);
+    const remaining = budget + topUp - ethers.parseEther('1');
+
+    await expect(promotions.connect(other).claimPromotionRefund(1n)).to.be.revertedWithCustomError(
+      promotions,
+      'InvalidState',
+    );
+    await expect(promotions.connect(admin).closePromotion(1n))
+      .to.emit(promotions, 'PromotionClosed')
+      .withArgs(1n, remaining);
+    await expect(promotions.connect(other).fundPromotion(1n, topUp, memoHash)).to.be.revertedWithCustomError(
+      promotions,
+      'InvalidState',
+    );
+
+    const otherShare = (remaining * topUp) / (budget + topUp);
+    await expect(promotions.connect(other).claimPromotionRefund(1n))
+      .to.emit(promotions, 'PromotionRefundClaimed')
+      .withArgs(1n, await other.getAddress(), otherShare);
+    await promotions.connect(admin).claimPromotionRefund(1n);
+    expect(await paymentToken.balanceOf(await other.getAddress())).to.equal(otherShare);
+    expect(await paymentToken.balanceOf(await admin.getAddress())).to.equal((remaining * budget) / (budget + topUp));
+    await expect(promotions.connect(other).claimPromotionRefund(1n)).to.be.revertedWithCustomError(
+      promotions,
+      'NothingToWithdraw',
+    );
+  });
+
+  it('redeems seller coupons once per buyer within their cap', async function () {
+    const chainIds = [BigInt((await ethers.provider.getNetwork()).chainId)];
+    const couponId = ethers.id('LAUNCH20');
+    const sku = ethers.id('SKU-COUPON');
+    const [SCOPE_GLOBAL, SCOPE_SKU, SCOPE_LISTING] = [0, 1, 2];
+    const listingFor = async (salt: bigint) =>
+      signListing({
+        chainIds,
+        token: await paymentToken.getAddress(),
+        price: ethers.parseEther('50'),
+        sku: 'SKU-COUPON',
+        seller: await seller.getAddress(),
+        salt,
+        expiry: futureTimestamp(),
+      });
+
+    await expect(promotions.connect(seller).createCoupon(couponId, SCOPE_SKU, sku, 2000, 1, futureTimestamp()))
+      .to.emit(promotions, 'CouponCreated')
+      .withArgs(await seller.getAddress(), couponId, SCOPE_SKU, sku, 2000, 1, anyValue);
+    await expect(
+      promotions.connect(seller).createCoupon(couponId, SCOPE_SKU, sku, 1000, 1, futureTimestamp()),
+    ).to.be.revertedWithCustomError(promotions, 'InvalidArgument');
+    await expect(
+      promotions.connect(seller).createCoupon(ethers.id('BAD'), SCOPE_GLOBAL, sku, 1000, 1, futureTimestamp()),
+    ).to.be.revertedWithCustomError(promotions, 'InvalidArgument');
+    // Another seller can issue the same code in its own namespace without touching this one
+    await promotions.connect(other).createCoupon(couponId, SCOPE_GLOBAL, ethers.ZeroHash, 9000, 5, futureTimestamp());
+
+    const first = await listingFor(1n);
+    await expect(
+      marketplace.connect(buyer).buyWithCoupon(first.listing, first.signature, first.listing.token, 0, couponId),
+    )
+      .to.emit(promotions, 'CouponRedeemed')
+      .withArgs(await seller.getAddress(), couponId, await buyer.getAddress(), sku, ethers.parseEther('10'));
+    expect(await paymentToken.balanceOf(await seller.getAddress())).to.equal(ethers.parseEther('40'));
+
+    const second = await listingFor(2n);
+    await expect(
+      marketplace.connect(buyer).buyWithCoupon(second.listing, second.signature, second.listing.token, 0, couponId),
+    ).to.be.revertedWithCustomError(marketplace, 'LimitExceeded');
+
+    // Listing-scoped coupons only apply to the listing they were issued for
+    const listingCoupon = ethers.id('THIS-ONE');
+    const third = await listingFor(3n);
+    const thirdHash = await marketplace.hashListing(third.listing);
+    await promotions
+      .connect(seller)
+      .createCoupon(listingCoupon, SCOPE_LISTING, thirdHash, 5000, 10, futureTimestamp());
+    await expect(
+      marketplace
+        .connect(buyer)
+        .buyWithCoupon(second.listing, second.signature, second.listing.token, 0, listingCoupon),
+    ).to.be.revertedWithCustomError(marketplace, 'NotFound');
+    await expect(
+      marketplace.connect(buyer).buyWithCoupon(third.listing, third.signature, third.listing.token, 0, listingCoupon),
+    )
+      .to.emit(promotions, 'CouponRedeemed')
+      .withArgs(await seller.getAddress(), listingCoupon, await buyer.getAddress(), sku, ethers.parseEther('25'));
+
+    await expect(promotions.connect(buyer).disableCoupon(couponId)).to.be.revertedWithCustomError(
+      promotions,
+      'NotFound',
+    );
+    await expect(promotions.connect(seller).disableCoupon(couponId))
+      .to.emit(promotions, 'CouponDisabled')
+      .withArgs(await seller.getAddress(), couponId);
+    expect((await promotions.coupons(await other.getAddress(), couponId)).maxRedemptions).to.equal(5n);
+  });
+
+  it('records the order memo hash attached by the buyer', async function () {
+    const { listing, signature } = await signListing({
+      chainIds: [BigInt((await ethers.provider.getNetwork()).chainId)],
+      token: await paymentToken.getAddress(),
+      price: ethers.parseEther('10'),
+      sku: 'SKU-MEMO',
+      seller: await seller.getAddress(),
+      salt: 1n,
+      expiry: futureTimestamp(),
+    });
+    const listingHash = await marketplace.hashListing(listing);
+    const memoHash = ethers.id('ship-to:encrypted-address');
+
+    await expect(
+      marketplace.connect(buyer).buyWithMemo(listing, signature, listing.token, 0, ethers.ZeroHash),
+    ).to.be.revertedWithCustomError(marketplace, 'InvalidArgument');
+    await expect(marketplace.connect(buyer).buyWithMemo(listing, signature, listing.token, 0, memoHash))
+      .to.emit(marketplace, 'OrderMemoAttached')
+      .withArgs(listingHash, await buyer.getAddress(), await seller.getAddress(), memoHash);
+
+    expect(await marketplace.orderMemos(listingHash, await buyer.getAddress())).to.equal(memoHash);
+  });
+
+  it('applies a cross-sell discount after the trigger SKU was purchased', async function () {
+    const chainIds = [BigInt((await ethers.provider.getNetwork()).chainId)];
+    const price = ethers.parseEther('50');
+    const trigger = await signListing({
+      chainIds,
+      token: await paymentToken.getAddress(),
+      price,
+      sku: 'SKU-TRIGGER',
+      seller: await seller.getAddress(),
+      salt: 1n,
+      expiry: futureTimestamp(),
+    });
+    const target = await signListing({
+      chainIds,
+      token: await paymentToken.getAddress(),
+      price,
+      sku: 'SKU-TARGET',
+      seller: await seller.getAddress(),
+      salt: 1n,
+      expiry: futureTimestamp(),
+    });
+
+    await promotions.connect(seller).setCrossSellRule(trigger.listing.sku, target.listing.sku, 2000, 3600);
+
+    await marketplace.connect(buyer).buy(trigger.listing, trigger.signature, trigger.listing.token, 0);
+    expect(
+      await promotions.getCrossSellDiscount(await buyer.getAddress(), await seller.getAddress(), target.listing.sku),
+    ).to.equal(2000n);
+
+    const discounted = price - (price * 2000n) / 10000n;
+    await expect(marketplace.connect(buyer).buy(target.listing, target.signature, target.listing.token, 0))
+      .to.emit(promotions, 'CrossSellDiscountApplied')
+      .withArgs(await buyer.getAddress(), await seller.getAddress(), target.listing.sku, trigger.listing.sku, 2000);
+
+    expect(await paymentToken.balanceOf(await seller.getAddress())).to.equal(price + discounted);
+    expect(
+      await promotions.getCrossSellDiscount(await buyer.getAddress(), await seller.getAddress(), target.listing.sku),
+    ).to.equal(0n);
+  });
+
+  it('counts a sale settled through an accepted offer as a cross-sell trigger', async function () {
+    const buyerAddress = await buyer.getAddress();
+    const sellerAddress = await seller.getAddress();
+    const triggerSku = ethers.id('SKU-OFFER-TRIGGER');
+    const targetSku = ethers.id('SKU-OFFER-TARGET');
+    await promotions.connect(seller).setCrossSellRule(triggerSku, targetSku, 1500, 3600);
+
+    await paymentToken.connect(buyer).approve(await auctions.getAddress(), ethers.MaxUint256);
+    await auctions
+      .connect(buyer)
+      .makeOffer(sellerAddress, triggerSku, await paymentToken.getAddress(), ethers.parseEther('10'), 0);
+    expect(await promotions.getCrossSellDiscount(buyerAddress, sellerAddress, targetSku)).to.equal(0n);
+
+    await auctions.connect(seller).acceptOffer(1n);
+    expect(await promotions.lastPurchaseAt(buyerAddress, sellerAddress, triggerSku)).to.be.gt(0n);
+    expect(await promotions.getCrossSellDiscount(buyerAddress, sellerAddress, targetSku)).to.equal(1500n);
+  });
+
+  it('purchases several listings atomically in one cart checkout', async function () {
+    const chainIds = [BigInt((await ethers.provider.getNetwork()).chainId)];
+    const first = await signListing({
+      chainIds,
+      token: await paymentToken.getAddress(),
+      price: ethers.parseEther('3'),
+      sku: 'SKU-CART-1',
+      seller: await seller.getAddress(),
+      salt: 1n,
+      expiry: futureTimestamp(),
+    });
+    const second = await signListing({
+      chainIds,
+      token: ethers.ZeroAddress,
+      price: ethers.parseEther('1'),
+      sku: 'SKU-CART-2',
+      seller: await seller.getAddress(),
+      salt: 1n,
+      expiry: futureTimestamp(),
+    });
+
+    const sellerNativeBefore = await ethers.provider.getBalance(await seller.getAddress());
+
+    await expect(
+      marketplace
+        .connect(buyer)
+        .buyBatch(
+          [first.listing, second.listing],
+          [first.signature, second.signature],
+          [first.listing.token, ethers.ZeroAddress],
+          [0, 0],
+          { value: ethers.parseEther('1.5') },
+        ),
+    ).to.emit(marketplace, 'CartCheckout');
+
+    expect(await paymentToken.balanceOf(await seller.getAddress())).to.equal(first.listing.price);
+    expect((await ethers.provider.getBalance(await seller.getAddress())) - sellerNativeBefore).to.equal(
+      second.listing.price,
+    );
+    expect(await ethers.provider.getBalance(await marketplace.getAddress())).to.equal(0n);
+  });
+
+  it('draws prepaid credit before charging the buyer wallet', async function () {
+    const prepaid = ethers.parseEther('40');
+    await paymentToken.mint(await other.getAddress(), prepaid);
+    await paymentToken.connect(other).approve(await credit.getAddress(), prepaid);
+    await credit
+      .connect(other)
+      .depositCredit(await buyer.getAddress(), await paymentToken.getAddress(), prepaid);
+
+    const { listing, signature } = await signListing({
+      chainIds: [BigInt((await ethers.provider.getNetwork()).chainId)],
+      token: await paymentToken.getAddress(),
+      price: ethers.parseEther('100'),
+      sku: 'SKU-CREDIT',
+      seller: await seller.getAddress(),
+      salt: 1n,
+      expiry: futureTimestamp(),
+    });
+
+    const buyerBefore = await paymentToken.balanceOf(await buyer.getAddress());
+    // only the part not covered by credit needs an allowance, and it goes to the marketplace
+    await paymentToken.connect(buyer).approve(await gateway.getAddress(), 0);
+    await paymentToken.connect(buyer).approve(await marketplace.getAddress(), listing.price - prepaid);
+
+    await expect(marketplace.connect(buyer).buyWithCredit(listing, signature, listing.token, 0))
+      .to.emit(credit, 'CreditSpent')
+      .withArgs(await buyer.getAddress(), await paymentToken.getAddress(), prepaid);
+
+    expect(buyerBefore - (await paymentToken.balanceOf(await buyer.getAddress()))).to.equal(listing.price - prepaid);
+    expect(await paymentToken.balanceOf(await seller.getAddress())).to.equal(listing.price);
+    expect(await credit.credits(await buyer.getAddress(), await paymentToken.getAddress())).to.equal(0n);
+    expect(await paymentToken.balanceOf(await marketplace.getAddress())).to.equal(0n);
+  });
+
+  it('needs no buyer allowance when credit covers the whole price', async function () {
+    const { listing, signature } = await signListing({
+      chainIds: [BigInt((await ethers.provider.getNetwork()).chainId)],
+      token: await paymentToken.getAddress(),
+      price: ethers.parseEther('30'),
+      sku: 'SKU-CREDIT-FULL',
+      seller: await seller.getAddress(),
+      salt: 1n,
+      expiry: futureTimestamp(),
+    });
+    await paymentToken.mint(await other.getAddress(), listing.price);
+    await paymentToken.connect(other).approve(await credit.getAddress(), listing.price);
+    await credit.connect(other).depositCredit(await buyer.getAddress(), listing.token, listing.price);
+    await paymentToken.connect(buyer).approve(await gateway.getAddress(), 0);
+
+    const buyerBefore = await paymentToken.balanceOf(await buyer.getAddress());
+    await marketplace.connect(buyer).buyWithCredit(listing, signature, listing.token, 0);
+
+    expect(await paymentToken.balanceOf(await buyer.getAddress())).to.equal(buyerBefore);
+    expect(await paymentToken.balanceOf(await seller.getAddress())).to.equal(listing.price);
+  });
+
+  it('escrows service purchases and releases funds per approved milestone', async function () {
+    const { listing, signature } = await signListing({
+      chainIds: [BigInt((await ethers.provider.getNetwork()).chainId)],
+      token: await paymentToken.getAddress(),
+      price: ethers.parseEther('100'),
+      sku: 'SKU-SERVICE',
+      seller: await seller.getAddress(),
+      salt: 1n,
+      expiry: futureTimestamp(),
+    });
+
+    await escrow.connect(seller).setMilestoneSchedule(listing.sku, [3000, 7000]);
+
+    await expect(marketplace.connect(buyer).buy(listing, signature, listing.token, 0)).to.emit(
+      escrow,
+      'MilestoneOrderOpened',
+    );
+    expect(await paymentToken.balanceOf(await seller.getAddress())).to.equal(0n);
+
+    await expect(escrow.connect(seller).approveMilestone(1n)).to.be.revertedWithCustomError(
+      escrow,
+      'Unauthorized',
+    );
+
+    await expect(escrow.connect(buyer).approveMilestone(1n))
+      .to.emit(escrow, 'MilestoneReleased')
+      .withArgs(1n, 0, ethers.parseEther('30'));
+    await escrow.connect(buyer).approveMilestone(1n);
+
+    expect(await paymentToken.balanceOf(await seller.getAddress())).to.equal(listing.price);
+    expect((await escrow.getMilestoneOrder(1n)).closed).to.equal(true);
+    expect((await marketplace.sellerStats(await seller.getAddress())).completedSales).to.equal(1n);
+    expect(await marketplace.sellerVolume(await seller.getAddress(), listing.token)).to.equal(listing.price);
+  });
+
+  it('lets a relayer submit a buyer-signed purchase intent once', async function () {
+    const { listing, signature } = await signListing({
+      chainIds: [BigInt((await ethers.provider.getNetwork()).chainId)],
+      token: await paymentToken.getAddress(),
+      price: ethers.parseEther('10'),
+      sku: 'SKU-SPONSORED',
+      seller: await seller.getAddress(),
+      salt: 1n,
+      expiry: futureTimestamp(),
+    });
+
+    const intent = {
+      buyer: await buyer.getAddress(),
+      listingHash: await marketplace.hashListing(listing),
+      paymentToken: ethers.ZeroAddress,
+      maxPaymentAmount: listing.price,
+      nonce: 7n,
+      deadline: futureTimestamp(),
+    };
+    const domain = {
+      chainId: (await ethers.provider.getNetwork()).chainId,
+      verifyingContract: await marketplace.getAddress(),
+    } as const;
+    const types = {
+      PurchaseIntent: [
+        { name: 'buyer', type: 'address' },
+        { name: 'listingHash', type: 'bytes32' },
+        { name: 'paymentToken', type: 'address' },
+        { name: 'maxPaymentAmount', type: 'uint256' },
+        { name: 'nonce', type: 'uint256' },
+        { name: 'deadline', type: 'uint64' },
+      ],
+    } as const;
+    const buyerSignature = await buyer.signTypedData(domain, types, intent);
+    const forged = await other.signTypedData(domain, types, intent);
+
+    await expect(
+      marketplace.connect(other).buySponsored(listing, signature, intent, forged),
+    ).to.be.revertedWithCustomError(marketplace, 'InvalidSignature');
+
+    const relayerBefore = await ethers.provider.getBalance(await other.getAddress());
+    await expect(marketplace.connect(other).buySponsored(listing, signature, intent, buyerSignature))
+      .to.emit(marketplace, 'SponsoredPurchase')
+      .withArgs(intent.listingHash, intent.buyer, await other.getAddress(), 7n)
+      .and.to.emit(marketplace, 'MarketplaceSale');
+
+    expect(await paymentToken.balanceOf(await seller.getAddress())).to.equal(listing.price);
+    expect(await paymentToken.balanceOf(await other.getAddress())).to.equal(0n);
+    expect(await ethers.provider.getBalance(await other.getAddress())).to.be.lt(relayerBefore);
+    expect(await marketplace.consumed(intent.listingHash, intent.buyer)).to.equal(true);
+    expect(await marketplace.usedNonces(intent.buyer, 7n)).to.equal(true);
+
+    await expect(
+      marketplace.connect(other).buySponsored(listing, signature, intent, buyerSignature),
+    ).to.be.revertedWithCustomError(marketplace, 'NonceAlreadyUsed');
+  });
+
+  it('lets signers invalidate unused intent nonces', async function () {
+    const buyerAddress = await buyer.getAddress();
+    await expect(marketplace.connect(buyer).invalidateNonce(3n))
+      .to.emit(marketplace, 'NonceUsed')
+      .withArgs(buyerAddress, 3n);
+    expect(await marketplace.usedNonces(buyerAddress, 3n)).to.equal(true);
+    expect(await marketplace.usedNonces(await other.getAddress(), 3n)).to.equal(false);
+
+    await expect(marketplace.connect(buyer).invalidateNonce(3n)).to.be.revertedWithCustomError(
+      marketplace,
+      'NonceAlreadyUsed',
+    );
+  });
+
+  it('indexes milestone orders per seller and buyer for pagination', async function () {
+    const chainId = BigInt((await ethers.provider.getNetwork()).chainId);
+    await escrow.connect(seller).setMilestoneSchedule(ethers.id('SKU-INDEXED'), [10000]);
+
+    for (const salt of [1n, 2n, 3n]) {
+      const { listing, signature } = await signListing({
+        chainIds: [chainId],
+        token: await paymentToken.getAddress(),
+        price: ethers.parseEther('10'),
+        sku: 'SKU-INDEXED',
+        seller: await seller.getAddress(),
+        salt,
+        expiry: futureTimestamp(),
+      });
+      await marketplace.connect(buyer).buy(listing, signature, listing.token, 0);
+    }
+
+    const sellerAddress = await seller.getAddress();
+    const buyerAddress = await buyer.getAddress();
+    expect(await escrow.getSellerMilestoneOrderCount(sellerAddress)).to.equal(3n);
+    expect(await escrow.getBuyerMilestoneOrderCount(buyerAddress)).to.equal(3n);
+    expect(await escrow.getSellerMilestoneOrders(sellerAddress, 0, 2)).to.deep.equal([1n, 2n]);
+    expect(await escrow.getBuyerMilestoneOrders(buyerAddress, 2, 5)).to.deep.equal([3n]);
+    expect(await escrow.getBuyerMilestoneOrders(buyerAddress, 3, 5)).to.deep.equal([]);
+  });
+
+  it('accepts buyer-chosen amounts above the minimum for open-priced SKUs', async function () {
+    const { listing, signature } = await signListing({
+      chainIds: [BigInt((await ethers.provider.getNetwork()).chainId)],
+      token: await paymentToken.getAddress(),
+      price: ethers.parseEther('10'),
+      sku: 'SKU-PWYW',
+      seller: await seller.getAddress(),
+      salt: 1n,
+      expiry: futureTimestamp(),
+    });
+    const chosen = ethers.parseEther('25');
+
+    await expect(
+      marketplace.connect(buyer).buyWithAmount(listing, signature, listing.token, chosen, 0),
+    ).to.be.revertedWithCustomError(marketplace, 'InvalidArgument');
+
+    await marketplace.connect(seller).setOpenPricing(listing.sku, true);
+
+    await expect(
+      marketplace.connect(buyer).buyWithAmount(listing, signature, listing.token, ethers.parseEther('5'), 0),
+    ).to.be.revertedWithCustomError(marketplace, 'InvalidPrice');
+
+    await expect(marketplace.connect(buyer).buyWithAmount(listing, signature, listing.token, chosen, 0)).to.emit(
+      marketplace,
+      'MarketplaceSale',
+    );
+    expect(await paymentToken.balanceOf(await seller.getAddress())).to.equal(chosen);
+  });
+
+  it('holds reserved listings for the reserving buyer and refunds the deposit on purchase', async function () {
+    const deposit = ethers.parseEther('0.01');
+    await core.connect(admin).grantRole(OPERATOR_ROLE, await admin.getAddress());
+
+    const { listing, signature } = await signListing({
+      chainIds: [BigInt((await ethers.provider.getNetwork()).chainId)],
+      token: await paymentToken.getAddress(),
+      price: ethers.parseEther('100'),
+      sku: 'SKU-RESERVE',
+      seller: await seller.getAddress(),
+      salt: 1n,
+      expiry: futureTimestamp(),
+    });
+    const listingHash = await marketplace.hashListing(listing);
+
+    // reservations stay disabled until a deposit is configured
+    await expect(marketplace.connect(buyer).reserveListing(listing, signature, 900)).to.be.revertedWithCustomError(
+      marketplace,
+      'InvalidState',
+    );
+    await marketplace.connect(admin).setReservationDeposit(deposit);
+
+    await expect(marketplace.connect(buyer).reserveListing(listing, signature, 900, { value: deposit }))
+      .to.emit(marketplace, 'ListingReserved')
+      .withArgs(listingHash, await buyer.getAddress(), anyValue, deposit);
+
+    await expect(marketplace.connect(other).buy(listing, signature, listing.token, 0)).to.be.revertedWithCustomError(
+      marketplace,
+      'Forbidden',
+    );
+    await expect(marketplace.releaseReservation(listingHash)).to.be.revertedWithCustomError(marketplace, 'NotDue');
+
+    await expect(marketplace.connect(buyer).buy(listing, signature, listing.token, 0))
+      .to.emit(marketplace, 'ReservationReleased')
+      .withArgs(listingHash, await buyer.getAddress(), await buyer.getAddress());
+    expect((await marketplace.reservations(listingHash)).buyer).to.equal(ethers.ZeroAddress);
+  });
+
+  it('forfeits expired reservation deposits and makes the same buyer wait before reserving again', async function () {
+    const deposit = ethers.parseEther('0.01');
+    await core.connect(admin).grantRole(OPERATOR_ROLE, await admin.getAddress());
+    await marketplace.connect(admin).setReservationDeposit(deposit);
+
+    const { listing, signature } = await signListing({
+      chainIds: [BigInt((await ethers.provider.getNetwork()).chainId)],
+      token: await paymentToken.getAddress(),
+      price: ethers.parseEther('100'),
+      sku: 'SKU-RESERVE-AGAIN',
+      seller: await seller.getAddress(),
+      salt: 1n,
+      expiry: 0n,
+    });
+    const listingHash = await marketplace.hashListing(listing);
+
+    const snapshot = await ethers.provider.send('evm_snapshot', []);
+    try {
+      await marketplace.connect(buyer).reserveListing(listing, signature, 900, { value: deposit });
+      await ethers.provider.send('evm_increaseTime', [900]);
+      await ethers.provider.send('evm_mine', []);
+
+      await expect(
+        marketplace.connect(buyer).reserveListing(listing, signature, 900, { value: deposit }),
+      ).to.be.revertedWithCustomError(marketplace, 'NotDue');
+      await expect(marketplace.releaseReservation(listingHash))
+        .to.emit(marketplace, 'ReservationReleased')
+        .withArgs(listingHash, await buyer.getAddress(), await seller.getAddress());
+
+      await expect(marketplace.connect(other).reserveListing(listing, signature, 900, { value: deposit })).to.emit(
+        marketplace,
+        'ListingReserved',
+      );
+
+      await ethers.provider.send('evm_increaseTime', [Number(await marketplace.RESERVATION_COOLDOWN())]);
+      await expect(marketplace.connect(buyer).reserveListing(listing, signature, 900, { value: deposit })).to.emit(
+        marketplace,
+        'ListingReserved',
+      );
+    } finally {
+      await ethers.provider.send('evm_revert', [snapshot]);
+    }
+  });
+
+  it('refunds disputed milestone escrow to buyer credit when requested', async function () {
+    const { listing, signature } = await signListing({
+      chainIds: [BigInt((await ethers.provider.getNetwork()).chainId)],
+      token: await paymentToken.getAddress(),
+      price: ethers.parseEther('100'),
+      sku: 'SKU-DISPUTE',
+      seller: await seller.getAddress(),
+      salt: 1n,
+      expiry: futureTimestamp(),
+    });
+
+    await escrow.connect(seller).setMilestoneSchedule(listing.sku, [5000, 5000]);
+    await marketplace.connect(buyer).buy(listing, signature, listing.token, 0);
+    await escrow.connect(buyer).approveMilestone(1n);
+
+    await expect(escrow.connect(admin).resolveMilestoneOrder(1n, false, true)).to.be.revertedWithCustomError(
+      escrow,
+      'NotArbiter',
+    );
+    await core.connect(admin).grantRole(ARBITER_ROLE, await admin.getAddress());
+    await expect(escrow.connect(admin).resolveMilestoneOrder(1n, false, true))
+      .to.emit(credit, 'CreditDeposited')
+      .withArgs(
+        await escrow.getAddress(),
+        await buyer.getAddress(),
+        await paymentToken.getAddress(),
+        ethers.parseEther('50'),
+      );
+
+    expect(await credit.credits(await buyer.getAddress(), await paymentToken.getAddress())).to.equal(
+      ethers.parseEther('50'),
+    );
+  });
+
+  it('enforces per-buyer purchase limits across a wallet cluster', async function () {
+    const chainId = BigInt((await ethers.provider.getNetwork()).chainId);
+    const drops = [];
+    for (const salt of [1n, 2n]) {
+      drops.push(
+        await signListing({
+          chainIds: [chainId],
+          token: await paymentToken.getAddress(),
+          price: ethers.parseEther('10'),
+          sku: 'SKU-DROP',
+          seller: await seller.getAddress(),
+          salt,
+          expiry: futureTimestamp(),
+        }),
+      );
+    }
+
+    await marketplace.connect(seller).setPurchaseLimit(drops[0].listing.sku, 1, true);
+    await core.connect(admin).grantRole(OPERATOR_ROLE, await admin.getAddress());
+    await marketplace
+      .connect(admin)
+      .setWalletCluster([await buyer.getAddress(), await other.getAddress()], ethers.id('cluster-1'));
+
+    await paymentToken.mint(await other.getAddress(), ethers.parseEther('10'));
+    await paymentToken.connect(other).approve(await gateway.getAddress(), ethers.MaxUint256);
+
+    await marketplace.connect(buyer).buy(drops[0].listing, drops[0].signature, drops[0].listing.token, 0);
+    await expect(
+      marketplace.connect(other).buy(drops[1].listing, drops[1].signature, drops[1].listing.token, 0),
+    ).to.be.revertedWithCustomError(marketplace, 'LimitExceeded');
+  });
+
+  it('records a verifiable receipt for each sale', async function () {
+    const { listing, signature } = await signListing({
+      chainIds: [BigInt((await ethers.provider.getNetwork()).chainId)],
+      token: await paymentToken.getAddress(),
+      price: ethers.parseEther('10'),
+      sku: 'SKU-LICENSE',
+      seller: await seller.getAddress(),
+      salt: 1n,
+      expiry: futureTimestamp(),
+    });
+    const listingHash = await marketplace.hashListing(listing);
+    const receipt = ethers.solidityPackedKeccak256(
+      ['bytes32', 'bytes32', 'address'],
+      [listingHash, listing.sku, await buyer.getAddress()],
+    );
+
+    await expect(marketplace.connect(buyer).buy(listing, signature, listing.token, 0))
+      .to.emit(marketplace, 'SaleReceipt')
+      .withArgs(listingHash, await buyer.getAddress(), receipt);
+    expect(await marketplace.saleReceipts(listingHash)).to.equal(receipt);
+  });
+
+  it('runs an English auction with refunds for outbid bidders', async function () {
+    const sku = ethers.id('SKU-AUCTION');
+    const token = await paymentToken.getAddress();
+
+    await expect(auctions.connect(seller).createAuction(sku, token, ethers.parseEther('10'), 500, 3600)).to.emit(
+      auctions,
+      'AuctionCreated',
+    );
+
+    await paymentToken.mint(await other.getAddress(), ethers.parseEther('100'));
+    await paymentToken.connect(buyer).approve(await auctions.getAddress(), ethers.MaxUint256);
+    await paymentToken.connect(other).approve(await auctions.getAddress(), ethers.MaxUint256);
+
+    await expect(auctions.connect(buyer).placeBid(1n, ethers.parseEther('9'))).to.be.revertedWithCustomError(
+      auctions,
+      'InvalidPrice',
+    );
+    await auctions.connect(buyer).placeBid(1n, ethers.parseEther('10'));
+    await expect(auctions.connect(other).placeBid(1n, ethers.parseEther('10.4'))).to.be.revertedWithCustomError(
+      auctions,
+      'InvalidPrice',
+    );
+
+    const buyerBefore = await paymentToken.balanceOf(await buyer.getAddress());
+    await auctions.connect(other).placeBid(1n, ethers.parseEther('10.5'));
+    expect((await paymentToken.balanceOf(await buyer.getAddress())) - buyerBefore).to.equal(ethers.parseEther('10'));
+
+    await expect(auctions.settleAuction(1n)).to.be.revertedWithCustomError(auctions, 'NotDue');
+
+    const snapshot = await ethers.provider.send('evm_snapshot', []);
+    try {
+      await ethers.provider.send('evm_increaseTime', [3600]);
+      await ethers.provider.send('evm_mine', []);
+
+      // a paused module must not lock escrowed bids
+      await core.connect(admin).grantRole(OPERATOR_ROLE, await admin.getAddress());
+      await core.connect(admin).setModulePaused(MODULE_ID, true);
+
+      const auctionHash = ethers.solidityPackedKeccak256(['string', 'uint256'], ['auction', 1n]);
+      await expect(auctions.settleAuction(1n))
+        .to.emit(auctions, 'AuctionSettled')
+        .withArgs(1n, await other.getAddress(), ethers.parseEther('10.5'), ethers.parseEther('10.5'))
+        .and.to.emit(marketplace, 'SaleReceipt')
+        .withArgs(auctionHash, await other.getAddress(), anyValue);
+      expect(await paymentToken.balanceOf(await seller.getAddress())).to.equal(ethers.parseEther('10.5'));
+      expect(await marketplace.saleReceipts(auctionHash)).to.equal(
+        ethers.solidityPackedKeccak256(['bytes32', 'bytes32', 'address'], [auctionHash, sku, await other.getAddress()]),
+      );
+    } finally {
+      await ethers.provider.send('evm_revert', [snapshot]);
+    }
+  });
+
+  it('lets the winning bidder reclaim a bid the marketplace keeps rejecting', async function () {
+    const sku = ethers.id('SKU-AUCTION-STUCK');
+    const token = await paymentToken.getAddress();
+    const Hook = await ethers.getContractFactory('SettlementHookMock', admin);
+    const hook = (await Hook.deploy()) as SettlementHookMock;
+    await core.connect(admin).grantRole(ethers.id('GOVERNOR_ROLE'), await admin.getAddress());
+    await marketplace.connect(admin).setHookApproval(await hook.getAddress(), true);
+    await marketplace.connect(seller).setSettlementHook(sku, await hook.getAddress());
+    await hook.setShouldRevert(true);
+
+    await auctions.connect(seller).createAuction(sku, token, ethers.parseEther('10'), 500, 3600);
+    await paymentToken.connect(buyer).approve(await auctions.getAddress(), ethers.MaxUint256);
+    const buyerBefore = await paymentToken.balanceOf(await buyer.getAddress());
+    await auctions.connect(buyer).placeBid(1n, ethers.parseEther('10'));
+
+    const snapshot = await ethers.provider.send('evm_snapshot', []);
+    try {
+      await ethers.provider.send('evm_increaseTime', [3600]);
+      await ethers.provider.send('evm_mine', []);
+
+      await expect(auctions.settleAuction(1n)).to.be.revertedWith('SettlementHookMock: rejected');
+      await expect(auctions.connect(buyer).reclaimBid(1n)).to.be.revertedWithCustomError(auctions, 'NotDue');
+
+      const grace = Number(await auctions.SETTLEMENT_GRACE_PERIOD());
+      await ethers.provider.send('evm_increaseTime', [grace]);
+      await ethers.provider.send('evm_mine', []);
+
+      await expect(auctions.connect(other).reclaimBid(1n)).to.be.revertedWithCustomError(auctions, 'Unauthorized');
+      await expect(auctions.connect(buyer).reclaimBid(1n))
+        .to.emit(auctions, 'AuctionBidReclaimed')
+        .withArgs(1n, await buyer.getAddress(), ethers.parseEther('10'));
+      expect(await paymentToken.balanceOf(await buyer.getAddress())).to.equal(buyerBefore);
+      await expect(auctions.settleAuction(1n)).to.be.revertedWithCustomError(auctions, 'InvalidState');
+    } finally {
+      await ethers.provider.send('evm_revert', [snapshot]);
+    }
+  });
+
+  it('only auctions tokens accepted by the token filter and one escrowed NFT at a time', async function () {
+    const TokenFilter = await ethers.getContractFactory('TokenFilterProcessor', admin);
+    const tokenFilter = (await TokenFilter.deploy()) as TokenFilterProcessor;
+    await registry.connect(admin).registerProcessor(await tokenFilter.getAddress(), 0);
+    const token = await paymentToken.getAddress();
+
+    const sku = ethers.id('SKU-AUCTION-NFT');
+    await expect(
+      auctions.connect(seller).createAuction(sku, token, ethers.parseEther('1'), 500, 3600),
+    ).to.be.revertedWithCustomError(auctions, 'UnsupportedPair');
+    await tokenFilter.connect(admin).setTokenAllowed(MODULE_ID, token, true);
+
+    const NFT = await ethers.getContractFactory('NFTManager', admin);
+    const nft = (await NFT.deploy('Items', 'ITM')) as NFTManager;
+    await nft.connect(admin).mint(await seller.getAddress(), 'ipfs://item', false);
+    await nft.connect(seller).approve(await marketplace.getAddress(), 1n);
+    await marketplace.connect(seller).escrowAsset(sku, await nft.getAddress(), 1n);
+
+    await auctions.connect(seller).createAuction(sku, token, ethers.parseEther('1'), 500, 3600);
+    expect(await auctions.openAuctions(await seller.getAddress(), sku)).to.equal(1n);
+    await expect(
+      auctions.connect(seller).createAuction(sku, token, ethers.parseEther('1'), 500, 3600),
+    ).to.be.revertedWithCustomError(auctions, 'InvalidState');
+
+    await auctions.connect(seller).cancelAuction(1n);
+    expect(await auctions.openAuctions(await seller.getAddress(), sku)).to.equal(0n);
+    await expect(auctions.connect(seller).createAuction(sku, token, ethers.parseEther('1'), 500, 3600)).to.emit(
+      auctions,
+      'AuctionCreated',
+    );
+  });
+
+  it('charges the declining Dutch price computed at purchase time', async function () {
+    const latest = await ethers.provider.getBlock('latest');
+    if (!latest) throw new Error('block not found');
+    const start = BigInt(latest.timestamp);
+
+    const { listing, signature } = await signListing({
+      chainIds: [BigInt((await ethers.provider.getNetwork()).chainId)],
+      token: await paymentToken.getAddress(),
+      price: ethers.parseEther('100'),
+      sku: 'SKU-DUTCH',
+      seller: await seller.getAddress(),
+      salt: 1n,
+      expiry: 0n,
+    });
+
+    await marketplace.connect(seller).setDutchSchedule(listing.sku, ethers.parseEther('20'), start, start + 1000n);
+
+    await ethers.provider.send('evm_setNextBlockTimestamp', [Number(start + 500n)]);
+    await marketplace.connect(buyer).buy(listing, signature, listing.token, 0);
+
+    expect(await paymentToken.balanceOf(await seller.getAddress())).to.equal(ethers.parseEther('60'));
+  });
+
+  it('escrows buyer offers and settles them when the seller accepts', async function () {
+    const sku = ethers.id('SKU-OFFER');
+    const amount = ethers.parseEther('30');
+    await paymentToken.connect(buyer).approve(await auctions.getAddress(), ethers.MaxUint256);
+
+    await expect(
+      auctions.connect(buyer).makeOffer(await seller.getAddress(), sku, await paymentToken.getAddress(), amount, 0),
+    ).to.emit(auctions, 'OfferMade');
+    expect(await paymentToken.balanceOf(await auctions.getAddress())).to.equal(amount);
+
+    await expect(auctions.connect(other).acceptOffer(1n)).to.be.revertedWithCustomError(auctions, 'NotSeller');
+
+    await core.connect(admin).grantRole(OPERATOR_ROLE, await admin.getAddress());
+    await core.connect(admin).setModulePaused(MODULE_ID, true);
+    await expect(auctions.connect(seller).acceptOffer(1n))
+      .to.emit(auctions, 'OfferAccepted')
+      .withArgs(1n, amount)
+      .and.to.emit(marketplace, 'MarketplaceSale')
+      .and.to.emit(marketplace, 'SaleReceipt');
+    expect(await paymentToken.balanceOf(await seller.getAddress())).to.equal(amount);
+
+    await expect(auctions.connect(buyer).cancelOffer(1n)).to.be.revertedWithCustomError(
+      auctions,
+      'InvalidState',
+    );
+  });
+
+  it('applies per-buyer purchase limits to offers and auction bids', async function () {
+    const sku = ethers.id('SKU-LIMITED-OFFER');
+    const token = await paymentToken.getAddress();
+    await paymentToken.connect(buyer).approve(await auctions.getAddress(), ethers.MaxUint256);
+    await marketplace.connect(seller).setPurchaseLimit(sku, 1, false);
+
+    await auctions.connect(buyer).makeOffer(await seller.getAddress(), sku, token, ethers.parseEther('5'), 0);
+    await auctions.connect(buyer).makeOffer(await seller.getAddress(), sku, token, ethers.parseEther('6'), 0);
+    await auctions.connect(seller).acceptOffer(1n);
+
+    await expect(auctions.connect(seller).acceptOffer(2n)).to.be.revertedWithCustomError(
+      auctions,
+      'LimitExceeded',
+    );
+    await expect(
+      auctions.connect(buyer).makeOffer(await seller.getAddress(), sku, token, ethers.parseEther('5'), 0),
+    ).to.be.revertedWithCustomError(auctions, 'LimitExceeded');
+
+    await auctions.connect(seller).createAuction(sku, token, ethers.parseEther('1'), 500, 3600);
+    await expect(auctions.connect(buyer).placeBid(1n, ethers.parseEther('1'))).to.be.revertedWithCustomError(
+      auctions,
+      'LimitExceeded',
+    );
+
+    // the over-limit offer stays refundable
+    await expect(auctions.connect(buyer).cancelOffer(2n)).to.emit(auctions, 'OfferCancelled');
+  });
+
+  it('splits seller payouts with the configured royalty recipient without losing dust', async function () {
+    const price = 1_000_003n;
+    const { listing, signature } = await signListing({
+      chainIds: [BigInt((await ethers.provider.getNetwork()).chainId)],
+      token: await paymentToken.getAddress(),
+      price,
+      sku: 'SKU-ROYALTY',
+      seller: await seller.getAddress(),
+      salt: 1n,
+      expiry: 0n,
+    });
+
+    await expect(marketplace.connect(seller).setRoyalty(await other.getAddress(), 333))
+      .to.emit(marketplace, 'RoyaltyConfigured')
+      .withArgs(await seller.getAddress(), await other.getAddress(), 333);
+
+    await marketplace.connect(buyer).buy(listing, signature, listing.token, 0);
+
+    const royalty = (price * 333n) / 10000n;
+    expect(await paymentToken.balanceOf(await other.getAddress())).to.equal(royalty);
+    expect(await paymentToken.balanceOf(await seller.getAddress())).to.equal(price - royalty);
+    expect(await paymentToken.balanceOf(await marketplace.getAddress())).to.equal(0n);
+  });
+
+  it('delivers an escrowed NFT to the buyer with the sale', async function () {
+    const NFT = await ethers.getContractFactory('NFTManager', admin);
+    const nft = (await NFT.deploy('Items', 'ITM')) as NFTManager;
+    await nft.connect(admin).mint(await seller.getAddress(), 'ipfs://item', false);
+
+    const chainId = BigInt((await ethers.provider.getNetwork()).chainId);
+    const first = await signListing({
+      chainIds: [chainId],
+      token: await paymentToken.getAddress(),
+      price: ethers.parseEther('10'),
+      sku: 'SKU-NFT',
+      seller: await seller.getAddress(),
+      salt: 1n,
+      expiry: 0n,
+    });
+    const second = await signListing({
+      chainIds: [chainId],
+      token: await paymentToken.getAddress(),
+      price: ethers.parseEther('10'),
+      sku: 'SKU-NFT',
+      seller: await seller.getAddress(),
+      salt: 2n,
+      expiry: 0n,
+    });
+
+    await nft.connect(seller).approve(await marketplace.getAddress(), 1n);
+    await expect(marketplace.connect(seller).escrowAsset(first.listing.sku, await nft.getAddress(), 1n))
+      .to.emit(marketplace, 'AssetEscrowed')
+      .withArgs(await seller.getAddress(), first.listing.sku, await nft.getAddress(), 1n);
+
+    await expect(marketplace.connect(buyer).buy(first.listing, first.signature, first.listing.token, 0))
+      .to.emit(marketplace, 'AssetReleased')
+      .withArgs(await seller.getAddress(), first.listing.sku, await buyer.getAddress());
+    expect(await nft.ownerOf(1n)).to.equal(await buyer.getAddress());
+
+    await expect(
+      marketplace.connect(buyer).buy(second.listing, second.signature, second.listing.token, 0),
+    ).to.be.revertedWithCustomError(marketplace, 'NotListed');
+  });
+
+  it('delivers escrowed NFTs on accepted offers and holds them for milestone orders until release', async function () {
+    const NFT = await ethers.getContractFactory('NFTManager', admin);
+    const nft = (await NFT.deploy('Items', 'ITM')) as NFTManager;
+    const nftAddress = await nft.getAddress();
+    const sellerAddress = await seller.getAddress();
+    const buyerAddress = await buyer.getAddress();
+    for (let i = 0; i < 3; i++) {
+      await nft.connect(admin).mint(sellerAddress, 'ipfs://item', false);
+    }
+    await nft.connect(seller).setApprovalForAll(await marketplace.getAddress(), true);
+
+    // offers deliver the NFT on acceptance
+    const offered = ethers.id('SKU-NFT-OFFER');
+    await marketplace.connect(seller).escrowAsset(offered, nftAddress, 1n);
+    await paymentToken.connect(buyer).approve(await auctions.getAddress(), ethers.MaxUint256);
+    await auctions.connect(buyer).makeOffer(sellerAddress, offered, await paymentToken.getAddress(), 100n, 0);
+    await expect(auctions.connect(seller).acceptOffer(1n))
+      .to.emit(marketplace, 'AssetReleased')
+      .withArgs(sellerAddress, offered, buyerAddress);
+    expect(await nft.ownerOf(1n)).to.equal(buyerAddress);
+
+    const chainId = BigInt((await ethers.provider.getNetwork()).chainId);
+    const serviceListing = (sku: string) =>
+      signListing({
+        chainIds: [chainId],
+        token: ethers.ZeroAddress,
+        price: ethers.parseEther('1'),
+        sku,
+        seller: sellerAddress,
+        salt: 1n,
+        expiry: 0n,
+      });
+
+    // a milestone order keeps the NFT until it is released to the seller
+    const released = await serviceListing('SKU-NFT-RELEASED');
+    await escrow.connect(seller).setMilestoneSchedule(released.listing.sku, [10000]);
+    await marketplace.connect(seller).escrowAsset(released.listing.sku, nftAddress, 2n);
+    await marketplace
+      .connect(buyer)
+      .buy(released.listing, released.signature, ethers.ZeroAddress, 0, { value: released.listing.price });
+    expect(await nft.ownerOf(2n)).to.equal(await marketplace.getAddress());
+    await expect(marketplace.connect(seller).withdrawAsset(released.listing.sku)).to.be.revertedWithCustomError(
+      marketplace,
+      'InvalidState',
+    );
+    await expect(escrow.connect(buyer).approveMilestone(1n))
+      .to.emit(marketplace, 'AssetReleased')
+      .withArgs(sellerAddress, released.listing.sku, buyerAddress);
+    expect(await nft.ownerOf(2n)).to.equal(buyerAddress);
+
+    // a fully refunded order leaves the NFT with the seller's escrow
+    const refunded = await serviceListing('SKU-NFT-REFUNDED');
+    await escrow.connect(seller).setMilestoneSchedule(refunded.listing.sku, [10000]);
+    await marketplace.connect(seller).escrowAsset(refunded.listing.sku, nftAddress, 3n);
+    await marketplace
+      .connect(buyer)
+      .buy(refunded.listing, refunded.signature, ethers.ZeroAddress, 0, { value: refunded.listing.price });
+    await core.connect(admin).grantRole(ARBITER_ROLE, await admin.getAddress());
+    await escrow.connect(admin).resolveMilestoneOrder(2n, false, false);
+    expect(await nft.ownerOf(3n)).to.equal(await marketplace.getAddress());
+    await marketplace.connect(seller).withdrawAsset(refunded.listing.sku);
+    expect(await nft.ownerOf(3n)).to.equal(sellerAddress);
+
+    // the withdrawn SKU stays sold out, so its live listing cannot be bought without the NFT
+    await escrow.connect(seller).setMilestoneSchedule(refunded.listing.sku, []);
+    const relisted = await signListing({
+      chainIds: [chainId],
+      token: ethers.ZeroAddress,
+      price: ethers.parseEther('1'),
+      sku: 'SKU-NFT-REFUNDED',
+      seller: sellerAddress,
+      salt: 2n,
+      expiry: 0n,
+    });
+    await expect(
+      marketplace
+        .connect(buyer)
+        .buy(relisted.listing, relisted.signature, ethers.ZeroAddress, 0, { value: relisted.listing.price }),
+    ).to.be.revertedWithCustomError(marketplace, 'NotListed');
+    await expect(marketplace.connect(seller).withdrawAsset(refunded.listing.sku)).to.be.revertedWithCustomError(
+      marketplace,
+      'InvalidState',
+    );
+  });
+
+  it('freezes disputed milestone orders until the arbiter splits the escrow', async function () {
+    const { listing, signature } = await signListing({
+      chainIds: [BigInt((await ethers.provider.getNetwork()).chainId)],
+      token: await paymentToken.getAddress(),
+      price: ethers.parseEther('100'),
+      sku: 'SKU-ARBITRATION',
+      seller: await seller.getAddress(),
+      salt: 1n,
+      expiry: 0n,
+    });
+
+    await escrow.connect(seller).setMilestoneSchedule(listing.sku, [10000]);
+    await marketplace.connect(buyer).buy(listing, signature, listing.token, 0);
+
+    await expect(escrow.connect(seller).openMilestoneDispute(1n))
+      .to.emit(escrow, 'MilestoneDisputeOpened')
+      .withArgs(1n, await seller.getAddress());
+    await expect(escrow.connect(buyer).approveMilestone(1n)).to.be.revertedWithCustomError(
+      escrow,
+      'InvalidState',
+    );
+    await expect(escrow.connect(buyer).submitMilestoneEvidence(1n, ethers.id('evidence')))
+      .to.emit(escrow, 'MilestoneEvidenceSubmitted')
+      .withArgs(1n, await buyer.getAddress(), ethers.id('evidence'));
+
+    await core.connect(admin).grantRole(OPERATOR_ROLE, await admin.getAddress());
+    await expect(escrow.connect(admin).splitMilestoneOrder(1n, 6000, false)).to.be.revertedWithCustomError(
+      escrow,
+      'NotArbiter',
+    );
+    await core.connect(admin).grantRole(ARBITER_ROLE, await admin.getAddress());
+    const buyerBefore = await paymentToken.balanceOf(await buyer.getAddress());
+    await expect(escrow.connect(admin).splitMilestoneOrder(1n, 6000, false))
+      .to.emit(escrow, 'MilestoneOrderSplit')
+      .withArgs(1n, ethers.parseEther('60'), ethers.parseEther('40'));
+
+    expect(await paymentToken.balanceOf(await seller.getAddress())).to.equal(ethers.parseEther('60'));
+    expect((await paymentToken.balanceOf(await buyer.getAddress())) - buyerBefore).to.equal(ethers.parseEther('40'));
+  });
+
+  it('settles a dispute the arbiter leaves unruled by its default outcome', async function () {
+    const sellerAddress = await seller.getAddress();
+    const buyerAddress = await buyer.getAddress();
+    const shipped = await signListing({
+      chainIds: [BigInt((await ethers.provider.getNetwork()).chainId)],
+      token: await paymentToken.getAddress(),
+      price: ethers.parseEther('100'),
+      sku: 'SKU-DISPUTE-SHIPPED',
+      seller: sellerAddress,
+      salt: 1n,
+      expiry: 0n,
+    });
+    const unshipped = await signListing({
+      chainIds: [BigInt((await ethers.provider.getNetwork()).chainId)],
+      token: await paymentToken.getAddress(),
+      price: ethers.parseEther('40'),
+      sku: 'SKU-DISPUTE-UNSHIPPED',
+      seller: sellerAddress,
+      salt: 1n,
+      expiry: 0n,
+    });
+
+    await escrow.connect(seller).setMilestoneSchedule(shipped.listing.sku, [10000]);
+    await escrow.connect(seller).setMilestoneSchedule(unshipped.listing.sku, [10000]);
+    await marketplace.connect(buyer).buy(shipped.listing, shipped.signature, shipped.listing.token, 0);
+    await marketplace.connect(buyer).buy(unshipped.listing, unshipped.signature, unshipped.listing.token, 0);
+    await escrow.connect(seller).markShipped(1n);
+    await escrow.connect(buyer).openMilestoneDispute(1n);
+    await escrow.connect(buyer).openMilestoneDispute(2n);
+
+    const { disputeDeadline } = await escrow.getMilestoneOrder(1n);
+    await expect(escrow.settleExpiredDispute(1n)).to.be.revertedWithCustomError(escrow, 'NotDue');
+
+    const snapshot = await ethers.provider.send('evm_snapshot', []);
+    try {
+      await ethers.provider.send('evm_setNextBlockTimestamp', [Number(disputeDeadline)]);
+      await ethers.provider.send('evm_mine', []);
+
+      // the arbiter's window is closed once the deadline passes
+      await core.connect(admin).grantRole(ARBITER_ROLE, await admin.getAddress());
+      await expect(escrow.connect(admin).splitMilestoneOrder(1n, 5000, false)).to.be.revertedWithCustomError(
+        escrow,
+        'Expired',
+      );
+      await expect(escrow.connect(buyer).submitMilestoneEvidence(1n, ethers.id('late'))).to.be.revertedWithCustomError(
+        escrow,
+        'Expired',
+      );
+
+      await expect(escrow.connect(admin).settleExpiredDispute(1n))
+        .to.emit(escrow, 'MilestoneDisputeExpired')
+        .withArgs(1n, true, ethers.parseEther('100'));
+      expect(await paymentToken.balanceOf(sellerAddress)).to.equal(ethers.parseEther('100'));
+
+      const buyerBefore = await paymentToken.balanceOf(buyerAddress);
+      await expect(escrow.connect(admin).settleExpiredDispute(2n))
+        .to.emit(escrow, 'MilestoneDisputeExpired')
+        .withArgs(2n, false, ethers.parseEther('40'));
+      expect((await paymentToken.balanceOf(buyerAddress)) - buyerBefore).to.equal(ethers.parseEther('40'));
+      expect((await marketplace.sellerStats(sellerAddress)).disputesLost).to.equal(1n);
+    } finally {
+      await ethers.provider.send('evm_revert', [snapshot]);
+    }
+  });
+
+  it('blocks purchases while the module is paused in core', async function () {
+    const { listing, signature } = await signListing({
+      chainIds: [BigInt((await ethers.provider.getNetwork()).chainId)],
+      token: await paymentToken.getAddress(),
+      price: ethers.parseEther('10'),
+      sku: 'SKU-PAUSE',
+      seller: await seller.getAddress(),
+      salt: 1n,
+      expiry: 0n,
+    });
+
+    await expect(core.connect(buyer).setModulePaused(MODULE_ID, true)).to.be.revertedWithCustomError(
+      core,
+      'NotOperator',
+    );
+
+    await core.connect(admin).grantRole(OPERATOR_ROLE, await admin.getAddress());
+    await expect(core.connect(admin).setModulePaused(MODULE_ID, true))
+      .to.emit(core, 'ModulePauseUpdated')
+      .withArgs(MODULE_ID, true, await admin.getAddress());
+
+    await expect(marketplace.connect(buyer).buy(listing, signature, listing.token, 0)).to.be.revertedWithCustomError(
+      marketplace,
+      'ModulePaused',
+    );
+
+    await core.connect(admin).setModulePaused(MODULE_ID, false);
+    await expect(marketplace.connect(buyer).buy(listing, signature, listing.token, 0)).to.emit(
+      marketplace,
+      'MarketplaceSale',
+    );
+  });
+
+  it('credits registered referrers with referred sales', async function () {
+    const { listing, signature } = await signListing({
+      chainIds: [BigInt((await ethers.provider.getNetwork()).chainId)],
+      token: await paymentToken.getAddress(),
+      price: ethers.parseEther('12'),
+      sku: 'SKU-REFERRAL',
+      seller: await seller.getAddress(),
+      salt: 1n,
+      expiry: futureTimestamp(),
+    });
+
+    await expect(
+      marketplace.connect(buyer).buyWithReferral(listing, signature, listing.token, 0, other.address),
+    ).to.be.revertedWithCustomError(marketplace, 'NotFound');
+
+    await expect(marketplace.connect(other).registerReferrer())
+      .to.emit(marketplace, 'ReferrerRegistered')
+      .withArgs(other.address);
+    await expect(
+      marketplace.connect(buyer).buyWithReferral(listing, signature, listing.token, 0, other.address),
+    ).to.be.revertedWithCustomError(marketplace, 'Unauthorized');
+
+    await expect(marketplace.connect(seller).setReferrerApproval(other.address, true))
+      .to.emit(marketplace, 'ReferrerApprovalUpdated')
+      .withArgs(await seller.getAddress(), other.address, true);
+
+    const listingHash = await marketplace.hashListing(listing);
+    await expect(marketplace.connect(buyer).buyWithReferral(listing, signature, listing.token, 0, other.address))
+      .to.emit(marketplace, 'ReferralRecorded')
+      .withArgs(other.address, await buyer.getAddress(), listingHash, listing.token, listing.price);
+
+    expect(await marketplace.referralSales(other.address)).to.equal(1n);
+    expect(await marketplace.referralVolume(other.address, listing.token)).to.equal(listing.price);
+  });
+
+  it('auto-releases shipped escrow orders after the acceptance window', async function () {
+    const { listing, signature } = await signListing({
+      chainIds: [BigInt((await ethers.provider.getNetwork()).chainId)],
+      token: await paymentToken.getAddress(),
+      price: ethers.parseEther('40'),
+      sku: 'SKU-SHIPPED',
+      seller: await seller.getAddress(),
+      salt: 1n,
+      expiry: futureTimestamp(),
+    });
+
+    await escrow.connect(seller).setMilestoneSchedule(listing.sku, [10000]);
+    await marketplace.connect(buyer).buy(listing, signature, listing.token, 0);
+
+    await expect(escrow.connect(other).autoReleaseMilestoneOrder(1n)).to.be.revertedWithCustomError(
+      escrow,
+      'InvalidState',
+    );
+    await expect(escrow.connect(buyer).markShipped(1n)).to.be.revertedWithCustomError(escrow, 'NotSeller');
+    await expect(escrow.connect(seller).markShipped(1n)).to.emit(escrow, 'MilestoneOrderShipped');
+    await expect(escrow.connect(other).autoReleaseMilestoneOrder(1n)).to.be.revertedWithCustomError(
+      escrow,
+      'NotDue',
+    );
+
+    const snapshot = await ethers.provider.send('evm_snapshot', []);
+    try {
+      // changing the global window does not move the deadline of an open order
+      const window = Number(await escrow.acceptanceWindow());
+      await core.connect(admin).grantRole(OPERATOR_ROLE, await admin.getAddress());
+      await escrow.connect(admin).setAcceptanceWindow(window * 2);
+      expect((await escrow.getMilestoneOrder(1n)).acceptanceWindow).to.equal(BigInt(window));
+
+      await ethers.provider.send('evm_increaseTime', [window]);
+      await expect(escrow.connect(other).autoReleaseMilestoneOrder(1n))
+        .to.emit(escrow, 'MilestoneOrderAutoReleased')
+        .withArgs(1n, await other.getAddress(), listing.price, 0n);
+      expect(await paymentToken.balanceOf(await seller.getAddress())).to.equal(listing.price);
+    } finally {
+      await ethers.provider.send('evm_revert', [snapshot]);
+    }
+  });
+
+  it('pays the crank bounty to whoever auto-releases a stale escrow', async function () {
+    const { listing, signature } = await signListing({
+      chainIds: [BigInt((await ethers.provider.getNetwork()).chainId)],
+      token: await paymentToken.getAddress(),
+      price: ethers.parseEther('50'),
+      sku: 'SKU-CRANK',
+      seller: await seller.getAddress(),
+      salt: 1n,
+      expiry: futureTimestamp(),
+    });
+
+    await core.connect(admin).grantRole(OPERATOR_ROLE, await admin.getAddress());
+    await expect(escrow.connect(admin).setCrankBounty(101)).to.be.revertedWithCustomError(
+      escrow,
+      'InvalidArgument',
+    );
+    await escrow.connect(admin).setCrankBounty(100);
+
+    await escrow.connect(seller).setMilestoneSchedule(listing.sku, [10000]);
+    await marketplace.connect(buyer).buy(listing, signature, listing.token, 0);
+    await escrow.connect(seller).markShipped(1n);
+
+    const snapshot = await ethers.provider.send('evm_snapshot', []);
+    try {
+      await ethers.provider.send('evm_increaseTime', [Number(await escrow.acceptanceWindow())]);
+      const bounty = listing.price / 100n;
+      await expect(escrow.connect(other).autoReleaseMilestoneOrder(1n))
+        .to.emit(escrow, 'MilestoneOrderAutoReleased')
+        .withArgs(1n, await other.getAddress(), listing.price - bounty, bounty);
+      expect(await paymentToken.balanceOf(await other.getAddress())).to.equal(bounty);
+      expect(await paymentToken.balanceOf(await seller.getAddress())).to.equal(listing.price - bounty);
+    } finally {
+      await ethers.provider.send('evm_revert', [snapshot]);
+    }
+  });
+
+  it('keeps seller sales, volume and lost disputes on-chain', async function () {
+    const { listing, signature } = await signListing({
+      chainIds: [BigInt((await ethers.provider.getNetwork()).chainId)],
+      token: await paymentToken.getAddress(),
+      price: ethers.parseEther('25'),
+      sku: 'SKU-STATS',
+      seller: await seller.getAddress(),
+      salt: 1n,
+      expiry: futureTimestamp(),
+    });
+
+    await escrow.connect(seller).setMilestoneSchedule(listing.sku, [10000]);
+    await marketplace.connect(buyer).buy(listing, signature, listing.token, 0);
+
+    // an escrowed order is not a sale until funds are released to the seller
+    let stats = await marketplace.sellerStats(await seller.getAddress());
+    expect(stats.completedSales).to.equal(0n);
+    expect(await marketplace.sellerVolume(await seller.getAddress(), listing.token)).to.equal(0n);
+
+    await escrow.connect(buyer).openMilestoneDispute(1n);
+    await core.connect(admin).grantRole(ARBITER_ROLE, await admin.getAddress());
+    await escrow.connect(admin).splitMilestoneOrder(1n, 0, false);
+
+    stats = await marketplace.sellerStats(await seller.getAddress());
+    expect(stats.disputesLost).to.equal(1n);
+    expect(stats.completedSales).to.equal(0n);
+  });
+
+  it('funds insurance from a slice of the platform fee and compensates buyers of any sale', async function () {
+    const { listing, signature } = await signListing({
+      chainIds: [BigInt((await ethers.provider.getNetwork()).chainId)],
+      token: await paymentToken.getAddress(),
+      price: ethers.parseEther('100'),
+      sku: 'SKU-INSURED',
+      seller: await seller.getAddress(),
+      salt: 1n,
+      expiry: futureTimestamp(),
+    });
+    const listingHash = await marketplace.hashListing(listing);
+
+    const Fee = await ethers.getContractFactory('FeeProcessor', admin);
+    const fee = (await Fee.deploy(0)) as FeeProcessor;
+    await fee.grantRole(await fee.PROCESSOR_ADMIN_ROLE(), await orchestrator.getAddress());
+    await registry.connect(admin).registerProcessor(await fee.getAddress(), 0);
+    await orchestrator
+      .connect(admin)
+      .configureProcessor(MODULE_ID, 'FeeProcessor', true, ethers.concat(['0x03e8', admin.address]));
+    // 5% of every platform fee goes to the insurance fund
+    await fee.setFeeSplits([admin.address, await insurance.getAddress()], [9500, 500]);
+
+    await marketplace.connect(buyer).buy(listing, signature, listing.token, 0);
+    // 10% fee on 100 tokens = 10, of which 0.5 is the insurance slice
+    expect(await insurance.insuranceFund(listing.token)).to.equal(ethers.parseEther('0.5'));
+    expect(await paymentToken.balanceOf(await seller.getAddress())).to.equal(ethers.parseEther('90'));
+
+    await core.connect(admin).grantRole(ethers.id('GOVERNOR_ROLE'), await admin.getAddress());
+    await core.connect(admin).grantRole(OPERATOR_ROLE, await admin.getAddress());
+    await expect(insurance.connect(admin).setClaimCap(5000)).to.emit(insurance, 'ClaimCapUpdated').withArgs(5000);
+
+    const topUp = ethers.parseEther('20');
+    await paymentToken.mint(await admin.getAddress(), topUp);
+    await paymentToken.connect(admin).approve(await insurance.getAddress(), topUp);
+    await insurance.connect(admin).fundInsurance(listing.token, topUp);
+
+    await expect(
+      insurance
+        .connect(other)
+        .fileInsuranceClaim(
+          listingHash,
+          listing.seller,
+          listing.token,
+          listing.price,
+          ethers.parseEther('10'),
+          ethers.id('loss'),
+        ),
+    ).to.be.revertedWithCustomError(insurance, 'NotFound');
+    await expect(
+      insurance
+        .connect(buyer)
+        .fileInsuranceClaim(
+          listingHash,
+          listing.seller,
+          listing.token,
+          listing.price,
+          ethers.parseEther('51'),
+          ethers.id('loss'),
+        ),
+    ).to.be.revertedWithCustomError(insurance, 'InvalidAmount');
+    await expect(
+      insurance
+        .connect(buyer)
+        .fileInsuranceClaim(
+          listingHash,
+          listing.seller,
+          listing.token,
+          listing.price,
+          ethers.parseEther('30'),
+          ethers.id('loss'),
+        ),
+    )
+      .to.emit(insurance, 'InsuranceClaimFiled')
+      .withArgs(listingHash, await buyer.getAddress(), ethers.parseEther('30'), ethers.id('loss'));
+    await expect(
+      insurance
+        .connect(buyer)
+        .fileInsuranceClaim(
+          listingHash,
+          listing.seller,
+          listing.token,
+          listing.price,
+          ethers.parseEther('1'),
+          ethers.id('loss'),
+        ),
+    ).to.be.revertedWithCustomError(insurance, 'InvalidState');
+
+    await expect(
+      insurance.connect(admin).approveInsuranceClaim(listingHash, ethers.parseEther('30')),
+    ).to.be.revertedWithCustomError(insurance, 'InsufficientBalance');
+
+    const payout = ethers.parseEther('20.5');
+    const buyerBefore = await paymentToken.balanceOf(await buyer.getAddress());
+    await expect(insurance.connect(admin).approveInsuranceClaim(listingHash, payout))
+      .to.emit(insurance, 'InsuranceClaimPaid')
+      .withArgs(listingHash, await buyer.getAddress(), listing.token, payout);
+    expect((await paymentToken.balanceOf(await buyer.getAddress())) - buyerBefore).to.equal(payout);
+    expect(await insurance.insuranceFund(listing.token)).to.equal(0n);
+
+    await expect(
+      insurance.connect(admin).approveInsuranceClaim(listingHash, ethers.parseEther('1')),
+    ).to.be.revertedWithCustomError(insurance, 'InvalidState');
+  });
+
+  it('rejects insurance claims for good and refuses claims on self-purchases', async function () {
+    const sellerAddress = await seller.getAddress();
+    const token = await paymentToken.getAddress();
+    const price = ethers.parseEther('10');
+    const listingFor = async (sku: string) => {
+      const signed = await signListing({
+        chainIds: [BigInt((await ethers.provider.getNetwork()).chainId)],
+        token,
+        price,
+        sku,
+        seller: sellerAddress,
+        salt: 1n,
+        expiry: 0n,
+      });
+      return { ...signed, hash: await marketplace.hashListing(signed.listing) };
+    };
+    await core.connect(admin).grantRole(ethers.id('GOVERNOR_ROLE'), await admin.getAddress());
+    await core.connect(admin).grantRole(OPERATOR_ROLE, await admin.getAddress());
+    await insurance.connect(admin).setClaimCap(5000);
+
+    const sold = await listingFor('SKU-INSURED-REJECTED');
+    await marketplace.connect(buyer).buy(sold.listing, sold.signature, token, 0);
+    await insurance.connect(buyer).fileInsuranceClaim(sold.hash, sellerAddress, token, price, 1n, ethers.id('loss'));
+    await expect(
+      insurance.connect(other).rejectInsuranceClaim(sold.hash, ethers.id('no loss')),
+    ).to.be.revertedWithCustomError(insurance, 'NotOperator');
+    await expect(insurance.connect(admin).rejectInsuranceClaim(sold.hash, ethers.id('no loss')))
+      .to.emit(insurance, 'InsuranceClaimRejected')
+      .withArgs(sold.hash, await buyer.getAddress(), ethers.id('no loss'));
+    await expect(
+      insurance.connect(buyer).fileInsuranceClaim(sold.hash, sellerAddress, token, price, 1n, ethers.id('again')),
+    ).to.be.revertedWithCustomError(insurance, 'InvalidState');
+    await expect(insurance.connect(admin).approveInsuranceClaim(sold.hash, 1n)).to.be.revertedWithCustomError(
+      insurance,
+      'InvalidState',
+    );
+
+    const own = await listingFor('SKU-INSURED-OWN');
+    await paymentToken.mint(sellerAddress, price);
+    await paymentToken.connect(seller).approve(await gateway.getAddress(), price);
+    await marketplace.connect(seller).buy(own.listing, own.signature, token, 0);
+    await expect(
+      insurance.connect(seller).fileInsuranceClaim(own.hash, sellerAddress, token, price, 1n, ethers.id('loss')),
+    ).to.be.revertedWithCustomError(insurance, 'Forbidden');
+  });
+
+  it('keeps the marketplace and its services under the EIP-170 size limit', async function () {
+    const contracts = { marketplace, escrow, auctions, promotions, credit, insurance };
+    for (const [name, contract] of Object.entries(contracts)) {
+      const code = await ethers.provider.getCode(await contract.getAddress());
+      const size = (code.length - 2) / 2;
+      expect(size, `${name} runtime code is ${size} bytes`).to.be.at.most(24576);
+    }
+  });
+
+  it('attaches separately deployed services to factory-created marketplaces', async function () {
+    const coreAddress = await core.getAddress();
+    const Factory = await ethers.getContractFactory('MarketplaceFactory', admin);
+    const factory = (await Factory.deploy(coreAddress, await gateway.getAddress())) as MarketplaceFactory;
+    await core.connect(admin).grantRole(FEATURE_OWNER_ROLE, await factory.getAddress());
+
+    const instance = await factory.connect(admin).createMarketplace.staticCall();
+    await factory.connect(admin).createMarketplace();
+    const instanceId = await (await ethers.getContractAt('Marketplace', instance)).MODULE_ID();
+
+    const deploy = async (name: string, ...args: unknown[]) =>
+      (await (await ethers.getContractFactory(name, admin)).deploy(...args)).getAddress();
+    const services = {
+      MarketplaceEscrow: await deploy('MarketplaceEscrow', coreAddress, instance, instanceId),
+      MarketplaceAuctions: await deploy('MarketplaceAuctions', coreAddress, instance, instanceId),
+      MarketplacePromotions: await deploy('MarketplacePromotions', coreAddress, instance),
+      MarketplaceCredit: await deploy('MarketplaceCredit', instance),
+      MarketplaceInsurance: await deploy('MarketplaceInsurance', coreAddress, instance),
+    };
+
+    // services deployed for another marketplace are rejected
+    await expect(
+      factory
+        .connect(admin)
+        .registerMarketplaceServices(
+          instance,
+          await escrow.getAddress(),
+          ethers.ZeroAddress,
+          ethers.ZeroAddress,
+          ethers.ZeroAddress,
+          ethers.ZeroAddress,
+        ),
+    ).to.be.revertedWithCustomError(factory, 'InvalidAddress');
+    await expect(
+      factory
+        .connect(admin)
+        .registerMarketplaceServices(
+          await marketplace.getAddress(),
+          ethers.ZeroAddress,
+          ethers.ZeroAddress,
+          ethers.ZeroAddress,
+          ethers.ZeroAddress,
+          ethers.ZeroAddress,
+        ),
+    ).to.be.revertedWithCustomError(factory, 'NotFound');
+    await expect(
+      factory
+        .connect(buyer)
+        .registerMarketplaceServices(
+          instance,
+          services.MarketplaceEscrow,
+          services.MarketplaceAuctions,
+          services.MarketplacePromotions,
+          services.MarketplaceCredit,
+          services.MarketplaceInsurance,
+        ),
+    ).to.be.revertedWithCustomError(factory, 'NotFeatureOwner');
+
+    await expect(
+      factory
+        .connect(admin)
+        .registerMarketplaceServices(
+          instance,
+          services.MarketplaceEscrow,
+          services.MarketplaceAuctions,
+          services.MarketplacePromotions,
+          services.MarketplaceCredit,
+          services.MarketplaceInsurance,
+        ),
+    ).to.emit(factory, 'MarketplaceServicesRegistered');
+    for (const [name, address] of Object.entries(services)) {
+      expect(await core.getService(instanceId, name)).to.equal(address);
+    }
+  });
+
+  it('routes the curator royalty for SKUs admitted to a collection', async function () {
+    const { listing, signature } = await signListing({
+      chainIds: [BigInt((await ethers.provider.getNetwork()).chainId)],
+      token: await paymentToken.getAddress(),
+      price: ethers.parseEther('100'),
+      sku: 'SKU-CURATED',
+      seller: await seller.getAddress(),
+      salt: 1n,
+      expiry: futureTimestamp(),
+    });
+
+    await expect(marketplace.connect(other).createCollection(ethers.id('Genesis'), 500))
+      .to.emit(marketplace, 'CollectionCreated')
+      .withArgs(1n, await other.getAddress(), ethers.id('Genesis'), 500);
+
+    const attestation = await marketplace.collectionAttestationHash(1n, await seller.getAddress(), listing.sku);
+    const forged = await buyer.signMessage(ethers.getBytes(attestation));
+    await expect(marketplace.connect(seller).setSkuCollection(listing.sku, 1n, forged)).to.be.revertedWithCustomError(
+      marketplace,
+      'InvalidSignature',
+    );
+
+    const curatorSignature = await other.signMessage(ethers.getBytes(attestation));
+    await expect(marketplace.connect(seller).setSkuCollection(listing.sku, 1n, curatorSignature))
+      .to.emit(marketplace, 'SkuCollectionUpdated')
+      .withArgs(await seller.getAddress(), listing.sku, 1n);
+
+    await core.connect(admin).grantRole(OPERATOR_ROLE, await admin.getAddress());
+    await marketplace.connect(admin).setCollectionVerified(1n, true);
+    expect((await marketplace.collections(1n)).verified).to.equal(true);
+
+    await marketplace.connect(buyer).buy(listing, signature, listing.token, 0);
+    expect(await paymentToken.balanceOf(await other.getAddress())).to.equal(ethers.parseEther('5'));
+    expect(await paymentToken.balanceOf(await seller.getAddress())).to.equal(ethers.parseEther('95'));
+  });
 });
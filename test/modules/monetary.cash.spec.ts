@@ -138,7 +138,7 @@ describe('MonetaryCash', function () {
 
     await expect(
       monetaryCash.connect(caller).activateCashWithSig(1, recipient.address, BigInt(now + 300), signature),
-    ).to.be.revertedWithCustomError(monetaryCash, 'Expired');
+    ).to.be.revertedWithCustomError(monetaryCash, 'DeadlineExpired');
   });
 
   it('allows admin to cancel and refund', async function () {
@@ -1,5 +1,6 @@
 import { expect } from 'chai';
 import { ethers } from '../../hardhat-connection';
+import { anyValue } from '@nomicfoundation/hardhat-ethers-chai-matchers/withArgs';
 import type {
   CoreSystem,
   PlanManager,
@@ -168,4 +169,63 @@ describe('PlanManager', function () {
     const stored = await planManager.getPlan(planHash);
     expect(stored.merchant).to.equal(newMerchant.address);
   });
+
+  it('prices new subscribers at once and holds increases for locked renewals', async function () {
+    const { plan, signature, planHash } = await buildPlan(1n);
+    await planManager.connect(merchant).createPlan(plan, signature, 'uri://price');
+    const newPrice = ethers.parseEther('12');
+
+    await expect(planManager.connect(operator).schedulePriceChange(planHash, newPrice)).to.be.revertedWithCustomError(
+      planManager,
+      'UnauthorizedMerchant',
+    );
+    await expect(planManager.connect(merchant).schedulePriceChange(planHash, newPrice))
+      .to.emit(planManager, 'PlanPriceChangeScheduled')
+      .withArgs(merchant.address, planHash, PLAN_PRICE, newPrice, anyValue);
+
+    const { scheduledAt, effectiveAt } = await planManager.priceChanges(planHash);
+    const latest = await ethers.provider.getBlock('latest');
+    expect(scheduledAt).to.equal(BigInt(latest!.timestamp));
+    expect(effectiveAt).to.equal(scheduledAt + PLAN_PERIOD * 2n);
+    expect(await planManager.getEffectivePrice(planHash)).to.equal(newPrice);
+    expect((await planManager.getPlan(planHash)).price).to.equal(PLAN_PRICE);
+
+    // locked in before the announcement: old price through the notice window
+    expect(await planManager.getRenewalPrice(planHash, PLAN_PRICE, scheduledAt - 1n)).to.equal(PLAN_PRICE);
+    // locked in after it, or never locked: list price
+    expect(await planManager.getRenewalPrice(planHash, PLAN_PRICE, scheduledAt)).to.equal(newPrice);
+    expect(await planManager.getRenewalPrice(planHash, 0, 0)).to.equal(newPrice);
+
+    const snapshot = await ethers.provider.send('evm_snapshot', []);
+    try {
+      await ethers.provider.send('evm_setNextBlockTimestamp', [Number(effectiveAt)]);
+      await ethers.provider.send('evm_mine', []);
+      expect(await planManager.getRenewalPrice(planHash, PLAN_PRICE, scheduledAt - 1n)).to.equal(newPrice);
+    } finally {
+      await ethers.provider.send('evm_revert', [snapshot]);
+    }
+  });
+
+  it('applies price decreases to renewals immediately', async function () {
+    const { plan, signature, planHash } = await buildPlan(1n);
+    await planManager.connect(merchant).createPlan(plan, signature, 'uri://price');
+    const raisedPrice = ethers.parseEther('12');
+    const loweredPrice = ethers.parseEther('8');
+    await planManager.connect(merchant).schedulePriceChange(planHash, raisedPrice);
+
+    await expect(planManager.connect(merchant).schedulePriceChange(planHash, loweredPrice))
+      .to.emit(planManager, 'PlanPriceChangeScheduled')
+      .withArgs(merchant.address, planHash, raisedPrice, loweredPrice, anyValue);
+    const { scheduledAt, effectiveAt } = await planManager.priceChanges(planHash);
+    expect(effectiveAt).to.equal(scheduledAt);
+    expect(await planManager.getEffectivePrice(planHash)).to.equal(loweredPrice);
+    expect(await planManager.getRenewalPrice(planHash, PLAN_PRICE, scheduledAt - 1n)).to.equal(loweredPrice);
+
+    // intro discounts are checked against the list price, not the signed one
+    await expect(planManager.connect(merchant).setPlanIntro(planHash, 0, loweredPrice)).to.be.revertedWithCustomError(
+      planManager,
+      'InvalidPrice',
+    );
+    await planManager.connect(merchant).setPlanIntro(planHash, 0, loweredPrice - 1n);
+  });
 });
@@ -326,6 +326,65 @@ describe('SubscriptionManager (multi-tier)', function () {
       expect(finalState.cancelReason).to.equal(2); // RetryFailed
       expect(await manager.getActivePlan(subscriber.address, merchant.address)).to.equal(ethers.ZeroHash);
     });
+
+    it('schedules retries after the configured delay', async function () {
+      const GOVERNOR_ROLE = ethers.keccak256(ethers.toUtf8Bytes('GOVERNOR_ROLE'));
+      await expect(manager.connect(deployer).setRetryDelay(6 * 60 * 60)).to.be.revertedWithCustomError(
+        manager,
+        'Forbidden',
+      );
+      await core.connect(deployer).grantRole(GOVERNOR_ROLE, deployer.address);
+      await expect(manager.connect(deployer).setRetryDelay(60)).to.be.revertedWithCustomError(
+        manager,
+        'InvalidArgument',
+      );
+      await expect(manager.connect(deployer).setRetryDelay(6 * 60 * 60))
+        .to.emit(manager, 'RetryDelayUpdated')
+        .withArgs(6 * 60 * 60);
+
+      const { plan, signature, planHash } = await createPlan({ tokenOverride: ethers.ZeroAddress, price: PLAN_PRICE });
+      await callSubscribe(subscriber, plan, signature, { value: PLAN_PRICE });
+
+      await manager.connect(automation).markFailedCharge(subscriber.address, planHash);
+      const latest = await ethers.provider.getBlock('latest');
+      const state = await manager.getSubscriptionByPlan(subscriber.address, planHash);
+      expect(state.retryAt).to.equal(BigInt(latest!.timestamp) + 6n * 60n * 60n);
+    });
+  });
+
+  describe('payment standing', function () {
+    it('reports past due subscriptions until a charge succeeds', async function () {
+      const { plan, signature, planHash } = await createPlan();
+      await callSubscribe(subscriber, plan, signature);
+
+      const [standing, missed] = await manager.getStanding(subscriber.address, merchant.address);
+      expect(standing).to.equal(1); // Current
+      expect(missed).to.equal(0n);
+
+      const snapshot = await ethers.provider.send('evm_snapshot', []);
+      try {
+        const state = await manager.getSubscriptionByPlan(subscriber.address, planHash);
+        const retryDelay = await manager.retryDelay();
+        await ethers.provider.send('evm_setNextBlockTimestamp', [
+          Number(state.nextChargeAt + retryDelay) + PLAN_PERIOD_SECONDS,
+        ]);
+        await ethers.provider.send('evm_mine', []);
+
+        const [pastDue, missedPeriods] = await manager.getStanding(subscriber.address, merchant.address);
+        expect(pastDue).to.equal(2); // PastDue
+        expect(missedPeriods).to.equal(2n);
+
+        await manager.connect(automation)['charge(address,bytes32)'](subscriber.address, planHash);
+        const [recovered] = await manager.getStanding(subscriber.address, merchant.address);
+        expect(recovered).to.equal(1);
+      } finally {
+        await ethers.provider.send('evm_revert', [snapshot]);
+      }
+
+      await manager.connect(subscriber).unsubscribe(merchant.address);
+      const [cancelled] = await manager.getStanding(subscriber.address, merchant.address);
+      expect(cancelled).to.equal(0); // None
+    });
   });
 
   describe('operator tools', function () {
@@ -396,4 +455,500 @@ describe('SubscriptionManager (multi-tier)', function () {
       );
     });
   });
+
+  describe('pause and resume', function () {
+    it('freezes the renewal schedule while paused', async function () {
+      const { plan, signature, planHash } = await createPlan();
+      await callSubscribe(subscriber, plan, signature);
+
+      await expect(manager.connect(subscriber).pauseSubscription(merchant.address)).to.be.revertedWithCustomError(
+        manager,
+        'Forbidden',
+      );
+
+      await manager.connect(merchant).setMaxPauseDuration(PLAN_PERIOD_SECONDS);
+      const before = await manager.getSubscriptionByPlan(subscriber.address, planHash);
+
+      await expect(manager.connect(subscriber).pauseSubscription(merchant.address)).to.emit(
+        manager,
+        'SubscriptionPaused',
+      );
+      expect((await manager.getSubscriptionByPlan(subscriber.address, planHash)).status).to.equal(3); // Paused
+
+      await ethers.provider.send('evm_increaseTime', [PLAN_PERIOD_SECONDS / 2]);
+      await ethers.provider.send('evm_mine', []);
+
+      await expect(
+        manager
+          .connect(automation)
+          ['charge(address,bytes32)'](subscriber.address, planHash),
+      ).to.be.revertedWithCustomError(manager, 'InvalidState');
+
+      await expect(manager.connect(subscriber).resumeSubscription(merchant.address)).to.emit(
+        manager,
+        'SubscriptionResumed',
+      );
+
+      const after = await manager.getSubscriptionByPlan(subscriber.address, planHash);
+      expect(after.status).to.equal(1);
+      expect(after.nextChargeAt - before.nextChargeAt).to.be.gte(BigInt(PLAN_PERIOD_SECONDS / 2));
+    });
+
+    it('resumes automatically once the max pause has elapsed', async function () {
+      const { plan, signature, planHash } = await createPlan();
+      await callSubscribe(subscriber, plan, signature);
+
+      await manager.connect(merchant).setMaxPauseDuration(3600);
+      await manager.connect(subscriber).pauseSubscription(merchant.address);
+
+      await ethers.provider.send('evm_increaseTime', [PLAN_PERIOD_SECONDS + 3600]);
+      await ethers.provider.send('evm_mine', []);
+
+      await expect(
+        manager
+          .connect(automation)
+          ['charge(address,bytes32)'](subscriber.address, planHash),
+      )
+        .to.emit(manager, 'SubscriptionResumed')
+        .and.to.emit(manager, 'SubscriptionCharged');
+    });
+  });
+
+  describe('prepaid periods', function () {
+    it('pushes the next charge forward by the prepaid periods', async function () {
+      const { plan, signature, planHash } = await createPlan();
+      await callSubscribe(subscriber, plan, signature);
+      const before = await manager.getSubscriptionByPlan(subscriber.address, planHash);
+
+      await expect(manager.connect(subscriber).prepayPeriods(merchant.address, 3))
+        .to.emit(manager, 'SubscriptionCharged')
+        .withArgs(
+          subscriber.address,
+          planHash,
+          PLAN_PRICE * 3n,
+          before.nextChargeAt + BigInt(PLAN_PERIOD_SECONDS) * 3n,
+        );
+
+      expect(await token.balanceOf(merchant.address)).to.equal(PLAN_PRICE * 4n);
+
+      await ethers.provider.send('evm_increaseTime', [PLAN_PERIOD_SECONDS]);
+      await ethers.provider.send('evm_mine', []);
+
+      await expect(
+        manager
+          .connect(automation)
+          ['charge(address,bytes32)'](subscriber.address, planHash),
+      ).to.be.revertedWithCustomError(manager, 'NotDue');
+    });
+  });
+
+  describe('tier changes', function () {
+    it('charges only the prorated difference when upgrading', async function () {
+      const basic = await createPlan({ salt: 1n });
+      const premium = await createPlan({ salt: 2n, price: PLAN_PRICE + ethers.parseEther('5') });
+      await callSubscribe(subscriber, basic.plan, basic.signature);
+
+      const merchantBefore = await token.balanceOf(merchant.address);
+      await expect(manager.connect(subscriber).changeTier(premium.planHash))
+        .to.emit(manager, 'SubscriptionTierChanged')
+        .withArgs(subscriber.address, basic.planHash, premium.planHash, anyValue, anyValue)
+        .and.to.emit(manager, 'SubscriptionSwitched');
+
+      const charged = (await token.balanceOf(merchant.address)) - merchantBefore;
+      expect(charged).to.be.gte(ethers.parseEther('5'));
+      expect(charged).to.be.lt(ethers.parseEther('5.01'));
+      expect(await manager.getActivePlan(subscriber.address, merchant.address)).to.equal(premium.planHash);
+    });
+
+    it('gives no credit for an unpaid trial period', async function () {
+      const basic = await createPlan({ salt: 1n });
+      const premiumPrice = PLAN_PRICE + ethers.parseEther('5');
+      const premium = await createPlan({ salt: 2n, price: premiumPrice });
+      await planManager.connect(merchant).setPlanIntro(basic.planHash, 7 * 24 * 60 * 60, 0);
+      await callSubscribe(subscriber, basic.plan, basic.signature);
+
+      const merchantBefore = await token.balanceOf(merchant.address);
+      await expect(manager.connect(subscriber).changeTier(premium.planHash))
+        .to.emit(manager, 'SubscriptionTierChanged')
+        .withArgs(subscriber.address, basic.planHash, premium.planHash, premiumPrice, 0n);
+      expect((await token.balanceOf(merchant.address)) - merchantBefore).to.equal(premiumPrice);
+    });
+
+    it('credits the discounted amount actually paid for the current period', async function () {
+      const discounted = ethers.parseEther('2');
+      const basic = await createPlan({ salt: 1n });
+      const premiumPrice = PLAN_PRICE + ethers.parseEther('5');
+      const premium = await createPlan({ salt: 2n, price: premiumPrice });
+      await planManager.connect(merchant).setPlanIntro(basic.planHash, 0, discounted);
+      await callSubscribe(subscriber, basic.plan, basic.signature);
+
+      const merchantBefore = await token.balanceOf(merchant.address);
+      await manager.connect(subscriber).changeTier(premium.planHash);
+
+      const charged = (await token.balanceOf(merchant.address)) - merchantBefore;
+      expect(charged).to.be.gt(premiumPrice - discounted);
+      expect(charged).to.be.lt(premiumPrice - discounted + ethers.parseEther('0.01'));
+    });
+
+    it('charges the current list price of the target plan', async function () {
+      const basic = await createPlan({ salt: 1n });
+      const premium = await createPlan({ salt: 2n, price: PLAN_PRICE + ethers.parseEther('5') });
+      const raisedPrice = PLAN_PRICE * 2n;
+      await callSubscribe(subscriber, basic.plan, basic.signature);
+      await planManager.connect(merchant).schedulePriceChange(premium.planHash, raisedPrice);
+      const { nextChargeAt } = await manager.getSubscriptionByPlan(subscriber.address, basic.planHash);
+
+      const snapshot = await ethers.provider.send('evm_snapshot', []);
+      try {
+        // the basic period has run out by then, so no credit is left
+        await ethers.provider.send('evm_setNextBlockTimestamp', [Number(nextChargeAt)]);
+        await expect(manager.connect(subscriber).changeTier(premium.planHash))
+          .to.emit(manager, 'SubscriptionTierChanged')
+          .withArgs(subscriber.address, basic.planHash, premium.planHash, raisedPrice, 0n);
+        const state = await manager.getSubscriptionByPlan(subscriber.address, premium.planHash);
+        expect(state.periodPrice).to.equal(raisedPrice);
+      } finally {
+        await ethers.provider.send('evm_revert', [snapshot]);
+      }
+    });
+  });
+
+  describe('price changes', function () {
+    it('holds an increase for existing subscribers through the notice window only', async function () {
+      const { plan, signature, planHash } = await createPlan();
+      const raisedPrice = PLAN_PRICE * 2n;
+      await callSubscribe(subscriber, plan, signature);
+      await planManager.connect(merchant).schedulePriceChange(planHash, raisedPrice);
+      const { scheduledAt, effectiveAt } = await planManager.priceChanges(planHash);
+
+      // new subscribers pay the new price straight away
+      await token.mint(secondSubscriber.address, raisedPrice);
+      await token.connect(secondSubscriber).approve(await gateway.getAddress(), raisedPrice);
+      await expect(callSubscribe(secondSubscriber, plan, signature))
+        .to.emit(manager, 'SubscriptionCharged')
+        .withArgs(secondSubscriber.address, planHash, raisedPrice, anyValue);
+
+      const snapshot = await ethers.provider.send('evm_snapshot', []);
+      try {
+        await ethers.provider.send('evm_increaseTime', [PLAN_PERIOD_SECONDS]);
+        await ethers.provider.send('evm_mine', []);
+        await expect(manager.connect(automation)['charge(address,bytes32)'](subscriber.address, planHash))
+          .to.emit(manager, 'SubscriptionCharged')
+          .withArgs(subscriber.address, planHash, PLAN_PRICE, anyValue);
+
+        const { nextChargeAt, priceLockedAt } = await manager.getSubscriptionByPlan(subscriber.address, planHash);
+        expect(priceLockedAt).to.be.lt(scheduledAt);
+        expect(nextChargeAt).to.be.gte(effectiveAt);
+        await ethers.provider.send('evm_setNextBlockTimestamp', [Number(nextChargeAt)]);
+        await expect(manager.connect(automation)['charge(address,bytes32)'](subscriber.address, planHash))
+          .to.emit(manager, 'SubscriptionCharged')
+          .withArgs(subscriber.address, planHash, raisedPrice, anyValue);
+      } finally {
+        await ethers.provider.send('evm_revert', [snapshot]);
+      }
+    });
+
+    it('renews at a lower price immediately', async function () {
+      const { plan, signature, planHash } = await createPlan();
+      const loweredPrice = PLAN_PRICE / 2n;
+      await callSubscribe(subscriber, plan, signature);
+      await planManager.connect(merchant).schedulePriceChange(planHash, loweredPrice);
+
+      const snapshot = await ethers.provider.send('evm_snapshot', []);
+      try {
+        await ethers.provider.send('evm_increaseTime', [PLAN_PERIOD_SECONDS]);
+        await ethers.provider.send('evm_mine', []);
+        await expect(manager.connect(automation)['charge(address,bytes32)'](subscriber.address, planHash))
+          .to.emit(manager, 'SubscriptionCharged')
+          .withArgs(subscriber.address, planHash, loweredPrice, anyValue);
+        const state = await manager.getSubscriptionByPlan(subscriber.address, planHash);
+        expect(state.lockedPrice).to.equal(loweredPrice);
+      } finally {
+        await ethers.provider.send('evm_revert', [snapshot]);
+      }
+    });
+  });
+
+  describe('introductory terms', function () {
+    it('starts a free trial once per merchant', async function () {
+      const trialSeconds = 7 * 24 * 60 * 60;
+      const first = await createPlan({ salt: 1n });
+      const second = await createPlan({ salt: 2n });
+      await planManager.connect(merchant).setPlanIntro(first.planHash, trialSeconds, 0);
+      await planManager.connect(merchant).setPlanIntro(second.planHash, trialSeconds, 0);
+
+      const merchantBefore = await token.balanceOf(merchant.address);
+      await expect(callSubscribe(subscriber, first.plan, first.signature)).to.emit(
+        manager,
+        'SubscriptionTrialStarted',
+      );
+      expect(await token.balanceOf(merchant.address)).to.equal(merchantBefore);
+      expect(await manager.introUsed(subscriber.address, merchant.address)).to.equal(true);
+
+      const state = await manager.getSubscriptionByPlan(subscriber.address, first.planHash);
+      expect(state.nextChargeAt - state.lastChargedAt).to.equal(BigInt(trialSeconds));
+
+      await expect(callSubscribe(subscriber, second.plan, second.signature)).to.emit(manager, 'SubscriptionCharged');
+      expect((await token.balanceOf(merchant.address)) - merchantBefore).to.be.gt(0n);
+    });
+
+    it('charges the discounted first period price', async function () {
+      const discounted = ethers.parseEther('5');
+      const { plan, signature, planHash } = await createPlan();
+      await expect(planManager.connect(merchant).setPlanIntro(planHash, 0, PLAN_PRICE)).to.be.revertedWithCustomError(
+        planManager,
+        'InvalidPrice',
+      );
+      await expect(
+        planManager.connect(secondSubscriber).setPlanIntro(planHash, 0, discounted),
+      ).to.be.revertedWithCustomError(planManager, 'Forbidden');
+      await planManager.connect(merchant).setPlanIntro(planHash, 0, discounted);
+
+      const subscriberBefore = await token.balanceOf(subscriber.address);
+      await expect(callSubscribe(subscriber, plan, signature))
+        .to.emit(manager, 'SubscriptionCharged')
+        .withArgs(subscriber.address, planHash, discounted, anyValue);
+      expect(subscriberBefore - (await token.balanceOf(subscriber.address))).to.equal(discounted);
+    });
+
+    it('discounts the first period with a plan coupon once per user', async function () {
+      const couponId = ethers.id('WELCOME10');
+      const { plan, signature, planHash } = await createPlan();
+      const other = await createPlan({ salt: 2n });
+      const expiresAt = BigInt((await ethers.provider.getBlock('latest'))!.timestamp) + 3600n;
+
+      await expect(
+        planManager.connect(secondSubscriber).createPlanCoupon(planHash, couponId, 1000, 1, expiresAt),
+      ).to.be.revertedWithCustomError(planManager, 'Forbidden');
+      await expect(planManager.connect(merchant).createPlanCoupon(planHash, couponId, 1000, 1, expiresAt))
+        .to.emit(planManager, 'PlanCouponCreated')
+        .withArgs(planHash, couponId, 1000, 1, expiresAt);
+      await expect(
+        planManager.connect(merchant).redeemCoupon(planHash, couponId, subscriber.address),
+      ).to.be.revertedWithCustomError(planManager, 'Unauthorized');
+
+      // Coupons are scoped to the plan they were issued for
+      await expect(
+        manager.connect(subscriber).subscribeWithCoupon(other.plan, other.signature, '0x', couponId),
+      ).to.be.revertedWithCustomError(planManager, 'NotFound');
+
+      const discounted = PLAN_PRICE - PLAN_PRICE / 10n;
+      const subscriberBefore = await token.balanceOf(subscriber.address);
+      await expect(manager.connect(subscriber).subscribeWithCoupon(plan, signature, '0x', couponId))
+        .to.emit(planManager, 'PlanCouponRedeemed')
+        .withArgs(planHash, couponId, subscriber.address)
+        .and.to.emit(manager, 'SubscriptionCharged')
+        .withArgs(subscriber.address, planHash, discounted, anyValue);
+      expect(subscriberBefore - (await token.balanceOf(subscriber.address))).to.equal(discounted);
+      expect((await manager.getSubscriptionByPlan(subscriber.address, planHash)).periodPrice).to.equal(discounted);
+
+      await token.mint(secondSubscriber.address, PLAN_PRICE);
+      await token.connect(secondSubscriber).approve(await gateway.getAddress(), PLAN_PRICE);
+      await expect(
+        manager.connect(secondSubscriber).subscribeWithCoupon(plan, signature, '0x', couponId),
+      ).to.be.revertedWithCustomError(planManager, 'LimitExceeded');
+    });
+  });
+
+  describe('streaming', function () {
+    it('accrues escrowed periods per second and refunds the rest on cancellation', async function () {
+      const { plan, signature, planHash } = await createPlan();
+      await callSubscribe(subscriber, plan, signature);
+      const tokenAddress = await token.getAddress();
+      const deposit = PLAN_PRICE * 2n;
+
+      const before = await manager.getSubscriptionByPlan(subscriber.address, planHash);
+      await expect(manager.connect(subscriber).streamPeriods(merchant.address, 2))
+        .to.emit(manager, 'StreamStarted')
+        .withArgs(
+          subscriber.address,
+          merchant.address,
+          tokenAddress,
+          deposit,
+          before.nextChargeAt,
+          before.nextChargeAt + BigInt(PLAN_PERIOD_SECONDS * 2),
+        );
+      expect(await token.balanceOf(await manager.getAddress())).to.equal(deposit);
+      await expect(manager.connect(subscriber).pauseSubscription(merchant.address)).to.be.revertedWithCustomError(
+        manager,
+        'InvalidState',
+      );
+
+      const snapshot = await ethers.provider.send('evm_snapshot', []);
+      try {
+        await expect(manager.connect(merchant).claimStreamed(subscriber.address)).to.be.revertedWithCustomError(
+          manager,
+          'NothingToWithdraw',
+        );
+
+        // halfway through the first streamed period
+        await ethers.provider.send('evm_setNextBlockTimestamp', [
+          Number(before.nextChargeAt) + PLAN_PERIOD_SECONDS / 2,
+        ]);
+        await expect(manager.connect(merchant).claimStreamed(subscriber.address))
+          .to.emit(manager, 'StreamClaimed')
+          .withArgs(subscriber.address, merchant.address, deposit / 4n);
+
+        // cancel once the first streamed period is over
+        await ethers.provider.send('evm_setNextBlockTimestamp', [Number(before.nextChargeAt) + PLAN_PERIOD_SECONDS]);
+        const subscriberBefore = await token.balanceOf(subscriber.address);
+        await expect(manager.connect(subscriber).unsubscribe(merchant.address))
+          .to.emit(manager, 'StreamClosed')
+          .withArgs(subscriber.address, merchant.address, deposit / 4n, deposit / 2n);
+
+        expect((await token.balanceOf(subscriber.address)) - subscriberBefore).to.equal(deposit / 2n);
+        expect(await manager.merchantRevenue(merchant.address, tokenAddress)).to.equal(PLAN_PRICE * 2n);
+        expect(await token.balanceOf(await manager.getAddress())).to.equal(0n);
+      } finally {
+        await ethers.provider.send('evm_revert', [snapshot]);
+      }
+    });
+
+    it('settles the previous stream when a new one is opened', async function () {
+      const { plan, signature, planHash } = await createPlan();
+      await callSubscribe(subscriber, plan, signature);
+      const tokenAddress = await token.getAddress();
+      const before = await manager.getSubscriptionByPlan(subscriber.address, planHash);
+      await manager.connect(subscriber).streamPeriods(merchant.address, 2);
+
+      const snapshot = await ethers.provider.send('evm_snapshot', []);
+      try {
+        // halfway through the first streamed period the running stream is extended, not blocked
+        const extendedAt = Number(before.nextChargeAt) + PLAN_PERIOD_SECONDS / 2;
+        const streamEnd = before.nextChargeAt + BigInt(PLAN_PERIOD_SECONDS * 3);
+        await ethers.provider.send('evm_setNextBlockTimestamp', [extendedAt]);
+        await expect(manager.connect(subscriber).streamPeriods(merchant.address, 1))
+          .to.emit(manager, 'StreamClaimed')
+          .withArgs(subscriber.address, merchant.address, PLAN_PRICE / 2n)
+          .and.to.emit(manager, 'StreamStarted')
+          .withArgs(subscriber.address, merchant.address, tokenAddress, (PLAN_PRICE * 5n) / 2n, extendedAt, streamEnd);
+
+        // once the stream has run out, an unclaimed stream no longer blocks a new one
+        await ethers.provider.send('evm_setNextBlockTimestamp', [Number(streamEnd) + 10]);
+        const merchantBefore = await token.balanceOf(merchant.address);
+        await expect(manager.connect(subscriber).streamPeriods(merchant.address, 1))
+          .to.emit(manager, 'StreamClaimed')
+          .withArgs(subscriber.address, merchant.address, (PLAN_PRICE * 5n) / 2n)
+          .and.to.emit(manager, 'StreamStarted')
+          .withArgs(subscriber.address, merchant.address, tokenAddress, PLAN_PRICE, Number(streamEnd) + 10, anyValue);
+        expect((await token.balanceOf(merchant.address)) - merchantBefore).to.equal((PLAN_PRICE * 5n) / 2n);
+        expect(await manager.merchantRevenue(merchant.address, tokenAddress)).to.equal(PLAN_PRICE * 4n);
+        expect(await token.balanceOf(await manager.getAddress())).to.equal(PLAN_PRICE);
+      } finally {
+        await ethers.provider.send('evm_revert', [snapshot]);
+      }
+    });
+
+    it('closes the stream when the subscription fails for good', async function () {
+      const { plan, signature, planHash } = await createPlan();
+      await callSubscribe(subscriber, plan, signature);
+      await manager.connect(subscriber).streamPeriods(merchant.address, 2);
+      const subscriberBefore = await token.balanceOf(subscriber.address);
+
+      await manager.connect(automation).markFailedCharge(subscriber.address, planHash);
+      await expect(manager.connect(automation).markFailedCharge(subscriber.address, planHash))
+        .to.emit(manager, 'SubscriptionFailedFinal')
+        .withArgs(subscriber.address, planHash, 2)
+        .and.to.emit(manager, 'StreamClosed')
+        .withArgs(subscriber.address, merchant.address, 0n, PLAN_PRICE * 2n);
+
+      expect((await token.balanceOf(subscriber.address)) - subscriberBefore).to.equal(PLAN_PRICE * 2n);
+      expect(await token.balanceOf(await manager.getAddress())).to.equal(0n);
+      expect((await manager.getSubscriptionByPlan(subscriber.address, planHash)).cancelReason).to.equal(2);
+    });
+
+    it('defers payouts a recipient rejects instead of blocking the stream refund', async function () {
+      const { plan, signature, planHash } = await createPlan({ tokenOverride: ethers.ZeroAddress, price: PLAN_PRICE });
+      await callSubscribe(subscriber, plan, signature, { value: PLAN_PRICE });
+      // the token contract has no receive function and rejects native payouts
+      const rejecting = await token.getAddress();
+      await planManager.connect(merchant).setRevenueShares(planHash, [{ recipient: rejecting, bps: 2000 }]);
+      const before = await manager.getSubscriptionByPlan(subscriber.address, planHash);
+      await manager.connect(subscriber).streamPeriods(merchant.address, 2, { value: PLAN_PRICE * 2n });
+
+      const snapshot = await ethers.provider.send('evm_snapshot', []);
+      try {
+        await ethers.provider.send('evm_setNextBlockTimestamp', [Number(before.nextChargeAt) + PLAN_PERIOD_SECONDS]);
+        await expect(manager.connect(subscriber).unsubscribe(merchant.address))
+          .to.emit(manager, 'PayoutDeferred')
+          .withArgs(rejecting, ethers.ZeroAddress, PLAN_PRICE / 5n)
+          .and.to.emit(manager, 'StreamClosed')
+          .withArgs(subscriber.address, merchant.address, PLAN_PRICE, PLAN_PRICE);
+        expect(await manager.pendingPayouts(rejecting, ethers.ZeroAddress)).to.equal(PLAN_PRICE / 5n);
+
+        await expect(manager.connect(merchant).withdrawPayout(ethers.ZeroAddress)).to.be.revertedWithCustomError(
+          manager,
+          'NothingToWithdraw',
+        );
+      } finally {
+        await ethers.provider.send('evm_revert', [snapshot]);
+      }
+    });
+
+    it('splits streamed payouts with the plan revenue share recipients', async function () {
+      const { plan, signature, planHash } = await createPlan();
+      await callSubscribe(subscriber, plan, signature);
+      await planManager.connect(merchant).setRevenueShares(planHash, [{ recipient: operator.address, bps: 2000 }]);
+      const tokenAddress = await token.getAddress();
+      const before = await manager.getSubscriptionByPlan(subscriber.address, planHash);
+      await manager.connect(subscriber).streamPeriods(merchant.address, 2);
+
+      const snapshot = await ethers.provider.send('evm_snapshot', []);
+      try {
+        await ethers.provider.send('evm_setNextBlockTimestamp', [Number(before.nextChargeAt) + PLAN_PERIOD_SECONDS]);
+        await expect(manager.connect(merchant).claimStreamed(subscriber.address))
+          .to.emit(manager, 'RevenueSharePaid')
+          .withArgs(planHash, operator.address, tokenAddress, PLAN_PRICE / 5n);
+        expect(await token.balanceOf(operator.address)).to.equal(PLAN_PRICE / 5n);
+
+        await ethers.provider.send('evm_setNextBlockTimestamp', [
+          Number(before.nextChargeAt) + (PLAN_PERIOD_SECONDS * 3) / 2,
+        ]);
+        await expect(manager.connect(subscriber).unsubscribe(merchant.address))
+          .to.emit(manager, 'RevenueSharePaid')
+          .withArgs(planHash, operator.address, tokenAddress, PLAN_PRICE / 10n);
+        expect(await token.balanceOf(operator.address)).to.equal((PLAN_PRICE * 3n) / 10n);
+        expect(await manager.merchantRevenue(merchant.address, tokenAddress)).to.equal((PLAN_PRICE * 5n) / 2n);
+      } finally {
+        await ethers.provider.send('evm_revert', [snapshot]);
+      }
+    });
+  });
+
+  describe('merchant revenue', function () {
+    it('accumulates net revenue per merchant and token', async function () {
+      const { plan, signature } = await createPlan();
+      await callSubscribe(subscriber, plan, signature);
+
+      expect(await manager.merchantRevenue(merchant.address, await token.getAddress())).to.equal(PLAN_PRICE);
+    });
+
+    it('splits each payment with the plan revenue share recipients', async function () {
+      const { plan, signature, planHash } = await createPlan();
+      const shares = [
+        { recipient: operator.address, bps: 2000 },
+        { recipient: secondSubscriber.address, bps: 3333 },
+      ];
+      const oversubscribed = [...shares, { recipient: operator.address, bps: 5000 }];
+      await expect(planManager.connect(subscriber).setRevenueShares(planHash, shares)).to.be.revertedWithCustomError(
+        planManager,
+        'UnauthorizedMerchant',
+      );
+      await expect(
+        planManager.connect(merchant).setRevenueShares(planHash, oversubscribed),
+      ).to.be.revertedWithCustomError(planManager, 'InvalidParameters');
+      await planManager.connect(merchant).setRevenueShares(planHash, shares);
+
+      const operatorShare = (PLAN_PRICE * 2000n) / 10_000n;
+      const collaboratorShare = (PLAN_PRICE * 3333n) / 10_000n;
+      await expect(callSubscribe(subscriber, plan, signature))
+        .to.emit(manager, 'RevenueSharePaid')
+        .withArgs(planHash, operator.address, await token.getAddress(), operatorShare);
+
+      expect(await token.balanceOf(operator.address)).to.equal(operatorShare);
+      expect(await token.balanceOf(secondSubscriber.address)).to.equal(collaboratorShare);
+      expect(await token.balanceOf(merchant.address)).to.equal(PLAN_PRICE - operatorShare - collaboratorShare);
+      expect(await manager.merchantRevenue(merchant.address, await token.getAddress())).to.equal(PLAN_PRICE);
+    });
+  });
 });
@@ -5,10 +5,13 @@ import type {
   DiscountProcessor,
   FeeProcessor,
   GatewayCaller,
+  MockPriceFeed,
+  OracleProcessor,
   PaymentGateway,
   PaymentOrchestrator,
   ProcessorRegistry,
   TestToken,
+  TokenFilterProcessor,
 } from '../../typechain-types';
 import { deployGatewayStack, deployTestToken } from '../shared/paymentStack';
 
@@ -139,6 +142,67 @@ describe('PaymentGateway', function () {
       [payer, moduleCaller, feeCollector],
       [-expectedPayerLoss, expectedNet, expectedFee],
     );
+    expect(await gateway.feesCollected(MODULE_ID, await token.getAddress())).to.equal(expectedFee);
+  });
+
+  it('pays the referrer share of the fee named in payment metadata', async function () {
+    const Fee = await ethers.getContractFactory('FeeProcessor', deployer);
+    const fee = (await Fee.deploy(0)) as FeeProcessor;
+    await fee.grantRole(await fee.PROCESSOR_ADMIN_ROLE(), await orchestrator.getAddress());
+    await registry.connect(deployer).registerProcessor(await fee.getAddress(), 0);
+
+    const feeConfig = ethers.concat([ethers.getBytes('0x03e8'), ethers.getBytes(feeCollector.address)]);
+    await orchestrator.connect(deployer).configureProcessor(MODULE_ID, 'FeeProcessor', true, feeConfig);
+    await expect(fee.setReferralShare(2500)).to.emit(fee, 'ReferralShareUpdated').withArgs(0, 2500);
+
+    await token.connect(payer).approve(await gateway.getAddress(), ERC20_AMOUNT);
+    const metadata = ethers.AbiCoder.defaultAbiCoder().encode(['address'], [outsider.address]);
+    const tx = gateway.connect(moduleCaller).processPayment(MODULE_ID, token, payer.address, ERC20_AMOUNT, metadata);
+
+    const totalFee = ERC20_AMOUNT / 10n;
+    const referral = totalFee / 4n;
+    await expect(tx).to.changeTokenBalances(
+      ethers,
+      token,
+      [payer, moduleCaller, feeCollector, outsider],
+      [-ERC20_AMOUNT, ERC20_AMOUNT - totalFee, totalFee - referral, referral],
+    );
+
+    // a word with dirty upper bits is not an address and earns no referral share
+    await token.connect(payer).approve(await gateway.getAddress(), ERC20_AMOUNT);
+    const dirty = ethers.toBeHex((1n << 200n) | BigInt(outsider.address), 32);
+    await expect(
+      gateway.connect(moduleCaller).processPayment(MODULE_ID, token, payer.address, ERC20_AMOUNT, dirty),
+    ).to.changeTokenBalances(ethers, token, [feeCollector, outsider], [totalFee, 0n]);
+  });
+
+  it('splits the platform fee among configured recipients', async function () {
+    const Fee = await ethers.getContractFactory('FeeProcessor', deployer);
+    const fee = (await Fee.deploy(0)) as FeeProcessor;
+    await fee.grantRole(await fee.PROCESSOR_ADMIN_ROLE(), await orchestrator.getAddress());
+    await registry.connect(deployer).registerProcessor(await fee.getAddress(), 0);
+    await orchestrator
+      .connect(deployer)
+      .configureProcessor(MODULE_ID, 'FeeProcessor', true, ethers.concat(['0x03e8', feeCollector.address]));
+
+    await expect(fee.setFeeSplits([feeCollector.address, outsider.address], [6000, 3000])).to.be.revertedWith(
+      'FeeProcessor: shares must total 10000',
+    );
+    await expect(fee.setFeeSplits([feeCollector.address, outsider.address], [7000, 3000])).to.emit(
+      fee,
+      'FeeSplitsUpdated',
+    );
+
+    await token.connect(payer).approve(await gateway.getAddress(), ERC20_AMOUNT);
+    const tx = gateway.connect(moduleCaller).processPayment(MODULE_ID, token, payer.address, ERC20_AMOUNT, '0x');
+
+    // 10% fee on 500 tokens = 50, split 35 / 15
+    await expect(tx).to.changeTokenBalances(
+      ethers,
+      token,
+      [payer, moduleCaller, feeCollector, outsider],
+      [-ERC20_AMOUNT, ethers.parseUnits('450', 18), ethers.parseUnits('35', 18), ethers.parseUnits('15', 18)],
+    );
   });
 
   it('maintains unique payment ids inside a single call frame', async function () {
@@ -178,7 +242,9 @@ describe('PaymentGateway', function () {
         .processPayment(MODULE_ID, ethers.ZeroAddress, moduleCaller.address, PAYMENT_AMOUNT, '0x', {
           value: PAYMENT_AMOUNT - 1n,
         }),
-    ).to.be.revertedWithCustomError(gateway, 'InsufficientBalance');
+    )
+      .to.be.revertedWithCustomError(gateway, 'InsufficientBalance')
+      .withArgs(PAYMENT_AMOUNT, PAYMENT_AMOUNT - 1n);
   });
 
   it('reverts on zero amount requests', async function () {
@@ -186,4 +252,203 @@ describe('PaymentGateway', function () {
       gateway.connect(moduleCaller).processPayment(MODULE_ID, ethers.ZeroAddress, moduleCaller.address, 0, '0x'),
     ).to.be.revertedWithCustomError(gateway, 'InvalidAmount');
   });
+
+  describe('FeeProcessor rounding', function () {
+    let fee: FeeProcessor;
+
+    beforeEach(async function () {
+      const Fee = await ethers.getContractFactory('FeeProcessor', deployer);
+      fee = (await Fee.deploy(250)) as FeeProcessor;
+    });
+
+    it('floors by default', async function () {
+      expect(await fee.computeFee(0n)).to.equal(0n);
+      expect(await fee.computeFee(39n)).to.equal(0n);
+      expect(await fee.computeFee(40n)).to.equal(1n);
+      expect(await fee.computeFee(79n)).to.equal(1n);
+      expect(await fee.computeFee(10000n)).to.equal(250n);
+    });
+
+    it('rounds up in ceil mode', async function () {
+      await fee.setRoundingMode(1);
+      expect(await fee.computeFee(0n)).to.equal(0n);
+      expect(await fee.computeFee(1n)).to.equal(1n);
+      expect(await fee.computeFee(40n)).to.equal(1n);
+      expect(await fee.computeFee(41n)).to.equal(2n);
+      expect(await fee.computeFee(10000n)).to.equal(250n);
+    });
+
+    it('rounds half up', async function () {
+      await fee.setRoundingMode(2);
+      expect(await fee.computeFee(19n)).to.equal(0n);
+      expect(await fee.computeFee(20n)).to.equal(1n);
+      expect(await fee.computeFee(59n)).to.equal(1n);
+      expect(await fee.computeFee(60n)).to.equal(2n);
+    });
+
+    it('applies the per-token minimum fee capped at the payment amount', async function () {
+      const tokenAddress = await token.getAddress();
+      await expect(fee.setMinFee(tokenAddress, 5)).to.emit(fee, 'MinFeeUpdated').withArgs(tokenAddress, 5n);
+      expect(await fee.computeFeeForToken(tokenAddress, 0n)).to.equal(0n);
+      expect(await fee.computeFeeForToken(tokenAddress, 3n)).to.equal(3n);
+      expect(await fee.computeFeeForToken(tokenAddress, 100n)).to.equal(5n);
+      expect(await fee.computeFeeForToken(tokenAddress, 10000n)).to.equal(250n);
+
+      // other tokens keep their own (unset) minimum
+      expect(await fee.computeFeeForToken(ethers.ZeroAddress, 100n)).to.equal(2n);
+    });
+
+    it('skips the minimum fee for tokens with a zero percent fee', async function () {
+      const tokenAddress = await token.getAddress();
+      await fee.setMinFee(tokenAddress, 5);
+      await fee.setTokenFee(tokenAddress, true, 0);
+      expect(await fee.computeFeeForToken(tokenAddress, 100n)).to.equal(0n);
+    });
+
+    it('emits an event when the fee percent is reconfigured', async function () {
+      await expect(fee.configure(MODULE_ID, '0x01f4')).to.emit(fee, 'FeePercentUpdated').withArgs(250, 500);
+    });
+
+    it('applies per-token fee overrides', async function () {
+      const tokenAddress = await token.getAddress();
+      await expect(fee.setTokenFee(tokenAddress, true, 100))
+        .to.emit(fee, 'TokenFeeUpdated')
+        .withArgs(tokenAddress, true, 100);
+
+      expect(await fee.computeFeeForToken(tokenAddress, 10000n)).to.equal(100n);
+      expect(await fee.computeFeeForToken(ethers.ZeroAddress, 10000n)).to.equal(250n);
+
+      await fee.setTokenFee(tokenAddress, false, 100);
+      expect(await fee.computeFeeForToken(tokenAddress, 10000n)).to.equal(250n);
+      await expect(fee.setTokenFee(tokenAddress, true, 10001)).to.be.revertedWith('FeeProcessor: fee percent too high');
+    });
+
+    it('restricts policy updates to processor admins', async function () {
+      await expect(fee.connect(outsider).setRoundingMode(1)).to.be.revertedWithCustomError(
+        fee,
+        'AccessControlUnauthorizedAccount',
+      );
+    });
+  });
+
+  describe('OracleProcessor', function () {
+    const USD = '0x0000000000000000000000000000000000000348';
+    const HEARTBEAT = 3600;
+    let oracle: OracleProcessor;
+    let ethFeed: MockPriceFeed;
+
+    beforeEach(async function () {
+      const Oracle = await ethers.getContractFactory('OracleProcessor', deployer);
+      oracle = (await Oracle.deploy()) as OracleProcessor;
+
+      const Feed = await ethers.getContractFactory('MockPriceFeed', deployer);
+      ethFeed = (await Feed.deploy(8, 2_000n * 10n ** 8n)) as MockPriceFeed;
+      const tokenFeed = (await Feed.deploy(8, 10n ** 8n)) as MockPriceFeed;
+
+      await oracle.setPriceFeed(ethers.ZeroAddress, await ethFeed.getAddress(), 18, HEARTBEAT);
+      await oracle.setPriceFeed(await token.getAddress(), await tokenFeed.getAddress(), 18, HEARTBEAT);
+    });
+
+    it('converts USD prices into native and token amounts', async function () {
+      const fiftyUsd = 50n * 10n ** 8n;
+      expect(await oracle.convertAmount(MODULE_ID, USD, ethers.ZeroAddress, fiftyUsd)).to.equal(
+        ethers.parseEther('0.025'),
+      );
+      expect(await oracle.convertAmount(MODULE_ID, USD, await token.getAddress(), fiftyUsd)).to.equal(
+        ethers.parseEther('50'),
+      );
+      expect(
+        await oracle.convertAmount(MODULE_ID, ethers.ZeroAddress, await token.getAddress(), ethers.parseEther('1')),
+      ).to.equal(ethers.parseEther('2000'));
+      expect(await oracle.isPairSupported(MODULE_ID, USD, ethers.ZeroAddress)).to.equal(true);
+      expect(await oracle.isPairSupported(MODULE_ID, USD, outsider.address)).to.equal(false);
+    });
+
+    it('rejects stale, non-positive and missing feeds', async function () {
+      const latest = await ethers.provider.getBlock('latest');
+      await ethFeed.setAnswer(2_000n * 10n ** 8n, BigInt(latest!.timestamp) - BigInt(HEARTBEAT) - 1n);
+      await expect(oracle.convertAmount(MODULE_ID, USD, ethers.ZeroAddress, 100n)).to.be.revertedWithCustomError(
+        oracle,
+        'StalePrice',
+      );
+
+      await ethFeed.setAnswer(0n, BigInt(latest!.timestamp));
+      await expect(oracle.convertAmount(MODULE_ID, USD, ethers.ZeroAddress, 100n)).to.be.revertedWithCustomError(
+        oracle,
+        'InvalidPrice',
+      );
+
+      await expect(oracle.convertAmount(MODULE_ID, USD, outsider.address, 100n)).to.be.revertedWithCustomError(
+        oracle,
+        'PriceFeedNotFound',
+      );
+      await expect(
+        oracle.connect(outsider).setPriceFeed(outsider.address, await ethFeed.getAddress(), 18, HEARTBEAT),
+      ).to.be.revertedWithCustomError(oracle, 'AccessControlUnauthorizedAccount');
+      await expect(
+        oracle.setPriceFeed(outsider.address, await ethFeed.getAddress(), 37, HEARTBEAT),
+      ).to.be.revertedWithCustomError(oracle, 'InvalidDecimals');
+    });
+
+    it('falls back to 1:1 through the gateway unless the module opts into strict conversion', async function () {
+      await registry.connect(deployer).registerProcessor(await oracle.getAddress(), 0);
+      await orchestrator.connect(deployer).configureProcessor(MODULE_ID, 'PriceOracle', true, '0x');
+
+      const fiftyUsd = 50n * 10n ** 8n;
+      expect(await gateway.convertAmount(MODULE_ID, USD, ethers.ZeroAddress, fiftyUsd)).to.equal(
+        ethers.parseEther('0.025'),
+      );
+
+      const latest = await ethers.provider.getBlock('latest');
+      await ethFeed.setAnswer(2_000n * 10n ** 8n, BigInt(latest!.timestamp) - BigInt(HEARTBEAT) - 1n);
+      expect(await gateway.convertAmount(MODULE_ID, USD, ethers.ZeroAddress, fiftyUsd)).to.equal(fiftyUsd);
+
+      await expect(
+        orchestrator.connect(outsider).setStrictConversion(MODULE_ID, true),
+      ).to.be.revertedWithCustomError(orchestrator, 'AccessControlUnauthorizedAccount');
+      await expect(orchestrator.connect(deployer).setStrictConversion(MODULE_ID, true))
+        .to.emit(orchestrator, 'StrictConversionUpdated')
+        .withArgs(MODULE_ID, true);
+      await expect(gateway.convertAmount(MODULE_ID, USD, ethers.ZeroAddress, fiftyUsd)).to.be.revertedWithCustomError(
+        oracle,
+        'StalePrice',
+      );
+    });
+  });
+
+  describe('TokenFilterProcessor', function () {
+    it('adds and removes single tokens without rewriting the list', async function () {
+      const Filter = await ethers.getContractFactory('TokenFilterProcessor', deployer);
+      const filter = (await Filter.deploy()) as TokenFilterProcessor;
+      const tokenAddress = await token.getAddress();
+
+      await filter.configure(MODULE_ID, ethers.concat([tokenAddress, outsider.address]));
+      await expect(filter.setTokenAllowed(MODULE_ID, feeCollector.address, true))
+        .to.emit(filter, 'TokenAllowanceUpdated')
+        .withArgs(MODULE_ID, feeCollector.address, true);
+
+      await filter.setTokenAllowed(MODULE_ID, tokenAddress, false);
+      expect(await filter.isTokenAllowed(MODULE_ID, tokenAddress)).to.equal(false);
+      expect(await filter.getAllowedTokens(MODULE_ID)).to.deep.equal([feeCollector.address, outsider.address]);
+
+      await expect(
+        filter.connect(outsider).setTokenAllowed(MODULE_ID, tokenAddress, true),
+      ).to.be.revertedWithCustomError(filter, 'AccessControlUnauthorizedAccount');
+    });
+
+    it('stores per-token minimum amounts', async function () {
+      const Filter = await ethers.getContractFactory('TokenFilterProcessor', deployer);
+      const filter = (await Filter.deploy()) as TokenFilterProcessor;
+      const tokenAddress = await token.getAddress();
+
+      await expect(filter.setMinAmount(MODULE_ID, tokenAddress, 1000n))
+        .to.emit(filter, 'MinAmountUpdated')
+        .withArgs(MODULE_ID, tokenAddress, 1000n);
+      expect(await filter.minAmounts(MODULE_ID, tokenAddress)).to.equal(1000n);
+
+      await expect(
+        filter.connect(outsider).setMinAmount(MODULE_ID, tokenAddress, 0n),
+      ).to.be.revertedWithCustomError(filter, 'AccessControlUnauthorizedAccount');
+    });
+  });
 });